@@ -2,7 +2,8 @@ use seed::{prelude::*, *};
 
 use crate::{Msg, StateApp};
 
-pub fn login() -> Node<Msg> {
+/// `totp_required` adds the field of the code, once the server asked it
+pub fn login(totp_required: bool) -> Node<Msg> {
     div![
         h1![C!["title"], "Welcome on your OpenCloud Server"],
         form![
@@ -19,6 +20,15 @@ pub fn login() -> Node<Msg> {
                     crate::InputType::Password
                 ))
             ],
+            if totp_required {
+                input![
+                    C!["input mt-2"],
+                    attrs! {At::Type => "text", At::Id => "login-code", At::Placeholder => "Code of the authenticator or recovery code", At::AutoComplete => "one-time-code"},
+                    input_ev(Ev::Input, |e| Msg::InputChange(e, crate::InputType::Code))
+                ]
+            } else {
+                empty![]
+            },
         ],
         div![
             C!["level mt-2"],
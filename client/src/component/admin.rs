@@ -0,0 +1,168 @@
+use crate::component::footer::human_size;
+use crate::{Msg, StateApp};
+use seed::{prelude::*, *};
+use shared::{InstanceStats, LogItem, UserItem};
+
+fn usage_of(user: &UserItem) -> String {
+    match user.quota_bytes {
+        Some(quota) => format!("{} / {}", human_size(user.bytes), human_size(quota)),
+        None => human_size(user.bytes),
+    }
+}
+
+fn stat(title: &str, value: String) -> Node<Msg> {
+    div![
+        C!["level-item has-text-centered"],
+        div![p![C!["heading"], title], p![C!["title is-5"], value]]
+    ]
+}
+
+fn stats_view(stats: &Option<InstanceStats>) -> Node<Msg> {
+    match stats {
+        Some(stats) => nav![
+            C!["level box"],
+            stat(
+                "Users",
+                format!("{} ({} disabled)", stats.users, stats.disabled_users)
+            ),
+            stat("Groups", stats.groups.to_string()),
+            stat("Storage", human_size(stats.bytes)),
+            stat("Files", stats.files.to_string()),
+            stat("Trash", human_size(stats.trash_bytes)),
+            stat("Versions", human_size(stats.versions_bytes)),
+        ],
+        None => empty![],
+    }
+}
+
+fn user_table(users: &[UserItem]) -> Node<Msg> {
+    let mut rows = vec![];
+    for user in users {
+        let (id, disabled) = (user.id, user.disabled);
+        let (reset_name, delete_name) = (user.name.clone(), user.name.clone());
+        let totp_name = user.name.clone();
+        rows.push(tr![
+            th![&user.name],
+            th![&user.role],
+            th![if user.disabled {
+                "Disabled"
+            } else if user.must_reset {
+                "Password reset"
+            } else {
+                "Active"
+            }],
+            th![usage_of(user)],
+            th![user.files.to_string()],
+            th![user
+                .last_login
+                .clone()
+                .unwrap_or_else(|| "Never".to_string())],
+            th![
+                button![
+                    C!["button mr-2 is-link"],
+                    if disabled { "Enable" } else { "Disable" },
+                    ev(Ev::Click, move |_| Msg::CallSetDisabled(id, !disabled))
+                ],
+                button![
+                    C!["button mr-2 is-link"],
+                    "Reset password",
+                    ev(Ev::Click, move |_| Msg::CallResetPassword(id, reset_name))
+                ],
+                if user.totp {
+                    button![
+                        C!["button mr-2 is-link"],
+                        "Reset 2FA",
+                        ev(Ev::Click, move |_| Msg::CallResetTotp(id, totp_name))
+                    ]
+                } else {
+                    empty![]
+                },
+                button![
+                    C!["button is-danger"],
+                    "Delete",
+                    ev(Ev::Click, move |_| Msg::CallDeleteUser(id, delete_name))
+                ],
+            ]
+        ]);
+    }
+    table![
+        C!["table is-hoverable is-fullwidth"],
+        thead![tr![
+            th!["Name"],
+            th!["Role"],
+            th!["State"],
+            th!["Storage"],
+            th!["Files"],
+            th!["Last login"],
+            th![""],
+        ]],
+        tbody![rows],
+    ]
+}
+
+fn log_table(logs: &[LogItem]) -> Node<Msg> {
+    let mut rows = vec![];
+    for log in logs {
+        rows.push(tr![
+            th![&log.date],
+            th![&log.user],
+            th![
+                &log.action,
+                if log.success {
+                    empty![]
+                } else {
+                    span![C!["tag is-danger ml-2"], "Failed"]
+                }
+            ],
+            th![match &log.destination {
+                Some(to) => format!("{} → {}", log.path.clone().unwrap_or_default(), to),
+                None => log.path.clone().unwrap_or_default(),
+            }],
+            th![if log.owner == log.user {
+                String::new()
+            } else {
+                log.owner.clone()
+            }],
+            th![log.ip.clone().unwrap_or_default()],
+        ]);
+    }
+    table![
+        C!["table is-hoverable is-fullwidth is-narrow"],
+        thead![tr![
+            th!["Date"],
+            th!["User"],
+            th!["Action"],
+            th!["File"],
+            th!["Owner of the file"],
+            th!["IP"],
+        ]],
+        tbody![rows],
+    ]
+}
+
+pub fn admin_view(
+    users: &[UserItem],
+    logs: &[LogItem],
+    stats: &Option<InstanceStats>,
+) -> Node<Msg> {
+    div![
+        div![
+            C!["buttons"],
+            button![
+                C!["button is-link"],
+                "Back to files",
+                ev(Ev::Click, |_| Msg::ChangeState(StateApp::Logged))
+            ],
+            button![
+                C!["button is-link"],
+                "New user",
+                ev(Ev::Click, |_| Msg::CallCreateUser)
+            ],
+        ],
+        stats_view(stats),
+        h2![C!["title is-4"], "Users"],
+        user_table(users),
+        h2![C!["title is-4"], "Activity"],
+        log_table(logs),
+    ]
+}
@@ -3,14 +3,27 @@ use crate::Msg;
 use seed::{prelude::*, *};
 use shared::Folder;
 
-pub fn folder_list(mut content: Vec<Folder>, url: String) -> Node<Msg> {
+pub fn folder_list(mut content: Vec<Folder>, url: String, selected: &[String]) -> Node<Msg> {
     content.sort();
     let mut folder_list = vec![];
     for t in content {
         let name = t.clone().name;
         let path = format!("{}{}", url.clone(), name.clone());
         let path_download = format!("{}{}", url.clone(), name.clone());
+        let path_select = format!("{}{}", url.clone(), name.clone());
+        let path_rename = format!("{}{}", url.clone(), name.clone());
+        let path_move = format!("{}{}", url.clone(), name.clone());
+        let path_share = format!("{}{}", url.clone(), name.clone());
+        let path_grant = format!("{}{}", url.clone(), name.clone());
+        let is_folder = t.ftype == *"Folder";
         folder_list.push(tr![
+            th![input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => selected.contains(&path_select).as_at_value()
+                },
+                ev(Ev::Change, move |_| Msg::ToggleSelect(path_select))
+            ]],
             th![if t.ftype == *"Folder" {
                 img![attrs! {At::Src => "/pkg/obj/folder.svg"}]
             } else {
@@ -35,29 +48,66 @@ pub fn folder_list(mut content: Vec<Folder>, url: String) -> Node<Msg> {
                     ev(Ev::Click, move |_| Msg::CallDelete(path))
                 ],
                 button![
-                    C!["button is-link"],
+                    C!["button mr-2 is-link"],
                     "Download",
                     ev(Ev::Click, move |_| Msg::CallDownload(path_download))
                 ],
+                button![
+                    C!["button mr-2 is-link"],
+                    "Rename",
+                    ev(Ev::Click, move |_| Msg::CallRename(path_rename))
+                ],
+                button![
+                    C!["button mr-2 is-link"],
+                    "Move",
+                    ev(Ev::Click, move |_| Msg::CallMove(path_move))
+                ],
+                button![
+                    C!["button mr-2 is-link"],
+                    "Share",
+                    ev(Ev::Click, move |_| Msg::CallShare(path_share))
+                ],
+                if is_folder {
+                    button![
+                        C!["button is-link"],
+                        "Give access",
+                        ev(Ev::Click, move |_| Msg::CallGrant(path_grant))
+                    ]
+                } else {
+                    empty![]
+                },
             ]
         ])
     }
-    div![table![
-        C!["table is-hoverable is-fullwidth"],
-        thead![tr![th![""], th!["Name"], th!["Type"],],],
-        tbody![
-            tr![
-                th![],
-                th![a![
-                    "..",
-                    ev(Ev::Click, move |_| Msg::ChangeRoute(
-                        "".to_string(),
-                        ChangeRouteType::Remove
-                    ))
-                ]],
-                th!["Folder"],
-            ],
-            folder_list,
+    let download_selected = if selected.is_empty() {
+        empty![]
+    } else {
+        button![
+            C!["button is-link"],
+            format!("Download selected ({})", selected.len()),
+            ev(Ev::Click, |_| Msg::CallDownloadSelected)
+        ]
+    };
+    div![
+        download_selected,
+        table![
+            C!["table is-hoverable is-fullwidth"],
+            thead![tr![th![""], th![""], th!["Name"], th!["Type"],],],
+            tbody![
+                tr![
+                    th![],
+                    th![],
+                    th![a![
+                        "..",
+                        ev(Ev::Click, move |_| Msg::ChangeRoute(
+                            "".to_string(),
+                            ChangeRouteType::Remove
+                        ))
+                    ]],
+                    th!["Folder"],
+                ],
+                folder_list,
+            ]
         ]
-    ]]
+    ]
 }
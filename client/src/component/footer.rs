@@ -1,11 +1,52 @@
 use crate::Msg;
 use seed::{prelude::*, *};
+use shared::StorageUsage;
 
-pub fn footer() -> Node<Msg> {
+pub fn human_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+fn usage_text(usage: &StorageUsage) -> String {
+    let bytes = match usage.quota_bytes {
+        Some(quota) => format!("{} of {} used", human_size(usage.bytes), human_size(quota)),
+        None => format!("{} used", human_size(usage.bytes)),
+    };
+    let files = match usage.quota_files {
+        Some(quota) => format!("{} of {} files", usage.files, quota),
+        None => format!("{} files", usage.files),
+    };
+    format!("{}, {}", bytes, files)
+}
+
+pub fn footer(usage: &Option<StorageUsage>) -> Node<Msg> {
     footer![
         C!["footer"],
         div![
             C!["content has-text-centered"],
+            match usage {
+                Some(usage) => p![
+                    progress![
+                        C!["progress is-small is-link"],
+                        attrs! {
+                            At::Value => usage.bytes,
+                            At::Max => usage.quota_bytes.unwrap_or(usage.bytes).max(1)
+                        }
+                    ],
+                    usage_text(usage)
+                ],
+                None => empty![],
+            },
             p![
                 strong!["OpenCloud"],
                 " by ".to_string(),
@@ -1,5 +1,10 @@
+pub mod admin;
 pub mod breadcrumb;
 pub mod dropdown;
 pub mod folder_list;
 pub mod footer;
+pub mod sessions;
+pub mod share;
+pub mod totp;
+pub mod trash;
 pub mod uploadfile;
@@ -0,0 +1,56 @@
+use crate::{Msg, StateApp};
+use seed::{prelude::*, *};
+use shared::SessionItem;
+
+pub fn session_list(sessions: &[SessionItem]) -> Node<Msg> {
+    let mut rows = vec![];
+    for session in sessions {
+        let id = session.id;
+        rows.push(tr![
+            th![session
+                .user_agent
+                .clone()
+                .unwrap_or_else(|| "Unknown".to_string())],
+            th![session.ip.clone().unwrap_or_default()],
+            th![&session.created],
+            th![&session.last_seen],
+            th![&session.expire],
+            th![if session.current {
+                span![C!["tag is-link"], "This browser"]
+            } else {
+                button![
+                    C!["button is-danger"],
+                    "Revoke",
+                    ev(Ev::Click, move |_| Msg::CallRevokeSession(id))
+                ]
+            }]
+        ]);
+    }
+    div![
+        div![
+            C!["buttons"],
+            button![
+                C!["button is-link"],
+                "Back to files",
+                ev(Ev::Click, |_| Msg::ChangeState(StateApp::Logged))
+            ],
+            button![
+                C!["button is-danger"],
+                "Log out everywhere",
+                ev(Ev::Click, |_| Msg::CallLogout(true))
+            ],
+        ],
+        table![
+            C!["table is-hoverable is-fullwidth"],
+            thead![tr![
+                th!["Browser"],
+                th!["IP"],
+                th!["Logged in"],
+                th!["Last seen"],
+                th!["Expire"],
+                th![""],
+            ]],
+            tbody![rows],
+        ]
+    ]
+}
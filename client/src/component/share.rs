@@ -0,0 +1,52 @@
+use crate::http::post::share::share_link;
+use crate::{Msg, StateApp};
+use seed::{prelude::*, *};
+use shared::ShareItem;
+
+pub fn share_list(shares: &[ShareItem]) -> Node<Msg> {
+    let mut rows = vec![];
+    for share in shares {
+        let slug = share.slug.clone();
+        let link = share_link(&share.slug);
+        let downloads = match share.max_downloads {
+            Some(max) => format!("{} / {}", share.downloads, max),
+            None => share.downloads.to_string(),
+        };
+        rows.push(tr![
+            th![&share.path],
+            th![&share.kind],
+            th![a![attrs! {At::Href => link}, &link]],
+            th![if share.password { "Yes" } else { "No" }],
+            th![share.expire.clone().unwrap_or_else(|| "Never".to_string())],
+            th![downloads],
+            th![button![
+                C!["button is-danger"],
+                "Revoke",
+                ev(Ev::Click, move |_| Msg::CallRevokeShare(slug))
+            ]]
+        ]);
+    }
+    div![
+        div![
+            C!["buttons"],
+            button![
+                C!["button is-link"],
+                "Back to files",
+                ev(Ev::Click, |_| Msg::ChangeState(StateApp::Logged))
+            ],
+        ],
+        table![
+            C!["table is-hoverable is-fullwidth"],
+            thead![tr![
+                th!["Path"],
+                th!["Type"],
+                th!["Link"],
+                th!["Password"],
+                th!["Expire"],
+                th!["Downloads"],
+                th![""],
+            ]],
+            tbody![rows],
+        ]
+    ]
+}
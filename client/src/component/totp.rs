@@ -0,0 +1,62 @@
+use crate::Msg;
+use seed::{prelude::*, *};
+use shared::{Profile, TotpSetup};
+
+fn recovery_list(codes: &[String]) -> Node<Msg> {
+    div![
+        C!["notification is-warning"],
+        p![
+            "Keep these recovery codes, each one logs you in once without the authenticator. They won't be shown again."
+        ],
+        ul![codes.iter().map(|e| li![code![e]])],
+    ]
+}
+
+/// State of the two-factor authentication of the user, with the QR code while it is set up
+pub fn totp_box(
+    profile: &Option<Profile>,
+    setup: &Option<TotpSetup>,
+    recovery_codes: &[String],
+) -> Node<Msg> {
+    let enabled = profile.as_ref().map(|e| e.totp) == Some(true);
+    let content = if let Some(setup) = setup {
+        div![
+            p!["Scan this QR code with your authenticator app, then confirm with the code it shows."],
+            div![C!["my-2"], raw![&setup.qr]],
+            p!["Or type this key : ", code![&setup.secret]],
+            button![
+                C!["button mt-2 is-link"],
+                "Confirm",
+                ev(Ev::Click, |_| Msg::CallConfirmTotp)
+            ],
+        ]
+    } else if enabled {
+        div![
+            p!["Two-factor authentication is enabled."],
+            button![
+                C!["button mt-2 is-danger"],
+                "Disable",
+                ev(Ev::Click, |_| Msg::CallRemoveTotp)
+            ],
+        ]
+    } else {
+        div![
+            p!["A code of an authenticator app is asked at login in addition to the password."],
+            button![
+                C!["button mt-2 is-link"],
+                "Enable",
+                ev(Ev::Click, |_| Msg::CallSetupTotp)
+            ],
+        ]
+    };
+    div![
+        C!["box"],
+        h2![C!["title is-4"], "Two-factor authentication"],
+        content,
+        if recovery_codes.is_empty() {
+            empty![]
+        } else {
+            recovery_list(recovery_codes)
+        },
+    ]
+}
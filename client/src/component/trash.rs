@@ -0,0 +1,53 @@
+use crate::{Msg, StateApp};
+use seed::{prelude::*, *};
+use shared::TrashItem;
+
+pub fn trash_list(items: &[TrashItem]) -> Node<Msg> {
+    let mut rows = vec![];
+    for item in items {
+        let restore_id = item.id.clone();
+        let delete_id = item.id.clone();
+        rows.push(tr![
+            th![if item.folder {
+                img![attrs! {At::Src => "/pkg/obj/folder.svg"}]
+            } else {
+                img![attrs! {At::Src => "/pkg/obj/file.svg"}]
+            }],
+            th![&item.path],
+            th![&item.date],
+            th![
+                button![
+                    C!["button mr-2 is-link"],
+                    "Restore",
+                    ev(Ev::Click, move |_| Msg::CallRestore(restore_id))
+                ],
+                button![
+                    C!["button is-danger"],
+                    "Delete forever",
+                    ev(Ev::Click, move |_| Msg::CallDeleteForever(delete_id))
+                ],
+            ]
+        ]);
+    }
+    div![
+        div![
+            C!["buttons"],
+            button![
+                C!["button is-link"],
+                "Back to files",
+                ev(Ev::Click, |_| Msg::ChangeState(StateApp::Logged))
+            ],
+            button![
+                C!["button is-danger"],
+                attrs! {At::Disabled => items.is_empty().as_at_value()},
+                "Empty trash",
+                ev(Ev::Click, |_| Msg::CallEmptyTrash)
+            ],
+        ],
+        table![
+            C!["table is-hoverable is-fullwidth"],
+            thead![tr![th![""], th!["Path"], th!["Deleted"], th![""],]],
+            tbody![rows],
+        ]
+    ]
+}
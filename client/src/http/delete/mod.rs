@@ -1,6 +1,7 @@
 use seed::window;
 
-use crate::Msg;
+use crate::{http::get_ip, Msg};
+use shared::TotpCode;
 
 pub async fn delete(token: String, name: String) -> Msg {
     let ip = format!(
@@ -21,7 +22,97 @@ pub async fn delete(token: String, name: String) -> Msg {
         .header("Token", token.as_str());
 
     match request.send().await {
-        Ok(_) => Msg::DeleteFile(Ok(200), "Delete successfully".to_string()),
+        Ok(_) => Msg::DeleteFile(Ok(200), "Moved to the trash".to_string()),
         Err(_) => Msg::DeleteFile(Err(0), "Delete unsuccessfully".to_string()),
     }
 }
+
+pub async fn delete_from_trash(token: String, id: String) -> Msg {
+    let request = reqwest::Client::new()
+        .delete(format!("{}/api/trash/{}", get_ip(), id))
+        .header("Token", token.as_str());
+    trash_result(request).await
+}
+
+pub async fn empty_trash(token: String) -> Msg {
+    let request = reqwest::Client::new()
+        .delete(format!("{}/api/trash", get_ip()))
+        .header("Token", token.as_str());
+    trash_result(request).await
+}
+
+async fn trash_result(request: reqwest::RequestBuilder) -> Msg {
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status().is_success();
+            Msg::TrashResult(status, e.text().await.unwrap_or_default())
+        }
+        Err(e) => Msg::TrashResult(false, e.to_string()),
+    }
+}
+
+pub async fn revoke_share(token: String, slug: String) -> Msg {
+    let request = reqwest::Client::new()
+        .delete(format!("{}/api/share/{}", get_ip(), slug))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status().is_success();
+            Msg::ShareResult(status, e.text().await.unwrap_or_default())
+        }
+        Err(e) => Msg::ShareResult(false, e.to_string()),
+    }
+}
+
+/// `home` is `keep`, `archive` or `delete`
+pub async fn delete_user(token: String, id: i32, home: String) -> Msg {
+    let request = reqwest::Client::new()
+        .delete(format!("{}/api/admin/users/{}?home={}", get_ip(), id, home))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) if e.status().is_success() => Msg::AdminResult(true, "User deleted".to_string()),
+        Ok(e) => Msg::AdminResult(false, e.text().await.unwrap_or_default()),
+        Err(e) => Msg::AdminResult(false, e.to_string()),
+    }
+}
+
+pub async fn revoke_session(token: String, id: i32) -> Msg {
+    let request = reqwest::Client::new()
+        .delete(format!("{}/api/user/sessions/{}", get_ip(), id))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status().is_success();
+            Msg::SessionResult(status, e.text().await.unwrap_or_default())
+        }
+        Err(e) => Msg::SessionResult(false, e.to_string()),
+    }
+}
+
+pub async fn remove_totp(token: String, code: String) -> Msg {
+    let request = reqwest::Client::new()
+        .delete(format!("{}/api/user/totp", get_ip()))
+        .header("Token", token.as_str())
+        .json(&TotpCode { code });
+    match request.send().await {
+        Ok(e) if e.status().is_success() => {
+            Msg::TotpResult(true, "Two-factor authentication disabled".to_string())
+        }
+        Ok(e) => Msg::TotpResult(false, e.text().await.unwrap_or_default()),
+        Err(e) => Msg::TotpResult(false, e.to_string()),
+    }
+}
+
+pub async fn reset_totp(token: String, id: i32, name: String) -> Msg {
+    let request = reqwest::Client::new()
+        .delete(format!("{}/api/admin/users/{}/totp", get_ip(), id))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) if e.status().is_success() => Msg::AdminResult(
+            true,
+            format!("Two-factor authentication of {} disabled", name),
+        ),
+        Ok(e) => Msg::AdminResult(false, e.text().await.unwrap_or_default()),
+        Err(e) => Msg::AdminResult(false, e.to_string()),
+    }
+}
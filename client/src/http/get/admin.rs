@@ -0,0 +1,42 @@
+use crate::{http::get_ip, Msg};
+use seed::log;
+use serde::de::DeserializeOwned;
+use shared::{InstanceStats, LogPage, Profile, UserItem};
+
+async fn fetch<T: DeserializeOwned>(token: String, path: &str) -> Option<T> {
+    let request = reqwest::Client::new()
+        .get(format!("{}/api/{}", get_ip(), path))
+        .header("Token", token)
+        .send()
+        .await;
+
+    match request {
+        Ok(e) => match e.json::<T>().await {
+            Ok(json) => Some(json),
+            Err(e) => {
+                log!(format! {"{:?}", e});
+                None
+            }
+        },
+        Err(e) => {
+            log!(format! {"{:?}", e});
+            None
+        }
+    }
+}
+
+pub async fn get_profile(token: String) -> Msg {
+    Msg::ProfileFetched(fetch::<Profile>(token, "user/me").await)
+}
+
+pub async fn get_users(token: String) -> Msg {
+    Msg::AdminUsersFetched(fetch::<Vec<UserItem>>(token, "admin/users").await)
+}
+
+pub async fn get_logs(token: String) -> Msg {
+    Msg::AdminLogsFetched(fetch::<LogPage>(token, "admin/logs").await.map(|e| e.items))
+}
+
+pub async fn get_stats(token: String) -> Msg {
+    Msg::AdminStatsFetched(fetch::<InstanceStats>(token, "admin/stats").await)
+}
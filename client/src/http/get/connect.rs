@@ -21,6 +21,14 @@ pub async fn get_token(account: Account) -> Msg {
     match request.send().await {
         Ok(r) => {
             let status = r.status().as_u16();
+            let totp = r
+                .headers()
+                .get("WWW-Authenticate")
+                .and_then(|e| e.to_str().ok())
+                == Some("TOTP");
+            if status == 401 && totp {
+                return Msg::TotpRequired(account.code.is_some());
+            }
             let text = r.text();
             if status == 200 {
                 match text.await {
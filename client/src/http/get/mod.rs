@@ -1,3 +1,8 @@
+pub mod admin;
 pub mod connect;
 pub mod get_files;
 pub mod refresh;
+pub mod sessions;
+pub mod share;
+pub mod trash;
+pub mod usage;
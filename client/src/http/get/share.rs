@@ -0,0 +1,27 @@
+use crate::{http::get_ip, Msg};
+use seed::log;
+use shared::ShareItem;
+
+pub async fn get_shares(token: String) -> Msg {
+    let request = reqwest::Client::new()
+        .get(format!("{}/api/share", get_ip()))
+        .header("Token", token)
+        .send()
+        .await;
+
+    let json = match request {
+        Ok(e) => match e.json::<Vec<ShareItem>>().await {
+            Ok(json) => Some(json),
+            Err(e) => {
+                log!(format! {"{:?}", e});
+                None
+            }
+        },
+        Err(e) => {
+            log!(format! {"{:?}", e});
+            None
+        }
+    };
+
+    Msg::SharesFetched(json)
+}
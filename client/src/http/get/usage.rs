@@ -0,0 +1,25 @@
+use crate::{http::get_ip, Msg};
+use seed::log;
+use shared::StorageUsage;
+
+pub async fn get_usage(token: String) -> Msg {
+    let request = reqwest::Client::new()
+        .get(format!("{}/api/user/usage", get_ip()))
+        .header("Token", token)
+        .send()
+        .await;
+
+    match request {
+        Ok(e) => match e.json::<StorageUsage>().await {
+            Ok(json) => Msg::UsageFetched(Some(json)),
+            Err(e) => {
+                log!(format! {"{:?}", e});
+                Msg::UsageFetched(None)
+            }
+        },
+        Err(e) => {
+            log!(format! {"{:?}", e});
+            Msg::UsageFetched(None)
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{http::get_ip, Msg};
+use shared::NewUser;
+
+async fn admin_result(request: reqwest::RequestBuilder) -> Msg {
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status().is_success();
+            Msg::AdminResult(status, e.text().await.unwrap_or_default())
+        }
+        Err(e) => Msg::AdminResult(false, e.to_string()),
+    }
+}
+
+pub async fn create_user(token: String, user: NewUser) -> Msg {
+    let name = user.name.clone();
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/admin/users", get_ip()))
+        .header("Token", token.as_str())
+        .json(&user);
+    match admin_result(request).await {
+        Msg::AdminResult(true, _) => Msg::AdminResult(true, format!("{} created", name)),
+        e => e,
+    }
+}
+
+pub async fn set_disabled(token: String, id: i32, disabled: bool) -> Msg {
+    let action = if disabled { "disable" } else { "enable" };
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/admin/users/{}/{}", get_ip(), id, action))
+        .header("Token", token.as_str());
+    match admin_result(request).await {
+        Msg::AdminResult(true, _) => Msg::AdminResult(true, format!("Account {}d", action)),
+        e => e,
+    }
+}
+
+pub async fn reset_password(token: String, id: i32, name: String) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/admin/users/{}/reset", get_ip(), id))
+        .header("Token", token.as_str());
+    match admin_result(request).await {
+        Msg::AdminResult(true, password) => Msg::AdminResult(
+            true,
+            format!("Temporary password of {} : {}", name, password),
+        ),
+        e => e,
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{http::get_ip, Msg};
+
+pub async fn create_folder(token: String, path: String) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/mkdir/{}", get_ip(), path))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status();
+            let text = e.text().await.unwrap_or_default();
+            if status.is_success() {
+                Msg::OperationResult(true, format!("{} created", text))
+            } else {
+                Msg::OperationResult(false, text)
+            }
+        }
+        Err(e) => Msg::OperationResult(false, e.to_string()),
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{http::get_ip, Msg};
+
+/// Ends the session of this browser, or every session of the user with `everywhere`
+pub async fn logout(token: String, everywhere: bool) -> Msg {
+    let path = if everywhere { "logout/all" } else { "logout" };
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/user/{}", get_ip(), path))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status().is_success();
+            Msg::LoggedOut(status, e.text().await.unwrap_or_default())
+        }
+        Err(e) => Msg::LoggedOut(false, e.to_string()),
+    }
+}
@@ -1,2 +1,10 @@
+pub mod admin;
+pub mod create_folder;
 pub mod create_user;
+pub mod logout;
+pub mod move_file;
+pub mod permission;
+pub mod restore;
+pub mod share;
+pub mod totp;
 pub mod upload;
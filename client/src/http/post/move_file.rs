@@ -0,0 +1,27 @@
+use crate::{http::get_ip, Msg};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct MoveRequest {
+    to: String,
+    conflict: Option<String>,
+}
+
+pub async fn move_file(token: String, path: String, to: String) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/move/{}", get_ip(), path))
+        .header("Token", token.as_str())
+        .json(&MoveRequest { to, conflict: None });
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status();
+            let text = e.text().await.unwrap_or_default();
+            if status.is_success() {
+                Msg::OperationResult(true, format!("Moved to {}", text))
+            } else {
+                Msg::OperationResult(false, text)
+            }
+        }
+        Err(e) => Msg::OperationResult(false, e.to_string()),
+    }
+}
@@ -0,0 +1,20 @@
+use crate::{http::get_ip, Msg};
+use shared::{NewPermission, PermissionItem};
+
+pub async fn grant_permission(token: String, path: String, permission: NewPermission) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/permission/{}", get_ip(), path))
+        .header("Token", token.as_str())
+        .json(&permission);
+    match request.send().await {
+        Ok(e) if e.status().is_success() => match e.json::<PermissionItem>().await {
+            Ok(e) => Msg::ShareResult(
+                true,
+                format!("{} has a {} access to {}", e.grantee, e.access, e.path),
+            ),
+            Err(e) => Msg::ShareResult(false, e.to_string()),
+        },
+        Ok(e) => Msg::ShareResult(false, e.text().await.unwrap_or_default()),
+        Err(e) => Msg::ShareResult(false, e.to_string()),
+    }
+}
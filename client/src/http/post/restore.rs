@@ -0,0 +1,19 @@
+use crate::{http::get_ip, Msg};
+
+pub async fn restore(token: String, id: String) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/trash/{}/restore", get_ip(), id))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status();
+            let text = e.text().await.unwrap_or_default();
+            if status.is_success() {
+                Msg::TrashResult(true, format!("{} restored", text))
+            } else {
+                Msg::TrashResult(false, text)
+            }
+        }
+        Err(e) => Msg::TrashResult(false, e.to_string()),
+    }
+}
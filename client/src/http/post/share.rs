@@ -0,0 +1,30 @@
+use crate::{http::get_ip, Msg};
+use seed::prelude::{js_sys::Date, JsValue};
+use shared::{NewShare, ShareItem};
+
+pub fn expire_in(days: f64) -> String {
+    Date::new(&JsValue::from_f64(
+        Date::now() + days * 24.0 * 3600.0 * 1000.0,
+    ))
+    .to_iso_string()
+    .into()
+}
+
+pub fn share_link(slug: &str) -> String {
+    format!("{}/api/public/{}/file/", get_ip(), slug)
+}
+
+pub async fn create_share(token: String, path: String, share: NewShare) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/share/{}", get_ip(), path))
+        .header("Token", token.as_str())
+        .json(&share);
+    match request.send().await {
+        Ok(e) if e.status().is_success() => match e.json::<ShareItem>().await {
+            Ok(e) => Msg::ShareResult(true, format!("Share link : {}", share_link(&e.slug))),
+            Err(e) => Msg::ShareResult(false, e.to_string()),
+        },
+        Ok(e) => Msg::ShareResult(false, e.text().await.unwrap_or_default()),
+        Err(e) => Msg::ShareResult(false, e.to_string()),
+    }
+}
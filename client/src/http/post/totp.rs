@@ -0,0 +1,36 @@
+use crate::{http::get_ip, Msg};
+use seed::log;
+use shared::{TotpCode, TotpSetup};
+
+pub async fn setup_totp(token: String) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/user/totp", get_ip()))
+        .header("Token", token.as_str());
+    match request.send().await {
+        Ok(e) if e.status().is_success() => match e.json::<TotpSetup>().await {
+            Ok(setup) => Msg::TotpSetupFetched(setup),
+            Err(e) => Msg::TotpResult(false, e.to_string()),
+        },
+        Ok(e) => Msg::TotpResult(false, e.text().await.unwrap_or_default()),
+        Err(e) => {
+            log!(format! {"{:?}", e});
+            Msg::TotpResult(false, e.to_string())
+        }
+    }
+}
+
+/// Sends the first code of the authenticator, the answer is the list of the recovery codes
+pub async fn confirm_totp(token: String, code: String) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/user/totp/confirm", get_ip()))
+        .header("Token", token.as_str())
+        .json(&TotpCode { code });
+    match request.send().await {
+        Ok(e) if e.status().is_success() => match e.json::<Vec<String>>().await {
+            Ok(codes) => Msg::TotpConfirmed(codes),
+            Err(e) => Msg::TotpResult(false, e.to_string()),
+        },
+        Ok(e) => Msg::TotpResult(false, e.text().await.unwrap_or_default()),
+        Err(e) => Msg::TotpResult(false, e.to_string()),
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Msg;
+use crate::{http::get_ip, Msg};
 use seed::{
     prelude::{
         js_sys::{ArrayBuffer, Uint8Array},
@@ -7,56 +7,61 @@ use seed::{
     },
     *,
 };
+use serde::Serialize;
 
-pub async fn upload_file(token: String, file: WebFile, path: String) -> Msg {
-    let ip = format!(
-        "{}{}{}{}",
-        "http://".to_owned(),
-        &window()
-            .location()
-            .host()
-            .unwrap_or_else(|_| "127.0.0.1:8081".to_string()),
-        "/api/file/",
-        path
-    );
+const CHUNK_SIZE: f64 = 4.0 * 1024.0 * 1024.0;
+const MAX_RETRY: u8 = 5;
 
-    let name = file.name();
-    let vec_file: Vec<u8> = if let Ok(e) = JsFuture::from(file.clone().array_buffer()).await {
-        if e.is_instance_of::<ArrayBuffer>() {
-            Uint8Array::new(&e).to_vec()
-        } else {
-            Vec::new()
-        }
-    } else {
-        log!("Error");
-        Vec::new()
-    };
+#[derive(Serialize)]
+struct NewUpload {
+    name: String,
+    length: i64,
+}
 
-    let file = File {
-        name,
-        data: vec_file,
+pub async fn upload_file(token: String, file: WebFile, path: String) -> Msg {
+    let length = file.size();
+    let id = match create_upload(&token, &file, &path).await {
+        Ok(e) => e,
+        Err(e) => {
+            log!(e);
+            return Msg::CallbackUploadFile(false, e);
+        }
     };
 
-    let body = vec_to_multipart(file);
-    let lenght = body.len();
+    let mut offset: f64 = 0.0;
+    let mut retry = 0;
+    while offset < length {
+        let end = (offset + CHUNK_SIZE).min(length);
+        match send_chunk(&token, &id, &file, offset, end).await {
+            Ok(e) => {
+                offset = e;
+                retry = 0;
+            }
+            Err(e) => {
+                log!(e);
+                retry += 1;
+                if retry > MAX_RETRY {
+                    return Msg::CallbackUploadFile(false, e);
+                }
+                // The server knows how many bytes it really received
+                if let Ok(e) = query_offset(&token, &id).await {
+                    offset = e;
+                }
+            }
+        }
+    }
 
     let request = reqwest::Client::new()
-        .post(ip)
-        .body(body)
-        .header("token", token)
-        .header(
-            "Content-Type",
-            format!("mutlipart/form-data; boundary={}", BOUNDARY),
-        )
-        .header("Content-Length", lenght);
+        .put(format!("{}/api/upload/{}", get_ip(), id))
+        .header("token", token);
     match request.send().await {
-        Ok(e) => match e.text().await {
-            Ok(e) => Msg::CallbackUploadFile(true, e),
-            Err(e) => {
-                log!(format!("{:?}", e.to_string()));
-                Msg::CallbackUploadFile(false, format! {"{:?}",e.to_string()})
+        Ok(e) => {
+            let status = e.status().is_success();
+            match e.text().await {
+                Ok(e) => Msg::CallbackUploadFile(status, e),
+                Err(e) => Msg::CallbackUploadFile(false, format! {"{:?}", e.to_string()}),
             }
-        },
+        }
         Err(e) => {
             log!(format!("{:?}", e.to_string()));
             Msg::CallbackUploadFile(false, format! {"{:?}", e.to_string()})
@@ -64,51 +69,72 @@ pub async fn upload_file(token: String, file: WebFile, path: String) -> Msg {
     }
 }
 
-pub struct File {
-    pub name: String,
-    pub data: Vec<u8>,
+async fn create_upload(token: &str, file: &WebFile, path: &str) -> Result<String, String> {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/upload/{}", get_ip(), path))
+        .header("token", token)
+        .json(&NewUpload {
+            name: file.name(),
+            length: file.size() as i64,
+        });
+    match request.send().await {
+        Ok(e) => {
+            let status = e.status().as_u16();
+            let text = e.text().await.map_err(|e| e.to_string())?;
+            if status == 201 {
+                Ok(text)
+            } else {
+                Err(text)
+            }
+        }
+        Err(e) => Err(e.to_string()),
+    }
 }
 
-impl File {
-    pub fn name(&self) -> String {
-        self.name.clone()
-    }
+async fn send_chunk(
+    token: &str,
+    id: &str,
+    file: &WebFile,
+    start: f64,
+    end: f64,
+) -> Result<f64, String> {
+    let blob = file
+        .slice_with_f64_and_f64(start, end)
+        .map_err(|_| String::from("Can't slice the file"))?;
+    let chunk: Vec<u8> = match JsFuture::from(blob.array_buffer()).await {
+        Ok(e) if e.is_instance_of::<ArrayBuffer>() => Uint8Array::new(&e).to_vec(),
+        _ => return Err(String::from("Can't read the file")),
+    };
 
-    pub fn data(&self) -> Vec<u8> {
-        self.data.clone()
+    let request = reqwest::Client::new()
+        .patch(format!("{}/api/upload/{}", get_ip(), id))
+        .header("token", token)
+        .header("Upload-Offset", (start as u64).to_string())
+        .header("Content-Type", "application/offset+octet-stream")
+        .body(chunk);
+    match request.send().await {
+        Ok(e) if e.status().is_success() => offset_of(&e),
+        Ok(e) => Err(e.text().await.unwrap_or_default()),
+        Err(e) => Err(e.to_string()),
     }
 }
 
-const BOUNDARY: &str = "OPENCLOUDBINARYFILE";
-
-pub fn vec_to_multipart(file: File) -> Vec<u8> {
-    let mut multivec: Vec<u8> = Vec::new();
-    let name = file.name();
-    let rn = b"\r\n";
-
-    multivec.extend(rn);
-    multivec.extend(rn);
-    multivec.extend(format!("--{}", BOUNDARY).as_str().bytes());
-    multivec.extend(rn);
-    multivec.extend(
-        format!(
-            "Content-Disposition: form-data; name=file; filename=\"{}\"",
-            name
-        )
-        .as_str()
-        .bytes(),
-    );
-    multivec.extend(rn);
-    multivec.extend("Content-Type: application: application/octet-stream".bytes());
-    multivec.extend(rn);
-    multivec.extend("Content-Transfer-Encoding: binary".bytes());
-    multivec.extend(rn);
-    multivec.extend(rn);
-    multivec.extend(file.data());
-    multivec.extend(rn);
-    multivec.extend(format!("--{}--", BOUNDARY).as_str().bytes());
-    multivec.extend(rn);
-    multivec.extend(rn);
+async fn query_offset(token: &str, id: &str) -> Result<f64, String> {
+    let request = reqwest::Client::new()
+        .head(format!("{}/api/upload/{}", get_ip(), id))
+        .header("token", token);
+    match request.send().await {
+        Ok(e) if e.status().is_success() => offset_of(&e),
+        Ok(e) => Err(e.status().to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
 
-    multivec
+fn offset_of(response: &reqwest::Response) -> Result<f64, String> {
+    response
+        .headers()
+        .get("Upload-Offset")
+        .and_then(|e| e.to_str().ok())
+        .and_then(|e| e.parse::<f64>().ok())
+        .ok_or_else(|| String::from("Upload-Offset is missing"))
 }
@@ -1,10 +1,38 @@
 use std::convert::TryInto;
 
+use crate::component::admin::admin_view;
 use crate::component::footer::footer;
+use crate::component::sessions::session_list;
+use crate::component::share::share_list;
+use crate::component::totp::totp_box;
+use crate::component::trash::trash_list;
 use account::{login::login, signup::signup};
 use component::uploadfile::get_name_of_file;
-use http::{get::refresh::refresh, post::create_user::create_user};
-use shared::{FType, JsonStruct};
+use http::{
+    get::{
+        admin::{get_logs, get_profile, get_stats, get_users},
+        refresh::refresh,
+        sessions::get_sessions,
+        share::get_shares,
+        trash::get_trash,
+        usage::get_usage,
+    },
+    post::{
+        admin::{reset_password, set_disabled},
+        create_folder::create_folder,
+        create_user::create_user,
+        logout::logout,
+        move_file::move_file,
+        permission::grant_permission,
+        restore::restore,
+        share::{create_share, expire_in},
+        totp::{confirm_totp, setup_totp},
+    },
+};
+use shared::{
+    FType, InstanceStats, JsonStruct, LogItem, NewPermission, NewShare, NewUser, Profile,
+    SessionItem, ShareItem, StorageUsage, TotpSetup, TrashItem, UserItem,
+};
 mod account;
 mod component;
 mod http;
@@ -14,7 +42,7 @@ use crate::component::breadcrumb::breadcrumb;
 use crate::component::uploadfile::upload_file;
 use crate::http::get::connect::get_token;
 use crate::http::get::get_files::{back, get_files};
-use crate::library::lib::download;
+use crate::library::lib::{download, download_selection};
 use library::lib::Account;
 use seed::{browser::Url, prelude::web_sys::File};
 use seed::{prelude::*, *};
@@ -24,6 +52,10 @@ pub enum StateApp {
     Login,
     SignUp,
     Logged,
+    Trash,
+    Shares,
+    Admin,
+    Sessions,
 }
 
 pub enum ChangeRouteType {
@@ -51,6 +83,18 @@ fn init(_: Url, _: &mut impl Orders<Msg>) -> Model {
         delete: (false, "".to_string()),
         file: File::new_with_str_sequence(&JsValue::from_str(&""), ""),
         notification: Vec::new(),
+        selected: Vec::new(),
+        trash: Vec::new(),
+        usage: None,
+        shares: Vec::new(),
+        profile: None,
+        users: Vec::new(),
+        logs: Vec::new(),
+        stats: None,
+        sessions: Vec::new(),
+        totp_required: false,
+        totp_setup: None,
+        recovery_codes: Vec::new(),
     }
 }
 
@@ -69,12 +113,33 @@ pub struct Model {
     pub route: String,
     pub delete: (bool, String),
     pub file: Result<File, seed::prelude::JsValue>,
+    pub selected: Vec<String>,
+    pub trash: Vec<TrashItem>,
+    pub usage: Option<StorageUsage>,
+    pub shares: Vec<ShareItem>,
+    pub profile: Option<Profile>,
+    pub users: Vec<UserItem>,
+    pub logs: Vec<LogItem>,
+    pub stats: Option<InstanceStats>,
+    pub sessions: Vec<SessionItem>,
+    /// The server asked the code of the authenticator at login
+    pub totp_required: bool,
+    pub totp_setup: Option<TotpSetup>,
+    /// Shown once, after the two-factor authentication was enabled
+    pub recovery_codes: Vec<String>,
+}
+
+impl Model {
+    pub fn is_admin(&self) -> bool {
+        self.profile.as_ref().map(|e| e.role == "admin") == Some(true)
+    }
 }
 
 pub enum InputType {
     Name,
     Password,
     Mail,
+    Code,
 }
 
 pub enum Msg {
@@ -91,6 +156,50 @@ pub enum Msg {
     DeleteFile(Result<u16, u16>, String),
     CallDelete(String),
     CallDownload(String),
+    ToggleSelect(String),
+    CallDownloadSelected,
+    ClearSelection,
+    CallRename(String),
+    CallMove(String),
+    CallNewFolder,
+    ShowTrash,
+    TrashFetched(Option<Vec<TrashItem>>),
+    CallRestore(String),
+    CallDeleteForever(String),
+    CallEmptyTrash,
+    TrashResult(bool, String),
+    UsageFetched(Option<StorageUsage>),
+    CallShare(String),
+    CallGrant(String),
+    ShowShares,
+    SharesFetched(Option<Vec<ShareItem>>),
+    CallRevokeShare(String),
+    ShareResult(bool, String),
+    OperationResult(bool, String),
+    ProfileFetched(Option<Profile>),
+    ShowAdmin,
+    AdminUsersFetched(Option<Vec<UserItem>>),
+    AdminLogsFetched(Option<Vec<LogItem>>),
+    AdminStatsFetched(Option<InstanceStats>),
+    CallCreateUser,
+    CallSetDisabled(i32, bool),
+    CallResetPassword(i32, String),
+    CallDeleteUser(i32, String),
+    AdminResult(bool, String),
+    ShowSessions,
+    SessionsFetched(Option<Vec<SessionItem>>),
+    CallRevokeSession(i32),
+    SessionResult(bool, String),
+    CallLogout(bool),
+    LoggedOut(bool, String),
+    TotpRequired(bool),
+    CallSetupTotp,
+    TotpSetupFetched(TotpSetup),
+    CallConfirmTotp,
+    TotpConfirmed(Vec<String>),
+    CallRemoveTotp,
+    TotpResult(bool, String),
+    CallResetTotp(i32, String),
     CallSignUp,
     FileSelect(File),
     CallUploadFile,
@@ -112,6 +221,7 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             InputType::Name => model.account.name = e,
             InputType::Password => model.account.password = e,
             InputType::Mail => model.account.mail = Some(e),
+            InputType::Code => model.account.code = Some(e).filter(|e| !e.trim().is_empty()),
         },
         Msg::Connect => {
             orders.skip().perform_cmd(get_token(model.clone().account));
@@ -122,7 +232,13 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             } else {
                 model.token = e.clone();
                 model.state = StateApp::Logged;
-                orders.skip().perform_cmd(get_files("".to_string(), e));
+                model.totp_required = false;
+                model.account.code = None;
+                orders
+                    .skip()
+                    .perform_cmd(get_files("".to_string(), e.clone()))
+                    .perform_cmd(get_usage(e.clone()))
+                    .perform_cmd(get_profile(e));
             }
         }
         Msg::Token(Err(e)) => {
@@ -131,7 +247,8 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         Msg::Refresh => {
             orders
                 .skip()
-                .perform_cmd(get_files(model.clone().route, model.clone().token));
+                .perform_cmd(get_files(model.clone().route, model.clone().token))
+                .perform_cmd(get_usage(model.token.clone()));
         }
         Msg::ChangeRoute(s, crt) => {
             let old_path = model.route.clone();
@@ -161,6 +278,340 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 .skip()
                 .perform_cmd(download(e, "zip".to_string(), model.clone().token));
         }
+        Msg::ToggleSelect(e) => {
+            if let Some(index) = model.selected.iter().position(|s| *s == e) {
+                model.selected.remove(index);
+            } else {
+                model.selected.push(e);
+            }
+        }
+        Msg::CallDownloadSelected => {
+            orders.skip().perform_cmd(download_selection(
+                model.selected.clone(),
+                model.token.clone(),
+            ));
+        }
+        Msg::ClearSelection => model.selected.clear(),
+        Msg::CallRename(e) => {
+            let (parent, name) = match e.rfind('/') {
+                Some(i) => (e[..=i].to_string(), e[i + 1..].to_string()),
+                None => (String::new(), e.clone()),
+            };
+            if let Ok(Some(new_name)) = window().prompt_with_message_and_default("New name", &name)
+            {
+                if !new_name.is_empty() && new_name != name {
+                    orders.skip().perform_cmd(move_file(
+                        model.token.clone(),
+                        e,
+                        format!("{}{}", parent, new_name),
+                    ));
+                }
+            }
+        }
+        Msg::CallMove(e) => {
+            if let Ok(Some(to)) = window().prompt_with_message_and_default("Move to", &e) {
+                if !to.is_empty() && to != e {
+                    orders
+                        .skip()
+                        .perform_cmd(move_file(model.token.clone(), e, to));
+                }
+            }
+        }
+        Msg::CallNewFolder => {
+            if let Ok(Some(name)) = window().prompt_with_message("Folder name") {
+                let name = name.trim_matches('/');
+                if !name.is_empty() {
+                    orders.skip().perform_cmd(create_folder(
+                        model.token.clone(),
+                        format!("{}{}", model.route, name),
+                    ));
+                }
+            }
+        }
+        Msg::ShowTrash => {
+            model.state = StateApp::Trash;
+            orders.skip().perform_cmd(get_trash(model.token.clone()));
+        }
+        Msg::TrashFetched(Some(items)) => model.trash = items,
+        Msg::TrashFetched(None) => {
+            model
+                .notification
+                .push((false, None, "Fetching the trash failed".to_string()));
+        }
+        Msg::CallRestore(id) => {
+            orders.skip().perform_cmd(restore(model.token.clone(), id));
+        }
+        Msg::CallDeleteForever(id) => {
+            orders
+                .skip()
+                .perform_cmd(http::delete::delete_from_trash(model.token.clone(), id));
+        }
+        Msg::CallEmptyTrash => {
+            orders
+                .skip()
+                .perform_cmd(http::delete::empty_trash(model.token.clone()));
+        }
+        Msg::TrashResult(result, message) => {
+            if result {
+                orders
+                    .skip()
+                    .perform_cmd(get_trash(model.token.clone()))
+                    .perform_cmd(refresh());
+            }
+            model.notification.push((result, None, message));
+        }
+        Msg::UsageFetched(usage) => model.usage = usage,
+        Msg::CallShare(path) => {
+            let password =
+                match window().prompt_with_message("Password of the link (leave empty for none)") {
+                    Ok(Some(e)) => e,
+                    _ => return,
+                };
+            let days = match window()
+                .prompt_with_message("Expire after how many days (leave empty for never)")
+            {
+                Ok(Some(e)) => e,
+                _ => return,
+            };
+            let share = NewShare {
+                kind: None,
+                password: Some(password).filter(|e| !e.is_empty()),
+                expire: days.trim().parse::<f64>().ok().map(expire_in),
+                max_downloads: None,
+                max_size: None,
+            };
+            orders
+                .skip()
+                .perform_cmd(create_share(model.token.clone(), path, share));
+        }
+        Msg::CallGrant(path) => {
+            let name = match window()
+                .prompt_with_message("Share the folder with which user (@name for a group)")
+            {
+                Ok(Some(e)) if !e.trim().is_empty() => e.trim().to_string(),
+                _ => return,
+            };
+            let access =
+                match window().prompt_with_message_and_default("Access (read or write)", "read") {
+                    Ok(Some(e)) => e.trim().to_lowercase(),
+                    _ => return,
+                };
+            orders.skip().perform_cmd(grant_permission(
+                model.token.clone(),
+                path,
+                NewPermission {
+                    user: Some(name.clone()).filter(|e| !e.starts_with('@')),
+                    group: name.strip_prefix('@').map(String::from),
+                    access,
+                },
+            ));
+        }
+        Msg::ShowShares => {
+            model.state = StateApp::Shares;
+            orders.skip().perform_cmd(get_shares(model.token.clone()));
+        }
+        Msg::SharesFetched(Some(shares)) => model.shares = shares,
+        Msg::SharesFetched(None) => {
+            model
+                .notification
+                .push((false, None, "Fetching the shares failed".to_string()));
+        }
+        Msg::CallRevokeShare(slug) => {
+            orders
+                .skip()
+                .perform_cmd(http::delete::revoke_share(model.token.clone(), slug));
+        }
+        Msg::ShareResult(result, message) => {
+            if result {
+                if let StateApp::Shares = model.state {
+                    orders.skip().perform_cmd(get_shares(model.token.clone()));
+                }
+            }
+            model.notification.push((result, None, message));
+        }
+        Msg::OperationResult(result, message) => {
+            if result {
+                model.selected.clear();
+                orders.skip().perform_cmd(refresh());
+            }
+            model.notification.push((result, None, message));
+        }
+        Msg::ProfileFetched(profile) => model.profile = profile,
+        Msg::ShowAdmin => {
+            model.state = StateApp::Admin;
+            orders
+                .skip()
+                .perform_cmd(get_users(model.token.clone()))
+                .perform_cmd(get_logs(model.token.clone()))
+                .perform_cmd(get_stats(model.token.clone()));
+        }
+        Msg::AdminUsersFetched(Some(users)) => model.users = users,
+        Msg::AdminLogsFetched(Some(logs)) => model.logs = logs,
+        Msg::AdminStatsFetched(Some(stats)) => model.stats = Some(stats),
+        Msg::AdminUsersFetched(None)
+        | Msg::AdminLogsFetched(None)
+        | Msg::AdminStatsFetched(None) => {
+            model.notification.push((
+                false,
+                None,
+                "Fetching the administration failed".to_string(),
+            ));
+        }
+        Msg::CallCreateUser => {
+            let name = match window().prompt_with_message("Name of the user") {
+                Ok(Some(e)) if !e.trim().is_empty() => e.trim().to_string(),
+                _ => return,
+            };
+            let password = match window().prompt_with_message("Password") {
+                Ok(Some(e)) if !e.is_empty() => e,
+                _ => return,
+            };
+            let role =
+                match window().prompt_with_message_and_default("Role (user or admin)", "user") {
+                    Ok(Some(e)) => e.trim().to_lowercase(),
+                    _ => return,
+                };
+            orders.skip().perform_cmd(http::post::admin::create_user(
+                model.token.clone(),
+                NewUser {
+                    name,
+                    email: None,
+                    password,
+                    role: Some(role),
+                },
+            ));
+        }
+        Msg::CallSetDisabled(id, disabled) => {
+            orders
+                .skip()
+                .perform_cmd(set_disabled(model.token.clone(), id, disabled));
+        }
+        Msg::CallResetPassword(id, name) => {
+            if let Ok(true) =
+                window().confirm_with_message(&format!("Reset the password of {} ?", name))
+            {
+                orders
+                    .skip()
+                    .perform_cmd(reset_password(model.token.clone(), id, name));
+            }
+        }
+        Msg::CallDeleteUser(id, name) => {
+            let home = match window().prompt_with_message_and_default(
+                &format!(
+                    "Delete {} ? Their home can be kept, archived or deleted (keep, archive or delete)",
+                    name
+                ),
+                "archive",
+            ) {
+                Ok(Some(e)) => e.trim().to_lowercase(),
+                _ => return,
+            };
+            orders
+                .skip()
+                .perform_cmd(http::delete::delete_user(model.token.clone(), id, home));
+        }
+        Msg::AdminResult(result, message) => {
+            if result {
+                orders
+                    .skip()
+                    .perform_cmd(get_users(model.token.clone()))
+                    .perform_cmd(get_logs(model.token.clone()))
+                    .perform_cmd(get_stats(model.token.clone()));
+            }
+            model.notification.push((result, None, message));
+        }
+        Msg::ShowSessions => {
+            model.state = StateApp::Sessions;
+            orders.skip().perform_cmd(get_sessions(model.token.clone()));
+        }
+        Msg::SessionsFetched(Some(sessions)) => model.sessions = sessions,
+        Msg::SessionsFetched(None) => {
+            model
+                .notification
+                .push((false, None, "Fetching the sessions failed".to_string()));
+        }
+        Msg::CallRevokeSession(id) => {
+            orders
+                .skip()
+                .perform_cmd(http::delete::revoke_session(model.token.clone(), id));
+        }
+        Msg::SessionResult(result, message) => {
+            if result {
+                orders.skip().perform_cmd(get_sessions(model.token.clone()));
+            }
+            model.notification.push((result, None, message));
+        }
+        Msg::CallLogout(everywhere) => {
+            orders
+                .skip()
+                .perform_cmd(logout(model.token.clone(), everywhere));
+        }
+        Msg::LoggedOut(true, _) => {
+            model.token = String::new();
+            model.profile = None;
+            model.usage = None;
+            model.totp_setup = None;
+            model.recovery_codes.clear();
+            model.state = StateApp::Login;
+        }
+        Msg::LoggedOut(false, message) => model.notification.push((false, None, message)),
+        Msg::TotpRequired(sent) => {
+            model.totp_required = true;
+            if sent {
+                model
+                    .notification
+                    .push((false, Some(401), "Invalid code".to_string()));
+            }
+        }
+        Msg::CallSetupTotp => {
+            orders.skip().perform_cmd(setup_totp(model.token.clone()));
+        }
+        Msg::TotpSetupFetched(setup) => {
+            model.recovery_codes.clear();
+            model.totp_setup = Some(setup);
+        }
+        Msg::CallConfirmTotp => {
+            if let Ok(Some(code)) = window().prompt_with_message("Code of the authenticator") {
+                if !code.trim().is_empty() {
+                    orders
+                        .skip()
+                        .perform_cmd(confirm_totp(model.token.clone(), code));
+                }
+            }
+        }
+        Msg::TotpConfirmed(codes) => {
+            model.totp_setup = None;
+            model.recovery_codes = codes;
+            orders.skip().perform_cmd(get_profile(model.token.clone()));
+        }
+        Msg::CallRemoveTotp => {
+            if let Ok(Some(code)) =
+                window().prompt_with_message("Code of the authenticator or recovery code")
+            {
+                if !code.trim().is_empty() {
+                    orders
+                        .skip()
+                        .perform_cmd(http::delete::remove_totp(model.token.clone(), code));
+                }
+            }
+        }
+        Msg::TotpResult(result, message) => {
+            if result {
+                model.recovery_codes.clear();
+                orders.skip().perform_cmd(get_profile(model.token.clone()));
+            }
+            model.notification.push((result, None, message));
+        }
+        Msg::CallResetTotp(id, name) => {
+            if let Ok(true) = window().confirm_with_message(&format!(
+                "Disable the two-factor authentication of {} ?",
+                name
+            )) {
+                orders
+                    .skip()
+                    .perform_cmd(http::delete::reset_totp(model.token.clone(), id, name));
+            }
+        }
         Msg::DeleteFile(result, name) => {
             let mut re = (false, None, name);
             if result.is_ok() {
@@ -184,6 +635,9 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         }
         Msg::CallbackUploadFile(e, msg) => {
             log!(format! {"{} / {}",e , msg});
+            if !e {
+                model.notification.push((false, None, msg));
+            }
             orders.skip().perform_cmd(refresh());
         }
         Msg::AddNotification(status, http_status, content) => {
@@ -242,7 +696,7 @@ fn view(model: &Model) -> Vec<Node<Msg>> {
                 div![
                     C!["is-flex is-flex-direction-column is-vcentered"],
                     div![notifs],
-                    login()
+                    login(model.totp_required)
                 ]
             ]]
         }
@@ -272,15 +726,110 @@ fn view(model: &Model) -> Vec<Node<Msg>> {
                                     C!["column"],
                                     upload_file(get_name_of_file(&model.file), &model.route),
                                 ],
+                                div![
+                                    C!["column is-narrow"],
+                                    button![
+                                        C!["button mr-2 is-link"],
+                                        "New folder",
+                                        ev(Ev::Click, |_| Msg::CallNewFolder)
+                                    ],
+                                    button![
+                                        C!["button mr-2 is-link"],
+                                        "Trash",
+                                        ev(Ev::Click, |_| Msg::ShowTrash)
+                                    ],
+                                    button![
+                                        C!["button mr-2 is-link"],
+                                        "Shares",
+                                        ev(Ev::Click, |_| Msg::ShowShares)
+                                    ],
+                                    button![
+                                        C!["button mr-2 is-link"],
+                                        "Sessions",
+                                        ev(Ev::Click, |_| Msg::ShowSessions)
+                                    ],
+                                    button![
+                                        C!["button is-light"],
+                                        "Log out",
+                                        ev(Ev::Click, |_| Msg::CallLogout(false))
+                                    ],
+                                    if model.is_admin() {
+                                        button![
+                                            C!["button ml-2 is-link"],
+                                            "Admin",
+                                            ev(Ev::Click, |_| Msg::ShowAdmin)
+                                        ]
+                                    } else {
+                                        empty![]
+                                    },
+                                ],
                             ],
                             component::folder_list::folder_list(
                                 model.api.content.clone(),
-                                model.route.clone()
+                                model.route.clone(),
+                                &model.selected
                             ),
                         ]
                     ]
                 ],
-                footer(),
+                footer(&model.usage),
+            ]
+        }
+        StateApp::Trash => {
+            vec![
+                div![
+                    attrs! {At::Id => "wrapper"},
+                    div![
+                        C!["container"],
+                        div![C!["column"], notifs, trash_list(&model.trash)]
+                    ]
+                ],
+                footer(&model.usage),
+            ]
+        }
+        StateApp::Shares => {
+            vec![
+                div![
+                    attrs! {At::Id => "wrapper"},
+                    div![
+                        C!["container"],
+                        div![C!["column"], notifs, share_list(&model.shares)]
+                    ]
+                ],
+                footer(&model.usage),
+            ]
+        }
+        StateApp::Sessions => {
+            vec![
+                div![
+                    attrs! {At::Id => "wrapper"},
+                    div![
+                        C!["container"],
+                        div![
+                            C!["column"],
+                            notifs,
+                            session_list(&model.sessions),
+                            totp_box(&model.profile, &model.totp_setup, &model.recovery_codes)
+                        ]
+                    ]
+                ],
+                footer(&model.usage),
+            ]
+        }
+        StateApp::Admin => {
+            vec![
+                div![
+                    attrs! {At::Id => "wrapper"},
+                    div![
+                        C!["container"],
+                        div![
+                            C!["column"],
+                            notifs,
+                            admin_view(&model.users, &model.logs, &model.stats)
+                        ]
+                    ]
+                ],
+                footer(&model.usage),
             ]
         }
     }
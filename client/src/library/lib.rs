@@ -1,5 +1,12 @@
-use seed::{log, window};
+use crate::{http::get_ip, Msg};
+use seed::prelude::{
+    js_sys::{Array, Uint8Array},
+    web_sys::{Blob, HtmlAnchorElement, Url},
+    JsCast,
+};
+use seed::{document, log, window};
 use serde::Serialize;
+use shared::ArchiveRequest;
 
 pub async fn download(url: String, dtype: String, token: String) {
     let mut url_string: String = "http://".to_owned()
@@ -27,6 +34,9 @@ pub struct Account {
     pub name: String,
     pub password: String,
     pub mail: Option<String>,
+    /// Code of the authenticator, only sent once the server asked it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
 }
 impl Account {
     pub fn new() -> Account {
@@ -34,6 +44,7 @@ impl Account {
             name: String::new(),
             mail: Some(String::new()),
             password: String::new(),
+            code: None,
         }
     }
 }
@@ -71,3 +82,45 @@ impl SignUpAccount {
         result
     }
 }
+
+pub async fn download_selection(paths: Vec<String>, token: String) -> Msg {
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/archive", get_ip()))
+        .header("Token", token)
+        .json(&ArchiveRequest {
+            paths,
+            format: String::from("zip"),
+            compression: None,
+        });
+    let bytes = match request.send().await {
+        Ok(e) if e.status().is_success() => match e.bytes().await {
+            Ok(e) => e,
+            Err(e) => return Msg::AddNotification(false, None, e.to_string()),
+        },
+        Ok(e) => {
+            let status = e.status().as_u16() as i32;
+            return Msg::AddNotification(false, Some(status), e.text().await.unwrap_or_default());
+        }
+        Err(e) => return Msg::AddNotification(false, None, e.to_string()),
+    };
+
+    let parts = Array::new();
+    parts.push(&Uint8Array::from(&bytes[..]).buffer());
+    let url = match Blob::new_with_u8_array_sequence(&parts)
+        .and_then(|blob| Url::create_object_url_with_blob(&blob))
+    {
+        Ok(e) => e,
+        Err(_) => return Msg::AddNotification(false, None, String::from("Can't save the archive")),
+    };
+    if let Ok(anchor) = document()
+        .create_element("a")
+        .map(|e| e.unchecked_into::<HtmlAnchorElement>())
+    {
+        anchor.set_href(&url);
+        anchor.set_download("selection.zip");
+        anchor.click();
+    }
+    let _ = Url::revoke_object_url(&url);
+
+    Msg::ClearSelection
+}
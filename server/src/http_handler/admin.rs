@@ -0,0 +1,485 @@
+//! Administration of the instance, every route is under `/api/admin` and requires the admin role.
+
+use crate::http_handler::logs::{log_response, LogParams};
+use crate::lib::archive::archive_folder;
+use crate::lib::config::Config;
+use crate::lib::db::group::delete::{delete_group as delete_group_row, remove_member};
+use crate::lib::db::group::get::{get_group, get_group_by_name, get_groups, get_members};
+use crate::lib::db::group::insert::{add_member, insert as insert_group};
+use crate::lib::db::group::model::Group;
+use crate::lib::db::group::update::update_group as update_group_row;
+use crate::lib::db::log::retention::{archive_id, LOG_ARCHIVE_FOLDER};
+use crate::lib::db::trash::model::trash_folder;
+use crate::lib::db::user::create_home::create_home;
+use crate::lib::db::user::delete::delete_user as delete_user_row;
+use crate::lib::db::user::get::{get_user_by_id, get_user_by_name, get_users};
+use crate::lib::db::user::insert::insert_user;
+use crate::lib::db::user::model::{Role, User};
+use crate::lib::db::user::token::generate_token;
+use crate::lib::db::user::update::{disable_totp, reset_password, set_disabled, set_role};
+use crate::lib::db::version::model::version_folder;
+use crate::lib::file::quota::{folder_usage, home_usage, quota_of};
+use crate::lib::file::stream::{stream_file, Disposition};
+use crate::lib::http::get_args;
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use datagn::DatabasePool;
+use shared::{GroupItem, InstanceStats, LogArchive, NewGroup, NewUser, RoleRequest, UserItem};
+use std::path::Path;
+
+/// The admin who sends the request, stored by the `RequireRole` middleware
+fn admin(req: &HttpRequest) -> Option<User> {
+    req.extensions().get::<User>().cloned()
+}
+
+/// The admin can't disable or delete their own account, the instance must always keep an admin
+fn is_self(req: &HttpRequest, id: i32) -> bool {
+    admin(req).and_then(|e| e.id) == Some(id)
+}
+
+#[get("/users")]
+pub async fn list_users(data: web::Data<DatabasePool>, config: web::Data<Config>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let mut users: Vec<UserItem> = Vec::new();
+    for user in get_users(&mut database).await {
+        let usage = home_usage(&user).await;
+        let quota = quota_of(&mut database, &user, &config).await;
+        users.push(UserItem {
+            id: user.id.unwrap_or_default(),
+            name: user.name,
+            email: user.email.filter(|e| !e.is_empty()),
+            role: user.role.format(),
+            disabled: user.disabled,
+            must_reset: user.must_reset,
+            last_login: user.last_login,
+            totp: user.totp_enabled,
+            bytes: usage.bytes,
+            files: usage.files,
+            quota_bytes: quota.bytes,
+            quota_files: quota.files,
+        });
+    }
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&users).unwrap_or_default())
+}
+
+#[post("/users")]
+pub async fn create_user(
+    body: web::Json<NewUser>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let name = body.name.trim().to_string();
+    if name.is_empty() || body.password.is_empty() {
+        return HttpResponse::BadRequest().body("Name or password cannot be empty");
+    }
+    if name.contains('/') || name.starts_with('.') {
+        return HttpResponse::BadRequest().body("This name can't be used");
+    }
+    let role = match body.role.as_deref().map(Role::from_name) {
+        Some(Some(e)) => e,
+        Some(None) => return HttpResponse::BadRequest().body("The role must be user or admin"),
+        None => Role::User,
+    };
+    if get_user_by_name(&mut database, name.clone())
+        .await
+        .is_some()
+    {
+        return HttpResponse::Conflict().body("A user already has this name");
+    }
+
+    match insert_user(
+        &mut database,
+        name.clone(),
+        body.email.clone().unwrap_or_default(),
+        body.password.clone(),
+        config.hash_cost(),
+    )
+    .await
+    {
+        Ok(n) if n > 0 => {}
+        _ => return HttpResponse::InternalServerError().body("Error on creation of the user"),
+    }
+    let id = match get_user_by_name(&mut database, name.clone()).await {
+        Some(e) => e.id.unwrap_or_default(),
+        None => return HttpResponse::InternalServerError().body("Error on creation of the user"),
+    };
+    if role != Role::User && !set_role(&mut database, id, role).await {
+        return HttpResponse::InternalServerError().body("Error on update of the role");
+    }
+    create_home(name).await;
+    HttpResponse::Created().body(id.to_string())
+}
+
+async fn update_disabled(
+    req: HttpRequest,
+    id: i32,
+    disabled: bool,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if disabled && is_self(&req, id) {
+        return HttpResponse::BadRequest().body("You can't disable your own account");
+    }
+    if get_user_by_id(&mut database, id).await.is_none() {
+        return HttpResponse::NotFound().body("No user was found");
+    }
+
+    if set_disabled(&mut database, id, disabled).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on update of the account")
+    }
+}
+
+#[post("/users/{id}/disable")]
+pub async fn disable_user(
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    update_disabled(req, id.0, true, data).await
+}
+
+#[post("/users/{id}/enable")]
+pub async fn enable_user(
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    update_disabled(req, id.0, false, data).await
+}
+
+/// Gives a temporary password to the user, to send them. They must change it before logging in.
+#[post("/users/{id}/reset")]
+pub async fn reset_user_password(
+    id: web::Path<i32>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if get_user_by_id(&mut database, id.0).await.is_none() {
+        return HttpResponse::NotFound().body("No user was found");
+    }
+
+    let password = generate_token();
+    if reset_password(&mut database, id.0, password.clone(), config.hash_cost()).await {
+        HttpResponse::Ok().body(password)
+    } else {
+        HttpResponse::InternalServerError().body("Error on reset of the password")
+    }
+}
+
+/// For a user who lost their authenticator and their recovery codes
+#[delete("/users/{id}/totp")]
+pub async fn reset_user_totp(id: web::Path<i32>, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if get_user_by_id(&mut database, id.0).await.is_none() {
+        return HttpResponse::NotFound().body("No user was found");
+    }
+
+    if disable_totp(&mut database, id.0).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on reset of the two-factor authentication")
+    }
+}
+
+/// `?home=archive` keeps the home in a tar.gz of the `archive` folder and `?home=delete` removes
+/// it, else it is moved as it is to `archive/{name}-{id}-{date}`. The name is free again, the home
+/// is never left for the next user of the name.
+#[delete("/users/{id}")]
+pub async fn delete_user(
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if is_self(&req, id.0) {
+        return HttpResponse::BadRequest().body("You can't delete your own account");
+    }
+    let user = match get_user_by_id(&mut database, id.0).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No user was found"),
+    };
+    let home = get_args(req).get("home").cloned().unwrap_or_default();
+    let home_path = format!("./home/{}", user.name);
+    let date = Utc::now().format("%Y%m%d%H%M%S");
+
+    if home == "archive" {
+        let name = user.name.clone();
+        let target = format!("./archive/{}-{}.tar.gz", user.name, date);
+        let source = home_path.clone();
+        let archived = web::block(move || {
+            std::fs::create_dir_all("./archive")?;
+            archive_folder(Path::new(&source), &name, Path::new(&target))
+        })
+        .await;
+        if archived.is_err() {
+            return HttpResponse::InternalServerError().body("Error on archive of the home");
+        }
+    } else if home != "delete" {
+        let target = format!("./archive/{}-{}-{}", user.name, id.0, date);
+        let source = home_path.clone();
+        let moved = web::block(move || {
+            if !Path::new(&source).exists() {
+                return Ok(());
+            }
+            std::fs::create_dir_all("./archive")?;
+            std::fs::rename(&source, &target)
+        })
+        .await;
+        if moved.is_err() {
+            return HttpResponse::InternalServerError().body("Error on move of the home");
+        }
+    }
+    if !delete_user_row(&mut database, id.0).await {
+        return HttpResponse::InternalServerError().body("Error on removal of the user");
+    }
+
+    let _ = async_std::fs::remove_dir_all(trash_folder(id.0)).await;
+    let _ = async_std::fs::remove_dir_all(version_folder(id.0)).await;
+    if home == "archive" || home == "delete" {
+        let _ = async_std::fs::remove_dir_all(home_path).await;
+    }
+    HttpResponse::NoContent().finish()
+}
+
+/// Activity of every user, with the filters of `/api/user/logs` and `?user=<name>`
+#[get("/logs")]
+pub async fn list_logs(
+    params: web::Query<LogParams>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user_id = match params.user.as_deref().filter(|e| !e.is_empty()) {
+        Some(name) => match get_user_by_name(&mut database, name.to_string()).await {
+            Some(e) => e.id,
+            None => return HttpResponse::NotFound().body("No user was found"),
+        },
+        None => None,
+    };
+
+    let query = params.query(user_id);
+    log_response(&mut database, query, params.format.as_deref()).await
+}
+
+/// Files of the entries removed from the log by the retention
+#[get("/logs/archives")]
+pub async fn list_log_archives() -> HttpResponse {
+    let mut archives: Vec<LogArchive> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(LOG_ARCHIVE_FOLDER) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.ends_with(".jsonl.gz") {
+                continue;
+            }
+            let bytes = entry.metadata().map(|e| e.len()).unwrap_or_default();
+            archives.push(LogArchive { name, bytes });
+        }
+    }
+    // The newest first, the ids don't have the same number of digits
+    archives.sort_by(|a, b| {
+        archive_id(&b.name)
+            .cmp(&archive_id(&a.name))
+            .then_with(|| b.name.cmp(&a.name))
+    });
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&archives).unwrap_or_default())
+}
+
+#[get("/logs/archives/{name}")]
+pub async fn download_log_archive(req: HttpRequest, name: web::Path<String>) -> HttpResponse {
+    if name.0.contains('/') || name.0.starts_with('.') || !name.0.ends_with(".jsonl.gz") {
+        return HttpResponse::BadRequest().body("Bad name");
+    }
+    stream_file(
+        &req,
+        format!("{}/{}", LOG_ARCHIVE_FOLDER, name.0),
+        Disposition::Attachment,
+    )
+    .await
+}
+
+#[get("/stats")]
+pub async fn get_stats(data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let users = get_users(&mut database).await;
+    let homes = folder_usage(String::from("./home")).await;
+    let stats = InstanceStats {
+        users: users.len() as u64,
+        disabled_users: users.iter().filter(|e| e.disabled).count() as u64,
+        groups: get_groups(&mut database).await.len() as u64,
+        bytes: homes.bytes,
+        files: homes.files,
+        trash_bytes: folder_usage(String::from("./trash")).await.bytes,
+        versions_bytes: folder_usage(String::from("./versions")).await.bytes,
+    };
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&stats).unwrap_or_default())
+}
+
+#[get("/groups")]
+pub async fn list_groups(data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let mut groups: Vec<GroupItem> = Vec::new();
+    for group in get_groups(&mut database).await {
+        groups.push(GroupItem {
+            members: get_members(&mut database, group.id).await,
+            id: group.id,
+            name: group.name,
+            quota_bytes: group.quota_bytes,
+            quota_files: group.quota_files,
+        });
+    }
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&groups).unwrap_or_default())
+}
+
+#[post("/groups")]
+pub async fn create_group(
+    body: web::Json<NewGroup>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let name = body.name.trim().to_string();
+    if name.is_empty() {
+        return HttpResponse::BadRequest().body("The name cannot be empty");
+    }
+    if get_group_by_name(&mut database, name.clone())
+        .await
+        .is_some()
+    {
+        return HttpResponse::Conflict().body("A group already has this name");
+    }
+
+    let group = Group {
+        id: 0,
+        name: name.clone(),
+        quota_bytes: body.quota_bytes,
+        quota_files: body.quota_files,
+        date: Utc::now().to_rfc3339(),
+    };
+    if !insert_group(&mut database, &group).await {
+        return HttpResponse::InternalServerError().body("Error on creation of the group");
+    }
+    match get_group_by_name(&mut database, name).await {
+        Some(e) => HttpResponse::Created().body(e.id.to_string()),
+        None => HttpResponse::InternalServerError().body("Error on creation of the group"),
+    }
+}
+
+#[put("/groups/{id}")]
+pub async fn update_group(
+    id: web::Path<i32>,
+    body: web::Json<NewGroup>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let mut group = match get_group(&mut database, id.0).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No group was found"),
+    };
+    let name = body.name.trim().to_string();
+    if name.is_empty() {
+        return HttpResponse::BadRequest().body("The name cannot be empty");
+    }
+    if name != group.name
+        && get_group_by_name(&mut database, name.clone())
+            .await
+            .is_some()
+    {
+        return HttpResponse::Conflict().body("A group already has this name");
+    }
+
+    group.name = name;
+    group.quota_bytes = body.quota_bytes;
+    group.quota_files = body.quota_files;
+    if update_group_row(&mut database, &group).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on update of the group")
+    }
+}
+
+#[delete("/groups/{id}")]
+pub async fn delete_group(id: web::Path<i32>, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if delete_group_row(&mut database, id.0).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on removal of the group")
+    }
+}
+
+#[put("/groups/{id}/members/{user}")]
+pub async fn add_group_member(
+    path: web::Path<(i32, String)>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let (id, name) = path.into_inner();
+    if get_group(&mut database, id).await.is_none() {
+        return HttpResponse::NotFound().body("No group was found");
+    }
+    let user = match get_user_by_name(&mut database, name).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No user has this name"),
+    };
+
+    if add_member(&mut database, id, user.id.unwrap_or_default()).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on adding the member")
+    }
+}
+
+#[delete("/groups/{id}/members/{user}")]
+pub async fn remove_group_member(
+    path: web::Path<(i32, String)>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let (id, name) = path.into_inner();
+    let user = match get_user_by_name(&mut database, name).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No user has this name"),
+    };
+
+    if remove_member(&mut database, id, user.id.unwrap_or_default()).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on removal of the member")
+    }
+}
+
+#[put("/users/{id}/role")]
+pub async fn set_user_role(
+    req: HttpRequest,
+    id: web::Path<i32>,
+    body: web::Json<RoleRequest>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let role = match Role::from_name(&body.role) {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("The role must be user or admin"),
+    };
+    if role != Role::Admin && is_self(&req, id.0) {
+        return HttpResponse::BadRequest().body("You can't remove your own admin role");
+    }
+    if get_user_by_id(&mut database, id.0).await.is_none() {
+        return HttpResponse::NotFound().body("No user was found");
+    }
+
+    if set_role(&mut database, id.0, role).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on update of the role")
+    }
+}
@@ -1,69 +1,136 @@
+use crate::lib::archive::extract::{self, Limit};
+use crate::lib::config::Config;
 use crate::lib::db::log::insert::insert;
-use crate::lib::db::log::model::ActionType;
-use crate::lib::db::user::get::get_user_by_token;
-use crate::lib::db::user::valid_session::from_headers_if_valid_token_get_token;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::permission::update::move_permissions;
+use crate::lib::db::trash::insert::insert as insert_trash;
+use crate::lib::db::trash::model::Trash;
+use crate::lib::db::user::model::User;
+use crate::lib::db::user::token::generate_token;
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::db::version::update::move_versions;
+use crate::lib::file::access::{
+    owner_of, resolve, resolve_path, resolve_writable, shared_root_entry, Level, Resolution,
+    Resolved, SHARED_ROOT,
+};
 use crate::lib::file::file_trait::TraitFolder;
-use crate::lib::file::{get_dir, Sort};
+use crate::lib::file::operation::{
+    copy_with_conflict, move_path, move_with_conflict, remove, Conflict, Placement,
+};
+use crate::lib::file::quota::{home_usage, path_usage, quota_of, Usage};
+use crate::lib::file::receive::{receive, Collision, Target};
+use crate::lib::file::trash::{move_to_trash, send_to_trash};
+use crate::lib::file::version::keep_previous;
+use crate::lib::file::{dir_listing, get_dir, listing_response, sort_content, Sort};
+use crate::lib::http::auth::Auth;
 use crate::lib::{archive::*, http::get_args};
+use actix_web::error::BlockingError;
 use actix_web::{delete, get, web, HttpRequest, HttpResponse};
+use chrono::Utc;
 use datagn::DatabasePool;
 use logger::error;
-use shared::{Folder, JsonStruct};
+use shared::{ArchiveRequest, ExtractRequest, FType, Folder, JsonStruct, MoveRequest};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+fn sort_of(args: &BTreeMap<String, String>) -> Sort {
+    match args.get("sort").map(String::as_str) {
+        Some("by_size") => Sort::Size,
+        Some("by_name") | None => Sort::Name,
+        Some("by_date") => Sort::Date,
+        Some(_) => Sort::Type,
+    }
+}
+
+fn virtual_listing(content: Vec<Folder>) -> HttpResponse {
+    let folder = JsonStruct {
+        result: true,
+        lenght: content.len() as i64,
+        ftype: FType::Folder,
+        content,
+    };
+    listing_response(serde_json::to_string(&folder).unwrap_or_default())
+}
+
+/// The root of the home also lists the folders shared with the user
+async fn list(
+    database: &mut DatabasePool,
+    user: &User,
+    resolved: &Resolved,
+    sort: Sort,
+) -> HttpResponse {
+    if resolved.level != Level::Owner || !resolved.relative.is_empty() {
+        return get_dir(resolved.full_path(), sort);
+    }
+    let mut folder = dir_listing(resolved.full_path(), sort);
+    if let Some(entry) = shared_root_entry(database, user).await {
+        folder.content.retain(|e| e.name != SHARED_ROOT);
+        folder.content.push(entry);
+        folder.lenght = folder.content.len() as i64;
+    }
+    listing_response(serde_json::to_string(&folder).unwrap_or_default())
+}
 
 #[get("/file/{path:.*}")]
 pub async fn get_files(
     req: HttpRequest,
+    Auth(user): Auth,
     path: web::Path<String>,
     data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
 ) -> HttpResponse {
     let result;
 
     let mut database = data.get_ref().clone();
-    let e = if let Some(token) =
-        from_headers_if_valid_token_get_token(&mut database, req.clone()).await
-    {
-        token
-    } else {
-        return HttpResponse::BadRequest().body("Error on token");
-    };
-
     let bvec = get_args(req.clone());
-    let user = match get_user_by_token(&mut database, e.clone()).await {
+    let user_id = match user.id {
         Some(e) => e,
-        None => {
-            return HttpResponse::BadRequest().body(String::from("Error on get user"));
-        }
-    };
-    let home = if let Some(e) = user.home {
-        e
-    } else {
-        return HttpResponse::InternalServerError().body("User don't have home");
+        None => return HttpResponse::BadRequest().body("Can't get user"),
     };
 
-    let path = path.0;
+    let resolved = match resolve(&mut database, &user, &path.0).await {
+        Ok(Resolution::Path(e)) => e,
+        Ok(Resolution::Virtual(mut content)) => {
+            sort_content(&mut content, sort_of(&bvec));
+            return virtual_listing(content);
+        }
+        Err(e) => return e,
+    };
+    let fullpath = resolved.full_path();
+    let mut action = ActionType::Get;
 
     if bvec.contains_key("download") {
         match bvec.get("download").unwrap_or(&String::new()).as_ref() {
             "tar.gz" | "tar" => {
+                action = ActionType::Archive;
                 result = download(
-                    format!("{}/{}", home, path),
-                    DownloadEnum::Archive(ArchiveType::Targz),
+                    &req,
+                    fullpath.clone(),
+                    DownloadEnum::Archive(ArchiveType::Targz, Compression::Deflate),
                 )
                 .await;
             }
             "zip" => {
+                action = ActionType::Archive;
                 result = download(
-                    format!("{}/{}", home, path),
-                    DownloadEnum::Archive(ArchiveType::Zip),
+                    &req,
+                    fullpath.clone(),
+                    DownloadEnum::Archive(
+                        ArchiveType::Zip,
+                        Compression::from_name(
+                            bvec.get("compression")
+                                .map(String::as_str)
+                                .unwrap_or("deflate"),
+                        ),
+                    ),
                 )
                 .await;
             }
             _ => {
-                if let Ok(file) = std::fs::File::open(format!("{}/{}", home, path)) {
+                if let Ok(file) = std::fs::File::open(&fullpath) {
                     if let Ok(metadata) = file.metadata() {
                         if metadata.is_file() {
-                            result = download(format!("{}/{}", home, path), DownloadEnum::Download)
-                                .await;
+                            result = download(&req, fullpath.clone(), DownloadEnum::Download).await;
                         } else {
                             result = HttpResponse::BadRequest().body("Bad File");
                         }
@@ -76,182 +143,552 @@ pub async fn get_files(
             }
         }
     } else if bvec.contains_key("sort") {
-        match bvec.get("sort").unwrap_or(&String::new()).as_ref() {
-            "by_size" => {
-                result = get_dir(format!("{}/{}", home, path), Sort::Size);
-            }
-            "by_name" => {
-                result = get_dir(format!("{}/{}", home, path), Sort::Name);
-            }
-            "by_date" => {
-                result = get_dir(format!("{}/{}", home, path), Sort::Date);
-            }
-            _ => {
-                result = get_dir(format!("{}/{}", home, path), Sort::Type);
-            }
-        }
+        result = list(&mut database, &user, &resolved, sort_of(&bvec)).await;
+    } else if bvec.contains_key("archive") {
+        result = get_archive_content(fullpath.clone(), config.max_extract_entries).await
     } else if bvec.contains_key("preview") {
-        result = download(format!("{}/{}", home, path), DownloadEnum::Preview).await
+        action = ActionType::Preview;
+        result = download(&req, fullpath.clone(), DownloadEnum::Preview).await
     } else {
-        result = get_dir(format!("{}/{}", home, path), Sort::Name);
+        result = list(&mut database, &user, &resolved, Sort::Name).await;
     }
-    if let Some(e) = user.id {
-        insert(&mut database, e, ActionType::Get).await;
-    } else {
-        error("Can't log user");
+    let mut log = NewLog::new(user_id, action)
+        .owner(resolved.owner_id)
+        .path(&resolved.relative)
+        .request(&req)
+        .success(result.status().is_success());
+    if action != ActionType::Archive {
+        if let Ok(e) = async_std::fs::metadata(&fullpath).await {
+            if e.is_file() {
+                log = log.bytes(e.len());
+            }
+        }
     }
+    insert(&mut database, log).await;
     result
 }
 
 use actix_multipart::Multipart;
 use actix_web::{post, Error};
-use async_std::io::prelude::WriteExt;
-use tokio_stream::StreamExt;
 
 #[post("/file/{path:.*}")]
 pub async fn save_file(
     req: HttpRequest,
+    Auth(user): Auth,
     mut payload: Multipart,
     path: web::Path<String>,
     data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
 ) -> Result<HttpResponse, Error> {
     let mut database = data.get_ref().clone();
-    let e = if let Some(token) =
-        from_headers_if_valid_token_get_token(&mut database, req.clone()).await
-    {
-        token
-    } else {
-        return Ok(HttpResponse::BadRequest().body("Error on token"));
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return Ok(HttpResponse::BadRequest().body("Can't get user")),
     };
-
-    let url = format!("/{}", path.0);
-    let user = match get_user_by_token(&mut database, e.clone()).await {
+    let resolved = match resolve_writable(&mut database, &user, &path.0).await {
+        Ok(e) => e,
+        Err(e) => return Ok(e),
+    };
+    // The files are counted in the quota of the owner of the folder
+    let owner = match owner_of(&mut database, &resolved, user).await {
         Some(e) => e,
-        None => {
-            return Ok(HttpResponse::BadRequest().body("Can't get user"));
-        }
+        None => return Ok(HttpResponse::InternalServerError().body("Can't get the owner")),
     };
 
-    if let Some(e) = user.id {
-        insert(&mut database, e, ActionType::Upload).await;
+    let quota = quota_of(&mut database, &owner, &config).await;
+    let usage = home_usage(&owner).await;
+    let length = req
+        .headers()
+        .get("Content-Length")
+        .and_then(|e| e.to_str().ok())
+        .and_then(|e| e.parse::<u64>().ok())
+        .unwrap_or_default();
+    if let Err(e) = quota.check(usage, length, 1) {
+        return Ok(e.response());
     }
 
-    let mut result = false;
-    while let Ok(Some(mut field)) = payload.try_next().await {
-        let filename = field
-            .content_disposition()
-            .and_then(|cd| cd.get_filename().map(ToString::to_string))
-            .expect("Can't get field name!");
-        let filepath = format!(
-            "./home/{}/{}/{}",
-            user.name,
-            url.strip_prefix("/").unwrap(),
-            filename
-        );
+    let target = Target {
+        user_id: resolved.owner_id,
+        actor: user_id,
+        home: format!("./home/{}", resolved.owner),
+        folder: resolved.relative,
+        quota,
+        usage,
+        max_size: None,
+        collision: Collision::Version(config.version_retention()),
+        log: NewLog::new(user_id, ActionType::Upload).request(&req),
+    };
+    match receive(&mut database, &mut payload, target).await {
+        Ok(e) if !e.is_empty() => Ok(HttpResponse::Ok().body("The file is uploaded")),
+        Ok(_) => Ok(HttpResponse::BadRequest().body("Error on uploading the file")),
+        Err(e) => Ok(e),
+    }
+}
 
-        if cfg!(debug_assertions) {
-            println!(
-                "--------------------- Url : {}, Name: {}, Path: {} ---------------------------",
-                url, filename, filepath
-            );
-        }
-        let mut f = if let Ok(e) = async_std::fs::File::create(filepath.clone()).await {
-            e
-        } else {
-            return Ok(HttpResponse::InternalServerError().body("Error on creation of file"));
-        };
+#[delete("/file/{path:.*}")]
+pub async fn delete_file(
+    req: HttpRequest,
+    Auth(user): Auth,
+    path: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> Result<HttpResponse, Error> {
+    let mut result = JsonStruct::default();
+    let mut database = data.get_ref().clone();
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return Ok(HttpResponse::BadRequest().body("Can't get user")),
+    };
+    let resolved = match resolve_writable(&mut database, &user, &path.0).await {
+        Ok(e) => e,
+        Err(e) => return Ok(e),
+    };
+    if !resolved.can_remove() {
+        return Ok(HttpResponse::Forbidden().body("This folder can't be deleted"));
+    }
 
-        while let Some(chunk) = field.next().await {
-            match chunk {
-                Ok(e) => {
-                    f = if let Ok(e) = f.write_all(&e).await.map(|_| f) {
-                        e
-                    } else {
-                        return Ok(HttpResponse::InternalServerError().body("Error"));
-                    };
-                }
-                Err(e) => {
-                    if cfg!(features = "log") {
-                        logger::error(format!("{:?}", e));
+    // The file goes to the trash of its owner
+    let full_path = resolved.full_path();
+    let trash = Trash {
+        uid: generate_token(),
+        user_id: resolved.owner_id,
+        path: resolved.relative.clone(),
+        date: Utc::now().to_rfc3339(),
+    };
+    let moved = trash.clone();
+    let log = NewLog::new(user_id, ActionType::Delete)
+        .owner(resolved.owner_id)
+        .path(&resolved.relative)
+        .request(&req);
+    match web::block(move || move_to_trash(Path::new(&full_path), &moved)).await {
+        Ok(_) => {
+            // An item of the trash which isn't saved can't be restored, it is put back
+            if !insert_trash(&mut database, &trash).await {
+                let (from, to) = (trash.trash_path(), resolved.full_path());
+                if let Err(e) =
+                    web::block(move || move_path(Path::new(&from), Path::new(&to))).await
+                {
+                    if cfg!(feature = "log") {
+                        error(format!("Can't put back {} : {:?}", resolved.full_path(), e));
                     }
                 }
+                insert(&mut database, log.success(false)).await;
+                return Ok(HttpResponse::InternalServerError()
+                    .body("Error on saving the item in the trash"));
             }
+            result.result = true;
+            result.content.push(Folder::new(
+                true,
+                path.0.trim_matches('/').to_string(),
+                0,
+                String::from("0-0-0000 00:00:00"),
+                String::from("0-0-0000 00:00:00"),
+                "File".to_string(),
+            ));
+            insert(&mut database, log).await;
+        }
+        Err(e) => {
+            insert(&mut database, log.success(false)).await;
+            result.content.push(Folder::error(e.to_string()))
+        }
+    };
+    Ok(HttpResponse::Ok()
+        .header("charset", "utf-8")
+        .header("Access-Control-Allow-Origin", "*")
+        .body(serde_json::to_string(&result).unwrap()))
+}
+
+#[post("/archive")]
+pub async fn get_archive(
+    req: HttpRequest,
+    body: web::Json<ArchiveRequest>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = user.id.unwrap_or_default();
+
+    let atype = match body.format.as_str() {
+        "zip" => ArchiveType::Zip,
+        "tar.gz" | "tar" => ArchiveType::Targz,
+        _ => return HttpResponse::BadRequest().body("Bad archive format"),
+    };
+    let mut paths: Vec<String> = body
+        .paths
+        .iter()
+        .map(|e| e.trim_matches('/').to_string())
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut roots: Vec<Entry> = Vec::new();
+    let mut logs: Vec<NewLog> = Vec::new();
+    for path in &paths {
+        // A file inside a selected folder is already in the archive
+        if paths
+            .iter()
+            .any(|e| e != path && (e.is_empty() || path.starts_with(&format!("{}/", e))))
+        {
+            continue;
+        }
+        let resolved = match resolve_path(&mut database, &user, path).await {
+            Ok(e) => e,
+            Err(e) => return e,
+        };
+        let fullpath = resolved.full_path();
+        if async_std::fs::metadata(fullpath.clone()).await.is_err() {
+            return HttpResponse::BadRequest().body(format!("No file : {}", path));
         }
-        result = true;
+        logs.push(
+            NewLog::new(user_id, ActionType::Archive)
+                .owner(resolved.owner_id)
+                .path(&resolved.relative)
+                .request(&req),
+        );
+        roots.push(Entry {
+            name: if path.is_empty() {
+                user.name.clone()
+            } else {
+                path.clone()
+            },
+            path: PathBuf::from(fullpath),
+        });
     }
-    if result {
-        return Ok(HttpResponse::Ok().body("The file is uploaded"));
-    } else {
-        return Ok(HttpResponse::BadRequest().body("Error on uploading the file"));
+    if roots.is_empty() {
+        return HttpResponse::BadRequest().body("No file was selected");
     }
+
+    for log in logs {
+        insert(&mut database, log).await;
+    }
+    archive_response(
+        "selection",
+        roots,
+        atype,
+        Compression::from_name(body.compression.as_deref().unwrap_or("deflate")),
+    )
 }
 
-#[delete("/file/{path:.*}")]
-pub async fn delete_file(
+#[post("/extract/{path:.*}")]
+pub async fn extract_archive(
     req: HttpRequest,
     path: web::Path<String>,
+    body: web::Json<ExtractRequest>,
     data: web::Data<DatabasePool>,
-) -> Result<HttpResponse, Error> {
-    let mut result = JsonStruct::default();
+    config: web::Data<Config>,
+) -> HttpResponse {
     let mut database = data.get_ref().clone();
-    let e = if let Some(token) =
-        from_headers_if_valid_token_get_token(&mut database, req.clone()).await
-    {
-        token
-    } else {
-        return Ok(HttpResponse::BadRequest().body("Error on token"));
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
     };
 
-    let user = match get_user_by_token(&mut database, e.clone()).await {
+    let user_id = user.id.unwrap_or_default();
+
+    let source = match resolve_path(&mut database, &user, &path.0).await {
+        Ok(e) => e,
+        Err(e) => return e,
+    };
+    let archive = source.full_path();
+    let resolved = match resolve_writable(&mut database, &user, &body.target).await {
+        Ok(e) => e,
+        Err(e) => return e,
+    };
+    let target = resolved.full_path();
+    match async_std::fs::metadata(archive.clone()).await {
+        Ok(e) if e.is_file() => {}
+        _ => return HttpResponse::BadRequest().body("Bad File"),
+    }
+    // The extracted files are counted in the quota of the owner of the target
+    let owner = match owner_of(&mut database, &resolved, user).await {
         Some(e) => e,
-        None => return Ok(HttpResponse::BadRequest().body("Can't get user")),
+        None => return HttpResponse::InternalServerError().body("Can't get the owner"),
     };
-    if cfg!(debug_assertions) {
-        println!("./home/{}/{}", user.name, path.0);
+    let quota = quota_of(&mut database, &owner, &config).await;
+    let usage = home_usage(&owner).await;
+    if let Err(e) = quota.check(usage, 0, 0) {
+        return e.response();
     }
-    if async_std::fs::metadata(format!("./home/{}/{}", user.name, path.0))
-        .await
-        .unwrap()
-        .is_dir()
+    let remaining = quota.remaining(usage);
+    if async_std::fs::create_dir_all(target.clone()).await.is_err() {
+        return HttpResponse::InternalServerError().body("Can't create the target folder");
+    }
+
+    let limit = Limit {
+        size: config.max_extract_size.min(remaining.bytes),
+        ratio: config.max_extract_ratio,
+        entries: config.max_extract_entries,
+        files: remaining.files,
+    };
+    let entries = body.entries.clone();
+    let log = NewLog::new(user_id, ActionType::Extract)
+        .owner(resolved.owner_id)
+        .path(&source.relative)
+        .destination(&resolved.relative)
+        .request(&req);
+    match web::block(move || {
+        extract::extract(
+            &PathBuf::from(archive),
+            &PathBuf::from(target),
+            entries.as_deref(),
+            limit,
+        )
+    })
+    .await
     {
-        match async_std::fs::remove_dir_all(format!("./home/{}/{}", user.name, path.0)).await {
-            Ok(_) => {
-                result.result = true;
-                result.content.push(Folder::new(
-                    true,
-                    path.0,
-                    0,
-                    String::from("0-0-0000 00:00:00"),
-                    String::from("0-0-0000 00:00:00"),
-                    "File".to_string(),
-                ));
-                if let Some(id) = user.id {
-                    insert(&mut database, id, ActionType::Delete).await;
+        Ok(size) => {
+            insert(&mut database, log.bytes(size)).await;
+            HttpResponse::Ok().body(format!("{} bytes were extracted", size))
+        }
+        Err(e) => {
+            insert(&mut database, log.success(false)).await;
+            HttpResponse::BadRequest().body(e.to_string())
+        }
+    }
+}
+
+/// Sends the item overwritten by a move or a copy to the versions, or to the trash if it is a
+/// folder, then puts the new content in its place
+async fn replace(
+    database: &mut DatabasePool,
+    config: &Config,
+    to: &Resolved,
+    target: &Placement,
+) -> std::io::Result<PathBuf> {
+    let replaced = match &target.replaced {
+        Some(e) => e.to_string_lossy().to_string(),
+        None => return Ok(target.path.clone()),
+    };
+    if Path::new(&replaced).is_dir() {
+        send_to_trash(database, to.owner_id, &to.relative, &replaced).await?;
+    } else {
+        let retention = config.version_retention();
+        keep_previous(
+            database,
+            to.owner_id,
+            to.relative.clone(),
+            &replaced,
+            retention,
+        )
+        .await?;
+    }
+    let finished = target.clone();
+    web::block(move || finished.finish())
+        .await
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+}
+
+async fn file_operation(
+    req: HttpRequest,
+    path: String,
+    body: MoveRequest,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+    copy: bool,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = user.id.unwrap_or_default();
+
+    // A copy only reads the source, a move removes it
+    let from = match resolve_path(&mut database, &user, &path).await {
+        Ok(e) if copy || e.can_remove() => e,
+        Ok(_) => return HttpResponse::Forbidden().body("This file can't be moved"),
+        Err(e) => return e,
+    };
+    let to = match resolve_writable(&mut database, &user, &body.to).await {
+        Ok(e) if !e.relative.is_empty() && !e.shared_root => e,
+        Ok(_) => return HttpResponse::BadRequest().body("Bad destination"),
+        Err(e) => return e,
+    };
+    if from.relative.is_empty() {
+        return HttpResponse::BadRequest().body("Stay at home please");
+    }
+    // A copy, or a move to the home of someone else, is counted in the quota of the destination
+    if copy || from.owner_id != to.owner_id {
+        let owner = match owner_of(&mut database, &to, user).await {
+            Some(e) => e,
+            None => return HttpResponse::InternalServerError().body("Can't get the owner"),
+        };
+        let source = from.full_path();
+        let added = web::block(move || Ok::<Usage, ()>(path_usage(Path::new(&source))))
+            .await
+            .unwrap_or_default();
+        let usage = home_usage(&owner).await;
+        if let Err(e) =
+            quota_of(&mut database, &owner, &config)
+                .await
+                .check(usage, added.bytes, added.files)
+        {
+            return e.response();
+        }
+    }
+
+    let conflict = Conflict::from_name(body.conflict.as_deref());
+    let (from_buf, to_buf) = (
+        PathBuf::from(from.full_path()),
+        PathBuf::from(to.full_path()),
+    );
+    let action = if copy {
+        ActionType::Copy
+    } else if from_buf.parent() == to_buf.parent() {
+        ActionType::Rename
+    } else {
+        ActionType::Move
+    };
+    let log = NewLog::new(user_id, action)
+        .owner(from.owner_id)
+        .path(&from.relative)
+        .request(&req);
+    let source = from_buf.clone();
+    let written = web::block(move || {
+        if copy {
+            copy_with_conflict(&from_buf, &to_buf, conflict)
+        } else {
+            move_with_conflict(&from_buf, &to_buf, conflict)
+        }
+    })
+    .await;
+    let done = match written {
+        Ok(target) => match replace(&mut database, &config, &to, &target).await {
+            Ok(e) => Ok(e),
+            Err(e) => {
+                // The new content goes back where it comes from
+                let _ = web::block(move || {
+                    if copy {
+                        remove(&target.path)
+                    } else {
+                        move_path(&target.path, &source)
+                    }
+                })
+                .await;
+                if cfg!(feature = "log") {
+                    error(format!("Can't overwrite {} : {:?}", to.relative, e));
+                }
+                insert(&mut database, log.destination(&to.relative).success(false)).await;
+                return HttpResponse::InternalServerError().body("Error on overwrite");
+            }
+        },
+        Err(e) => Err(e),
+    };
+    match done {
+        Ok(e) => {
+            let moved = e
+                .strip_prefix(format!("./home/{}", to.owner))
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_default();
+            insert(&mut database, log.clone().destination(&moved)).await;
+            if to.owner_id != from.owner_id {
+                let action = if copy {
+                    ActionType::Copy
                 } else {
-                    error("Error on log");
+                    ActionType::Move
+                };
+                let log = NewLog {
+                    owner_id: to.owner_id,
+                    action,
+                    ..log
+                };
+                insert(&mut database, log.destination(&moved)).await;
+            }
+            // The grants and the versions follow an item moved inside the home of its owner
+            if !copy && from.owner_id == to.owner_id {
+                move_permissions(
+                    &mut database,
+                    from.owner_id,
+                    from.relative.clone(),
+                    moved.clone(),
+                )
+                .await;
+                move_versions(&mut database, from.owner_id, from.relative, moved).await;
+            }
+            // The new name as seen by the user, it may have been changed by the conflict policy
+            let name = Path::new(body.to.trim_matches('/'))
+                .with_file_name(e.file_name().unwrap_or_default())
+                .to_string_lossy()
+                .to_string();
+            HttpResponse::Ok().body(name)
+        }
+        Err(e) => {
+            insert(&mut database, log.destination(&to.relative).success(false)).await;
+            match e {
+                BlockingError::Error(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    HttpResponse::Conflict().body(e.to_string())
                 }
+                e => HttpResponse::BadRequest().body(e.to_string()),
             }
-            Err(e) => result.content.push(Folder::error(e.to_string())),
-        };
-    } else {
-        match async_std::fs::remove_file(format!("./home/{}/{}", user.name, path.0)).await {
-            Ok(_) => {
-                result.result = true;
-                result.content.push(Folder::new(
-                    true,
-                    path.0,
-                    0,
-                    String::from("0-0-0000 00:00:00"),
-                    String::from("0-0-0000 00:00:00"),
-                    "File".to_string(),
-                ));
-                insert(&mut database, user.id.unwrap(), ActionType::Delete).await;
+        }
+    }
+}
+
+#[post("/move/{path:.*}")]
+pub async fn move_file(
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<MoveRequest>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    file_operation(req, path.0, body.into_inner(), data, config, false).await
+}
+
+#[post("/copy/{path:.*}")]
+pub async fn copy_file(
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<MoveRequest>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    file_operation(req, path.0, body.into_inner(), data, config, true).await
+}
+
+#[post("/mkdir/{path:.*}")]
+pub async fn create_folder(
+    req: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    let resolved = match resolve_writable(&mut database, &user, &path.0).await {
+        Ok(e) if !e.relative.is_empty() => e,
+        Ok(_) => return HttpResponse::BadRequest().body("Stay at home please"),
+        Err(e) => return e,
+    };
+    let path = path.0.trim_matches('/').to_string();
+    let folder = resolved.full_path();
+
+    match async_std::fs::metadata(&folder).await {
+        Ok(e) if e.is_dir() => return HttpResponse::Ok().body(path),
+        Ok(_) => return HttpResponse::Conflict().body("A file already has this name"),
+        Err(_) => {}
+    }
+    match async_std::fs::create_dir_all(&folder).await {
+        Ok(_) => {
+            if let Some(id) = user.id {
+                let log = NewLog::new(id, ActionType::Mkdir)
+                    .owner(resolved.owner_id)
+                    .path(&resolved.relative)
+                    .request(&req);
+                insert(&mut database, log).await;
             }
-            Err(e) => result.content.push(Folder::error(e.to_string())),
-        };
+            HttpResponse::Created().body(path)
+        }
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on create folder : {:?}", e));
+            }
+            HttpResponse::BadRequest().body(e.to_string())
+        }
     }
-    Ok(HttpResponse::Ok()
-        .header("charset", "utf-8")
-        .header("Access-Control-Allow-Origin", "*")
-        .body(serde_json::to_string(&result).unwrap()))
 }
@@ -0,0 +1,127 @@
+//! Search in the activity log, for the user and for the admins.
+//!
+//! `?action=`, `?from=`, `?to=` and `?path=` filter the entries. The latest come first, a page
+//! has `?limit=` entries (100 by default) and the next one starts at `?before=<next>`. The
+//! answer is JSON, or CSV with `?format=csv` and the cursor in `X-Next-Cursor`.
+
+use crate::lib::db::log::get::get_logs;
+use crate::lib::db::log::model::LogQuery;
+use crate::lib::http::auth::Auth;
+use actix_web::{get, web, HttpResponse};
+use datagn::DatabasePool;
+use serde::Deserialize;
+use shared::{LogItem, LogPage};
+
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct LogParams {
+    pub action: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub path: Option<String>,
+    pub before: Option<i32>,
+    pub limit: Option<i64>,
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+    /// Name of the user, only for the admins
+    pub user: Option<String>,
+}
+
+impl LogParams {
+    pub fn query(&self, user_id: Option<i32>) -> LogQuery {
+        LogQuery {
+            user_id,
+            action: self.action.clone(),
+            from: self.from.clone(),
+            to: self.to.clone(),
+            path: self.path.clone(),
+            before: self.before,
+            limit: self.limit.filter(|e| *e > 0).unwrap_or(100).min(MAX_LIMIT),
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    // A spreadsheet runs a cell starting with one of those as a formula, a path or a user agent
+    // can be chosen by anyone
+    let value = if value.starts_with(&['=', '+', '-', '@', '\t', '\r'][..]) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn to_csv(items: &[LogItem]) -> String {
+    let mut csv =
+        String::from("id,date,action,user,owner,path,destination,bytes,ip,user_agent,success\r\n");
+    for item in items {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{}\r\n",
+            item.id,
+            csv_field(&item.date),
+            csv_field(&item.action),
+            csv_field(&item.user),
+            csv_field(&item.owner),
+            csv_field(item.path.as_deref().unwrap_or_default()),
+            csv_field(item.destination.as_deref().unwrap_or_default()),
+            item.bytes.map(|e| e.to_string()).unwrap_or_default(),
+            csv_field(item.ip.as_deref().unwrap_or_default()),
+            csv_field(item.user_agent.as_deref().unwrap_or_default()),
+            item.success,
+        ));
+    }
+    csv
+}
+
+/// Runs the search and answers in the format of the request
+pub async fn log_response(
+    database: &mut DatabasePool,
+    mut query: LogQuery,
+    format: Option<&str>,
+) -> HttpResponse {
+    let limit = query.limit;
+    // One more entry tells if there is a next page
+    query.limit += 1;
+    let mut items: Vec<LogItem> = get_logs(database, &query)
+        .await
+        .into_iter()
+        .map(|(log, user, owner)| log.item(user, owner))
+        .collect();
+    let next = if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+        items.last().map(|e| e.id)
+    } else {
+        None
+    };
+
+    if format == Some("csv") {
+        let mut response = HttpResponse::Ok();
+        response.content_type("text/csv; charset=utf-8");
+        if let Some(next) = next {
+            response.header("X-Next-Cursor", next.to_string());
+        }
+        response.body(to_csv(&items))
+    } else {
+        HttpResponse::Ok()
+            .content_type("application/json")
+            .body(serde_json::to_string(&LogPage { items, next }).unwrap_or_default())
+    }
+}
+
+/// Actions of the user and of the others on their files
+#[get("/user/logs")]
+pub async fn my_logs(
+    Auth(user): Auth,
+    params: web::Query<LogParams>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let query = params.query(Some(user.id.unwrap_or_default()));
+    log_response(&mut database, query, params.format.as_deref()).await
+}
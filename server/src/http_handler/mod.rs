@@ -1,3 +1,12 @@
+pub mod admin;
 pub mod default;
 pub mod files;
+pub mod logs;
+pub mod permission;
+pub mod sessions;
+pub mod share;
+pub mod totp;
+pub mod trash;
+pub mod upload;
 pub mod users;
+pub mod version;
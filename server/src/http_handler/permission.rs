@@ -0,0 +1,187 @@
+//! Folders shared between users.
+//!
+//! The owner gives access to a folder of their home with `POST /api/permission/{path}` and a JSON
+//! body `{"user": "bob", "access": "read"}` (or `write`), or `{"group": "team", ...}` to share it
+//! with every member of a group. Giving it again changes the access.
+//! The folder is then found by the other user under `Shared with me/<owner>/<folder>` in the
+//! files API.
+
+use crate::lib::db::group::get::get_group_by_name;
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::permission::delete::delete_permission;
+use crate::lib::db::permission::get::get_permissions_of_owner;
+use crate::lib::db::permission::insert::insert as insert_permission;
+use crate::lib::db::permission::model::{Access, GranteeType, Permission};
+use crate::lib::db::permission::update::set_access;
+use crate::lib::db::user::get::get_user_by_name;
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::file::access::{resolve_path, Level};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use datagn::DatabasePool;
+use shared::{NewPermission, PermissionItem};
+
+fn permission_item(permission: Permission, grantee: String) -> PermissionItem {
+    PermissionItem {
+        id: permission.id,
+        path: permission.path,
+        grantee_type: permission.grantee_type.format(),
+        grantee,
+        access: permission.access.format(),
+        date: permission.date,
+    }
+}
+
+async fn find(
+    database: &mut DatabasePool,
+    owner_id: i32,
+    path: &str,
+    grantee_type: GranteeType,
+    grantee_id: i32,
+) -> Option<Permission> {
+    get_permissions_of_owner(database, owner_id)
+        .await
+        .into_iter()
+        .map(|(e, _)| e)
+        .find(|e| e.path == path && e.grantee_type == grantee_type && e.grantee_id == grantee_id)
+}
+
+#[post("/permission/{path:.*}")]
+pub async fn grant_permission(
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<NewPermission>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+
+    // A folder shared with the user can't be shared again
+    let resolved = match resolve_path(&mut database, &user, &path.0).await {
+        Ok(e) if e.level == Level::Owner => e,
+        Ok(_) => return HttpResponse::Forbidden().body("Only the owner can share a folder"),
+        Err(e) => return e,
+    };
+    match async_std::fs::metadata(resolved.full_path()).await {
+        Ok(e) if e.is_dir() => {}
+        Ok(_) => return HttpResponse::BadRequest().body("Only a folder can be shared"),
+        Err(_) => return HttpResponse::NotFound().body("No file"),
+    }
+    let access = match Access::from_name(&body.access) {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("The access must be read or write"),
+    };
+    let (grantee_type, grantee_id, grantee) = match (&body.user, &body.group) {
+        (_, Some(group)) => match get_group_by_name(&mut database, group.clone()).await {
+            Some(e) => (GranteeType::Group, e.id, e.name),
+            None => return HttpResponse::NotFound().body("No group has this name"),
+        },
+        (Some(name), None) => match get_user_by_name(&mut database, name.clone()).await {
+            Some(e) if e.id != user.id => (GranteeType::User, e.id.unwrap_or_default(), e.name),
+            Some(_) => {
+                return HttpResponse::BadRequest().body("A folder can't be shared with yourself")
+            }
+            None => return HttpResponse::NotFound().body("No user has this name"),
+        },
+        (None, None) => return HttpResponse::BadRequest().body("A user or a group is required"),
+    };
+
+    let (permission, mut status) = match find(
+        &mut database,
+        user_id,
+        &resolved.relative,
+        grantee_type,
+        grantee_id,
+    )
+    .await
+    {
+        Some(mut permission) => {
+            if !set_access(&mut database, permission.id, access).await {
+                return HttpResponse::InternalServerError().body("Error on update of the access");
+            }
+            permission.access = access;
+            (permission, HttpResponse::Ok())
+        }
+        None => {
+            let permission = Permission {
+                id: 0,
+                owner_id: user_id,
+                path: resolved.relative.clone(),
+                grantee_type,
+                grantee_id,
+                access,
+                date: Utc::now().to_rfc3339(),
+            };
+            if !insert_permission(&mut database, &permission).await {
+                return HttpResponse::InternalServerError().body("Error on creation of the access");
+            }
+            // The id is given by the database
+            match find(
+                &mut database,
+                user_id,
+                &resolved.relative,
+                grantee_type,
+                grantee_id,
+            )
+            .await
+            {
+                Some(e) => (e, HttpResponse::Created()),
+                None => (permission, HttpResponse::Created()),
+            }
+        }
+    };
+    let log = NewLog::new(user_id, ActionType::Share)
+        .path(&resolved.relative)
+        .request(&req);
+    insert(&mut database, log).await;
+
+    status
+        .content_type("application/json")
+        .body(serde_json::to_string(&permission_item(permission, grantee)).unwrap_or_default())
+}
+
+#[get("/permission")]
+pub async fn list_permissions(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    let permissions: Vec<PermissionItem> =
+        get_permissions_of_owner(&mut database, user.id.unwrap_or_default())
+            .await
+            .into_iter()
+            .map(|(permission, grantee)| permission_item(permission, grantee))
+            .collect();
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&permissions).unwrap_or_default())
+}
+
+#[delete("/permission/{id}")]
+pub async fn revoke_permission(
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    if delete_permission(&mut database, id.0, user.id.unwrap_or_default()).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on removal of the access")
+    }
+}
@@ -0,0 +1,109 @@
+//! Sessions of the user: each login creates one, with its own token.
+
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::session::delete::{delete_session, delete_sessions_of_user};
+use crate::lib::db::session::get::{get_session_by_token, get_sessions_of_user};
+use crate::lib::db::user::valid_session::{from_headers_get_user, token_of};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use datagn::DatabasePool;
+use shared::SessionItem;
+
+#[get("/user/sessions")]
+pub async fn list_sessions(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let current = get_session_by_token(&mut database, &token_of(&req))
+        .await
+        .map(|e| e.id);
+
+    let sessions: Vec<SessionItem> =
+        get_sessions_of_user(&mut database, user.id.unwrap_or_default())
+            .await
+            .into_iter()
+            .filter(|e| !e.is_expired())
+            .map(|e| SessionItem {
+                current: Some(e.id) == current,
+                id: e.id,
+                created: e.created,
+                last_seen: e.last_seen,
+                expire: e.expire,
+                user_agent: e.user_agent,
+                ip: e.ip,
+            })
+            .collect();
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&sessions).unwrap_or_default())
+}
+
+#[delete("/user/sessions/{id}")]
+pub async fn revoke_session(
+    req: HttpRequest,
+    id: web::Path<i32>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    match delete_session(&mut database, id.0, user.id.unwrap_or_default()).await {
+        Some(true) => HttpResponse::Ok().body("The session is revoked"),
+        Some(false) => HttpResponse::NotFound().body("No such session"),
+        None => HttpResponse::InternalServerError().body("Error on revoking the session"),
+    }
+}
+
+#[post("/user/logout")]
+pub async fn logout(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let session = match get_session_by_token(&mut database, &token_of(&req)).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    let user_id = user.id.unwrap_or_default();
+    if delete_session(&mut database, session.id, user_id)
+        .await
+        .is_some()
+    {
+        insert(
+            &mut database,
+            NewLog::new(user_id, ActionType::Logout).request(&req),
+        )
+        .await;
+        HttpResponse::Ok().body("Logged out")
+    } else {
+        HttpResponse::InternalServerError().body("Error on logout")
+    }
+}
+
+#[post("/user/logout/all")]
+pub async fn logout_everywhere(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    let user_id = user.id.unwrap_or_default();
+    if delete_sessions_of_user(&mut database, user_id).await {
+        insert(
+            &mut database,
+            NewLog::new(user_id, ActionType::Logout).request(&req),
+        )
+        .await;
+        HttpResponse::Ok().body("Logged out everywhere")
+    } else {
+        HttpResponse::InternalServerError().body("Error on logout")
+    }
+}
@@ -0,0 +1,386 @@
+//! Share links give access to a file or a folder without an account.
+//!
+//! The owner creates a link with `POST /api/share/{path}`, the item is then served by
+//! `GET /api/public/{slug}/file/{path}`: the file itself, or the content of a folder, a file
+//! inside it, or `?download=zip|tar.gz` for an archive. A password is given with the
+//! `Share-Password` header, a query would end in the logs of the proxies. The wrong passwords
+//! are throttled like the logins, by link and by IP.
+//!
+//! A link of kind `upload` is a drop box: files are sent to the folder with a multipart form on
+//! `POST /api/public/{slug}/upload`, nothing can be listed or downloaded.
+
+use crate::lib::archive::{get_tar, get_zip, Compression};
+use crate::lib::config::Config;
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::share::delete::delete_share;
+use crate::lib::db::share::get::{get_share, get_shares_of_user};
+use crate::lib::db::share::insert::insert as insert_share;
+use crate::lib::db::share::model::{Share, ShareKind};
+use crate::lib::db::share::update::add_download;
+use crate::lib::db::user::get::get_user_by_id;
+use crate::lib::db::user::token::generate_token;
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::db::user::{hash_password, verify_password, Verified};
+use crate::lib::file::file_trait::TraitFolder;
+use crate::lib::file::quota::{home_usage, quota_of};
+use crate::lib::file::receive::{receive, Collision, Target};
+use crate::lib::file::stream::{first_byte, stream_file, Disposition};
+use crate::lib::file::{inhome, listing_response, sort_content, Sort};
+use crate::lib::http::get_args;
+use crate::lib::http::throttle::{ip_of, LoginThrottle, TooManyRequests};
+use actix_multipart::Multipart;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, ResponseError};
+use chrono::{DateTime, Utc};
+use datagn::DatabasePool;
+use shared::{FType, Folder, JsonStruct, NewShare, ShareItem};
+
+impl From<Share> for ShareItem {
+    fn from(share: Share) -> Self {
+        ShareItem {
+            slug: share.slug,
+            path: share.path,
+            kind: share.kind.format(),
+            password: share.password.is_some(),
+            expire: share.expire,
+            max_downloads: share.max_downloads,
+            downloads: share.downloads,
+            max_size: share.max_size,
+            date: share.date,
+        }
+    }
+}
+
+#[post("/share/{path:.*}")]
+pub async fn create_share(
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<NewShare>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+
+    let relative = path.0.trim_matches('/').to_string();
+    let full_path = format!("./home/{}/{}", user.name, relative);
+    if !inhome(full_path.clone()) {
+        return HttpResponse::BadRequest().body("Stay at home please");
+    }
+    let kind = ShareKind::from_name(body.kind.as_deref().unwrap_or_default());
+    match async_std::fs::metadata(&full_path).await {
+        Ok(e) if kind == ShareKind::Upload && !e.is_dir() => {
+            return HttpResponse::BadRequest().body("A drop box must be a folder")
+        }
+        Ok(_) => {}
+        Err(_) => return HttpResponse::NotFound().body("No file"),
+    }
+    let expire = match &body.expire {
+        Some(e) => match DateTime::parse_from_rfc3339(e) {
+            Ok(e) => Some(e.with_timezone(&Utc).to_rfc3339()),
+            Err(_) => return HttpResponse::BadRequest().body("Bad expiry date"),
+        },
+        None => None,
+    };
+
+    let password = match body.password.clone().filter(|e| !e.is_empty()) {
+        Some(e) => match hash_password(e, config.hash_cost()).await {
+            Some(e) => Some(e),
+            None => return HttpResponse::InternalServerError().body("Error on the password"),
+        },
+        None => None,
+    };
+
+    let share = Share {
+        slug: generate_token(),
+        user_id,
+        path: relative,
+        kind,
+        password,
+        expire,
+        max_downloads: body.max_downloads.filter(|e| *e > 0),
+        downloads: 0,
+        max_size: body.max_size.filter(|e| *e > 0),
+        date: Utc::now().to_rfc3339(),
+    };
+    if !insert_share(&mut database, &share).await {
+        return HttpResponse::InternalServerError().body("Error on creation of the share");
+    }
+    let log = NewLog::new(user_id, ActionType::Share)
+        .path(&share.path)
+        .request(&req);
+    insert(&mut database, log).await;
+
+    HttpResponse::Created()
+        .content_type("application/json")
+        .body(serde_json::to_string(&ShareItem::from(share)).unwrap_or_default())
+}
+
+#[get("/share")]
+pub async fn list_shares(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    let shares: Vec<ShareItem> = get_shares_of_user(&mut database, user.id.unwrap_or_default())
+        .await
+        .into_iter()
+        .map(ShareItem::from)
+        .collect();
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&shares).unwrap_or_default())
+}
+
+#[delete("/share/{slug}")]
+pub async fn revoke_share(
+    req: HttpRequest,
+    slug: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = user.id.unwrap_or_default();
+    match get_share(&mut database, slug.0.clone()).await {
+        Some(e) if e.user_id == user_id => {}
+        _ => return HttpResponse::NotFound().body("No share was found"),
+    }
+
+    if delete_share(&mut database, slug.0.clone(), user_id).await {
+        HttpResponse::Ok().body("The share is revoked")
+    } else {
+        HttpResponse::InternalServerError().body("Error on revoking the share")
+    }
+}
+
+/// Checks that the link can still be used and that the password is right
+async fn open_share(
+    req: &HttpRequest,
+    database: &mut DatabasePool,
+    slug: String,
+    config: &Config,
+    throttle: &LoginThrottle,
+) -> Result<Share, HttpResponse> {
+    let share = match get_share(database, slug).await {
+        Some(e) => e,
+        None => return Err(HttpResponse::NotFound().body("No share was found")),
+    };
+    if share.is_expired() || share.is_exhausted() {
+        return Err(HttpResponse::Gone().body("This link is not available anymore"));
+    }
+    if let Some(hash) = &share.password {
+        let (ip_key, share_key) = (
+            format!("ip:{}", ip_of(req)),
+            format!("share:{}", share.slug),
+        );
+        if let Some(seconds) = throttle
+            .retry_after(&ip_key)
+            .or_else(|| throttle.retry_after(&share_key))
+        {
+            return Err(TooManyRequests(seconds).error_response());
+        }
+        let password = req
+            .headers()
+            .get("Share-Password")
+            .and_then(|e| e.to_str().ok())
+            .map(String::from);
+        let verified = match &password {
+            Some(e) => verify_password(e.clone(), hash.clone(), config.hash_cost()).await,
+            None => Verified::Invalid,
+        };
+        // The links made before Argon2id keep their old hash
+        if let Verified::Invalid = verified {
+            if password.is_some() {
+                throttle.hit(&ip_key, config.login_ip_attempts);
+                throttle.hit(&share_key, config.login_attempts);
+            }
+            return Err(HttpResponse::Unauthorized()
+                .header("WWW-Authenticate", "Share-Password")
+                .body("A password is required"));
+        }
+        throttle.clear(&share_key);
+    }
+    Ok(share)
+}
+
+/// The next requests of a download which is resumed are not counted, a request which gets the
+/// start of the file is
+fn is_new_download(req: &HttpRequest, metadata: &std::fs::Metadata) -> bool {
+    first_byte(req, metadata) == Some(0)
+}
+
+/// Content of a folder of the link. The names are relative to the shared folder, nothing of the
+/// home of the owner is shown.
+fn public_listing(full_path: &str, sub: &str) -> JsonStruct {
+    let mut content: Vec<Folder> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(full_path) {
+        for entry in entries.flatten() {
+            let metadata = match entry.metadata() {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let mut folder =
+                Folder::from_metadata(metadata, entry.path().to_string_lossy().to_string());
+            folder.name = if sub.is_empty() {
+                name
+            } else {
+                format!("{}/{}", sub, name)
+            };
+            content.push(folder);
+        }
+    }
+    sort_content(&mut content, Sort::Name);
+    JsonStruct {
+        result: true,
+        lenght: content.len() as i64,
+        ftype: FType::Folder,
+        content,
+    }
+}
+
+#[get("/public/{slug}/file/{path:.*}")]
+pub async fn public_file(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+    throttle: web::Data<LoginThrottle>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let (slug, sub) = path.into_inner();
+    let share = match open_share(&req, &mut database, slug, &config, &throttle).await {
+        Ok(e) => e,
+        Err(e) => return e,
+    };
+    if share.kind == ShareKind::Upload {
+        return HttpResponse::Forbidden().body("This link only accepts uploads");
+    }
+    let owner = match get_user_by_id(&mut database, share.user_id).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No share was found"),
+    };
+
+    // Only what is inside of the shared folder can be reached
+    let sub = sub.trim_matches('/').to_string();
+    if sub.split('/').any(|e| e == "..") {
+        return HttpResponse::BadRequest().body("Bad path");
+    }
+    let base = format!("./home/{}/{}", owner.name, share.path);
+    let full_path = if sub.is_empty() {
+        base
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), sub)
+    };
+    if !inhome(full_path.clone()) {
+        return HttpResponse::BadRequest().body("Bad path");
+    }
+    let metadata = match async_std::fs::metadata(&full_path).await {
+        Ok(e) => e,
+        Err(_) => return HttpResponse::NotFound().body("No file"),
+    };
+
+    let args = get_args(req.clone());
+    if metadata.is_dir() && !args.contains_key("download") {
+        return listing_response(
+            serde_json::to_string(&public_listing(&full_path, &sub)).unwrap_or_default(),
+        );
+    }
+
+    if metadata.is_dir() || is_new_download(&req, &metadata) {
+        // The other requests which passed `is_exhausted` may have taken the last downloads
+        if !add_download(&mut database, share.slug.clone()).await {
+            return HttpResponse::Gone().body("This link is not available anymore");
+        }
+        let relative = format!("{}/{}", share.path, sub);
+        let log = if metadata.is_file() {
+            NewLog::new(share.user_id, ActionType::Get).bytes(metadata.len())
+        } else {
+            NewLog::new(share.user_id, ActionType::Archive)
+        };
+        insert(&mut database, log.path(&relative).request(&req)).await;
+    }
+    if metadata.is_file() {
+        return stream_file(&req, full_path, Disposition::Attachment).await;
+    }
+    match args.get("download").map(String::as_str) {
+        Some("tar.gz") | Some("tar") => get_tar(full_path).await,
+        _ => {
+            get_zip(
+                full_path,
+                Compression::from_name(
+                    args.get("compression")
+                        .map(String::as_str)
+                        .unwrap_or("deflate"),
+                ),
+            )
+            .await
+        }
+    }
+}
+
+#[post("/public/{slug}/upload")]
+pub async fn public_upload(
+    req: HttpRequest,
+    slug: web::Path<String>,
+    mut payload: Multipart,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+    throttle: web::Data<LoginThrottle>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let share = match open_share(&req, &mut database, slug.0.clone(), &config, &throttle).await {
+        Ok(e) => e,
+        Err(e) => return e,
+    };
+    if share.kind != ShareKind::Upload {
+        return HttpResponse::Forbidden().body("This link doesn't accept uploads");
+    }
+    let owner = match get_user_by_id(&mut database, share.user_id).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No share was found"),
+    };
+    let home = format!("./home/{}", owner.name);
+    match async_std::fs::metadata(format!("{}/{}", home, share.path)).await {
+        Ok(e) if e.is_dir() => {}
+        _ => return HttpResponse::Gone().body("This link is not available anymore"),
+    }
+
+    let target = Target {
+        user_id: share.user_id,
+        actor: share.user_id,
+        home,
+        folder: share.path.clone(),
+        quota: quota_of(&mut database, &owner, &config).await,
+        usage: home_usage(&owner).await,
+        max_size: share.max_size.map(|e| e as u64),
+        collision: Collision::Rename,
+        log: NewLog::new(share.user_id, ActionType::Upload).request(&req),
+    };
+    match receive(&mut database, &mut payload, target).await {
+        Ok(e) if !e.is_empty() => {
+            // Only the names given to the files are sent back, not the folder of the owner
+            let names: Vec<String> = e
+                .iter()
+                .filter_map(|e| e.rsplit('/').next().map(String::from))
+                .collect();
+            HttpResponse::Created()
+                .content_type("application/json")
+                .body(serde_json::to_string(&names).unwrap_or_default())
+        }
+        Ok(_) => HttpResponse::BadRequest().body("No file was sent"),
+        Err(e) => e,
+    }
+}
@@ -0,0 +1,89 @@
+//! Two-factor authentication of the user with an authenticator app.
+//!
+//! The secret is saved by `POST /user/totp` and only asked at login once a first code was
+//! confirmed, which gives the recovery codes.
+
+use crate::lib::db::recovery::insert::replace_codes;
+use crate::lib::db::user::update::{disable_totp, enable_totp, set_totp_secret};
+use crate::lib::http::auth::Auth;
+use crate::lib::totp::{
+    check_second_factor, generate_recovery_codes, generate_secret, provisioning_uri, qr_svg, verify,
+};
+use actix_web::{delete, post, web, HttpResponse};
+use datagn::DatabasePool;
+use shared::{TotpCode, TotpSetup};
+
+#[post("/user/totp")]
+pub async fn setup_totp(Auth(user): Auth, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if user.totp_enabled {
+        return HttpResponse::Conflict().body("Two-factor authentication is already enabled");
+    }
+
+    let secret = generate_secret();
+    if !set_totp_secret(&mut database, user.id.unwrap_or_default(), secret.clone()).await {
+        return HttpResponse::InternalServerError().body("Error on creation of the secret");
+    }
+    let uri = provisioning_uri(&user.name, &secret);
+    let qr = qr_svg(&uri).unwrap_or_default();
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&TotpSetup { secret, uri, qr }).unwrap_or_default())
+}
+
+/// Enables the second factor with a first code and returns the recovery codes, they are only
+/// shown this time
+#[post("/user/totp/confirm")]
+pub async fn confirm_totp(
+    Auth(user): Auth,
+    body: web::Json<TotpCode>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let id = user.id.unwrap_or_default();
+    if user.totp_enabled {
+        return HttpResponse::Conflict().body("Two-factor authentication is already enabled");
+    }
+    let secret = match &user.totp_secret {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("No secret was created"),
+    };
+    let step = match verify(secret, &body.code, None) {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Invalid code"),
+    };
+
+    let codes = generate_recovery_codes();
+    if !replace_codes(&mut database, id, &codes).await
+        || !enable_totp(&mut database, id, step).await
+    {
+        return HttpResponse::InternalServerError()
+            .body("Error on activation of the two-factor authentication");
+    }
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&codes).unwrap_or_default())
+}
+
+/// A code of the authenticator or a recovery code is needed
+#[delete("/user/totp")]
+pub async fn remove_totp(
+    Auth(user): Auth,
+    body: web::Json<TotpCode>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if !user.totp_enabled {
+        return HttpResponse::BadRequest().body("Two-factor authentication is not enabled");
+    }
+    if !check_second_factor(&mut database, &user, &body.code).await {
+        return HttpResponse::BadRequest().body("Invalid code");
+    }
+
+    if disable_totp(&mut database, user.id.unwrap_or_default()).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError()
+            .body("Error on removal of the two-factor authentication")
+    }
+}
@@ -0,0 +1,162 @@
+//! Deleted files are moved to `./trash/{user id}/{id}` instead of being removed, and can be
+//! restored at their original path until the retention set in the config is over.
+
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::trash::delete::delete_trash;
+use crate::lib::db::trash::get::{get_trash, get_trash_of_user};
+use crate::lib::db::trash::model::Trash;
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::file::operation::remove;
+use crate::lib::file::trash::restore;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use datagn::DatabasePool;
+use logger::error;
+use shared::TrashItem;
+use std::path::Path;
+
+async fn remove_item(database: &mut DatabasePool, trash: Trash) -> bool {
+    let path = trash.trash_path();
+    let removed = match web::block(move || remove(Path::new(&path))).await {
+        Ok(_) => true,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!(
+                    "Can't remove {} from the trash : {:?}",
+                    trash.uid, e
+                ));
+            }
+            false
+        }
+    };
+    removed && delete_trash(database, trash.uid).await
+}
+
+#[get("/trash")]
+pub async fn list_trash(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+
+    let mut items: Vec<TrashItem> = Vec::new();
+    for trash in get_trash_of_user(&mut database, user_id).await {
+        let metadata = async_std::fs::metadata(trash.trash_path()).await.ok();
+        items.push(TrashItem {
+            folder: metadata.as_ref().map_or(false, |e| e.is_dir()),
+            size: metadata.map_or(0, |e| e.len()),
+            id: trash.uid,
+            path: trash.path,
+            date: trash.date,
+        });
+    }
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&items).unwrap_or_default())
+}
+
+#[post("/trash/{id}/restore")]
+pub async fn restore_trash(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+    let trash = match get_trash(&mut database, id.0.clone(), user_id).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("Not in the trash"),
+    };
+
+    let home = format!("./home/{}", user.name);
+    let restored = trash.clone();
+    let home_path = home.clone();
+    match web::block(move || restore(&restored, &home_path)).await {
+        Ok(e) => {
+            delete_trash(&mut database, trash.uid).await;
+            let path = e
+                .strip_prefix(&home)
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let log = NewLog::new(user_id, ActionType::Restore)
+                .path(&trash.path)
+                .destination(&path)
+                .request(&req);
+            insert(&mut database, log).await;
+            HttpResponse::Ok().body(path)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+#[delete("/trash/{id}")]
+pub async fn delete_from_trash(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+    let trash = match get_trash(&mut database, id.0.clone(), user_id).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("Not in the trash"),
+    };
+
+    let log = NewLog::new(user_id, ActionType::Delete)
+        .path(&trash.path)
+        .request(&req);
+    if remove_item(&mut database, trash).await {
+        insert(&mut database, log).await;
+        HttpResponse::Ok().body("Deleted")
+    } else {
+        HttpResponse::InternalServerError().body("Can't delete the file")
+    }
+}
+
+#[delete("/trash")]
+pub async fn empty_trash(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+
+    let mut failed = 0;
+    for trash in get_trash_of_user(&mut database, user_id).await {
+        if !remove_item(&mut database, trash).await {
+            failed += 1;
+        }
+    }
+    let log = NewLog::new(user_id, ActionType::Delete)
+        .request(&req)
+        .success(failed == 0);
+    insert(&mut database, log).await;
+    if failed == 0 {
+        HttpResponse::Ok().body("The trash is empty")
+    } else {
+        HttpResponse::InternalServerError().body(format!("{} items can't be deleted", failed))
+    }
+}
@@ -0,0 +1,347 @@
+//! Resumable upload protocol, modeled on tus.io.
+//!
+//! 1. `POST /api/upload/{folder}` with a JSON body `{"name": "movie.mp4", "length": 1234}`
+//!    creates an upload. The answer is `201 Created` with the upload id as body and a
+//!    `Location: /api/upload/{id}` header.
+//! 2. `PATCH /api/upload/{id}` with an `Upload-Offset` header and the raw bytes of the chunk as
+//!    body appends the chunk. The offset must be the current size of the staged data, otherwise
+//!    the answer is `409 Conflict`. On success the new offset is sent back in `Upload-Offset`.
+//! 3. `HEAD /api/upload/{id}` returns `Upload-Offset` and `Upload-Length`, so a client can resume
+//!    after a failure.
+//! 4. `PUT /api/upload/{id}` finalizes the upload once every byte has been received: the staged
+//!    file is moved from `./temp/upload` into the user's home, or into a folder shared with them.
+//!
+//! `DELETE /api/upload/{id}` aborts an upload and removes the staged data. A request on an upload
+//! which is being written by another one is answered with `409 Conflict`. The uploads without a
+//! chunk for `upload_expire_days` are removed.
+
+use crate::lib::config::Config;
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::upload::delete::delete_upload;
+use crate::lib::db::upload::get::{get_upload, pending_usage};
+use crate::lib::db::upload::insert::insert as insert_upload;
+use crate::lib::db::upload::model::{NewUpload, Upload};
+use crate::lib::db::user::model::User;
+use crate::lib::db::user::token::generate_token;
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::file::access::{owner_of, resolve_writable};
+use crate::lib::file::quota::{home_usage, quota_of, Exceeded, Usage};
+use crate::lib::file::upload::UploadLocks;
+use crate::lib::file::version::{keep_previous, record_uploader};
+use actix_web::{delete, head, patch, post, put, web, HttpRequest, HttpResponse};
+use async_std::io::prelude::WriteExt;
+use datagn::DatabasePool;
+use logger::error;
+use tokio_stream::StreamExt;
+
+fn upload_offset(req: &HttpRequest) -> Option<u64> {
+    req.headers()
+        .get("Upload-Offset")
+        .and_then(|e| e.to_str().ok())
+        .and_then(|e| e.parse::<u64>().ok())
+}
+
+async fn staged_size(upload: &Upload) -> u64 {
+    async_std::fs::metadata(upload.temp_path())
+        .await
+        .map(|e| e.len())
+        .unwrap_or_default()
+}
+
+/// `user` is the owner of the folder. A replaced file still counts, it goes to the versions.
+/// The other unfinished uploads of the owner count with their whole length, else many of them
+/// could fill the disk through `./temp`.
+async fn check_quota(
+    database: &mut DatabasePool,
+    user: &User,
+    config: &Config,
+    length: u64,
+    uid: &str,
+) -> Result<(), Exceeded> {
+    let home = home_usage(user).await;
+    let pending = pending_usage(database, user.id.unwrap_or_default(), uid).await;
+    let usage = Usage {
+        bytes: home.bytes.saturating_add(pending.bytes),
+        files: home.files.saturating_add(pending.files),
+    };
+    quota_of(database, user, config)
+        .await
+        .check(usage, length, 1)
+}
+
+fn busy() -> HttpResponse {
+    HttpResponse::Conflict().body("The upload is being written by another request")
+}
+
+#[post("/upload/{path:.*}")]
+pub async fn create_upload(
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<NewUpload>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+
+    let folder = path.0.trim_matches('/').to_string();
+    if body.name.is_empty() || body.name.contains('/') || body.name == ".." || body.length < 0 {
+        return HttpResponse::BadRequest().body("Bad file name or length");
+    }
+    let resolved = match resolve_writable(&mut database, &user, &folder).await {
+        Ok(e) => e,
+        Err(e) => return e,
+    };
+    match async_std::fs::metadata(resolved.full_path()).await {
+        Ok(e) if e.is_dir() => {}
+        _ => return HttpResponse::BadRequest().body("Bad Folder"),
+    }
+
+    let owner = match owner_of(&mut database, &resolved, user).await {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get the owner"),
+    };
+    let uid = generate_token();
+    if let Err(e) = check_quota(&mut database, &owner, &config, body.length as u64, &uid).await {
+        return e.response();
+    }
+
+    let upload = Upload {
+        uid,
+        user_id,
+        owner_id: owner.id.unwrap_or_default(),
+        path: folder,
+        name: body.name.clone(),
+        length: body.length,
+    };
+    if async_std::fs::File::create(upload.temp_path())
+        .await
+        .is_err()
+    {
+        return HttpResponse::InternalServerError().body("Error on creation of file");
+    }
+    if !insert_upload(&mut database, &upload).await {
+        let _ = async_std::fs::remove_file(upload.temp_path()).await;
+        return HttpResponse::InternalServerError().body("Error on creation of upload");
+    }
+
+    HttpResponse::Created()
+        .header("Location", format!("/api/upload/{}", upload.uid))
+        .header("Upload-Offset", "0")
+        .header("Upload-Length", upload.length.to_string())
+        .body(upload.uid)
+}
+
+#[head("/upload/{id}")]
+pub async fn upload_status(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().finish(),
+    };
+    let upload = match get_upload(&mut database, id.0, user.id.unwrap_or_default()).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().finish(),
+    };
+
+    HttpResponse::Ok()
+        .header("Cache-Control", "no-store")
+        .header("Upload-Offset", staged_size(&upload).await.to_string())
+        .header("Upload-Length", upload.length.to_string())
+        .finish()
+}
+
+#[patch("/upload/{id}")]
+pub async fn append_upload(
+    req: HttpRequest,
+    id: web::Path<String>,
+    mut payload: web::Payload,
+    data: web::Data<DatabasePool>,
+    locks: web::Data<UploadLocks>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let upload = match get_upload(&mut database, id.0, user.id.unwrap_or_default()).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No upload was found"),
+    };
+    // The offset is checked and the chunk appended by one request at a time
+    let _lock = match locks.lock(&upload.uid) {
+        Some(e) => e,
+        None => return busy(),
+    };
+
+    let current = staged_size(&upload).await;
+    match upload_offset(&req) {
+        Some(offset) if offset == current => {}
+        Some(_) => {
+            return HttpResponse::Conflict()
+                .header("Upload-Offset", current.to_string())
+                .body("Bad offset")
+        }
+        None => return HttpResponse::BadRequest().body("Upload-Offset is missing"),
+    }
+
+    let mut f = match async_std::fs::OpenOptions::new()
+        .append(true)
+        .open(upload.temp_path())
+        .await
+    {
+        Ok(e) => e,
+        Err(_) => return HttpResponse::InternalServerError().body("Error on opening of file"),
+    };
+
+    let mut written = current;
+    while let Some(chunk) = payload.next().await {
+        let chunk = match chunk {
+            Ok(e) => e,
+            Err(e) => {
+                if cfg!(feature = "log") {
+                    error(format!("{:?}", e));
+                }
+                break;
+            }
+        };
+        if written + chunk.len() as u64 > upload.length as u64 {
+            let _ = f.flush().await;
+            return HttpResponse::PayloadTooLarge()
+                .header("Upload-Offset", written.to_string())
+                .body("The chunk is bigger than the upload");
+        }
+        if f.write_all(&chunk).await.is_err() {
+            return HttpResponse::InternalServerError().body("Error");
+        }
+        written += chunk.len() as u64;
+    }
+    if f.flush().await.is_err() {
+        return HttpResponse::InternalServerError().body("Error");
+    }
+
+    HttpResponse::NoContent()
+        .header("Upload-Offset", written.to_string())
+        .finish()
+}
+
+#[put("/upload/{id}")]
+pub async fn finish_upload(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+    locks: web::Data<UploadLocks>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = user.id.unwrap_or_default();
+    let upload = match get_upload(&mut database, id.0, user_id).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No upload was found"),
+    };
+    let _lock = match locks.lock(&upload.uid) {
+        Some(e) => e,
+        None => return busy(),
+    };
+
+    let size = staged_size(&upload).await;
+    if size != upload.length as u64 {
+        return HttpResponse::Conflict()
+            .header("Upload-Offset", size.to_string())
+            .body("The upload is not complete");
+    }
+
+    // The permission on the folder may have been removed since the creation of the upload
+    let resolved = match resolve_writable(&mut database, &user, &upload.path).await {
+        Ok(e) => e,
+        Err(e) => return e,
+    };
+    let filepath = format!("{}/{}", resolved.full_path(), upload.name);
+    let owner = match owner_of(&mut database, &resolved, user).await {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get the owner"),
+    };
+    // The usage may have changed since the creation of the upload
+    if let Err(e) = check_quota(&mut database, &owner, &config, size, &upload.uid).await {
+        return e.response();
+    }
+    let relative = format!("{}/{}", resolved.relative, upload.name);
+    if keep_previous(
+        &mut database,
+        resolved.owner_id,
+        relative.clone(),
+        &filepath,
+        config.version_retention(),
+    )
+    .await
+    .is_err()
+    {
+        return HttpResponse::InternalServerError().body("Error on keeping the previous version");
+    }
+    if async_std::fs::rename(upload.temp_path(), filepath.clone())
+        .await
+        .is_err()
+    {
+        // ./temp and ./home may be on different devices
+        if async_std::fs::copy(upload.temp_path(), filepath)
+            .await
+            .is_err()
+        {
+            return HttpResponse::InternalServerError().body("Error on moving the file");
+        }
+        let _ = async_std::fs::remove_file(upload.temp_path()).await;
+    }
+    delete_upload(&mut database, upload.uid).await;
+    record_uploader(&mut database, resolved.owner_id, &relative, user_id).await;
+    let log = NewLog::new(user_id, ActionType::Upload)
+        .owner(resolved.owner_id)
+        .path(&relative)
+        .bytes(size)
+        .request(&req);
+    insert(&mut database, log).await;
+
+    HttpResponse::Ok().body("The file is uploaded")
+}
+
+#[delete("/upload/{id}")]
+pub async fn abort_upload(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+    locks: web::Data<UploadLocks>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let upload = match get_upload(&mut database, id.0, user.id.unwrap_or_default()).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No upload was found"),
+    };
+    let _lock = match locks.lock(&upload.uid) {
+        Some(e) => e,
+        None => return busy(),
+    };
+
+    let _ = async_std::fs::remove_file(upload.temp_path()).await;
+    delete_upload(&mut database, upload.uid).await;
+
+    HttpResponse::NoContent().finish()
+}
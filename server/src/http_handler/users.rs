@@ -1,56 +1,292 @@
+use crate::lib::config::{Config, MAX_DAYS};
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::session::delete::{delete_expired_sessions, delete_sessions_of_user};
+use crate::lib::db::session::insert::insert as insert_session;
+use crate::lib::db::session::model::{hash_token, Session};
 use crate::lib::db::user::create_home::create_home;
-use crate::lib::db::user::get::get_id_of_user;
+use crate::lib::db::user::get::{get_id_of_user, get_user_by_id, get_user_by_name};
 use crate::lib::db::user::insert::insert_user;
 use crate::lib::db::user::model::User;
 use crate::lib::db::user::token::generate_token;
-use crate::lib::db::user::update::update_token;
-use crate::lib::db::user::valid_session::valid_session;
-use actix_web::{post, web, Error, HttpResponse};
+use crate::lib::db::user::update::{promote_first_user, set_last_login, set_password};
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::file::quota::{home_usage, quota_of};
+use crate::lib::http::throttle::{ip_of, LoginThrottle, TooManyRequests};
+use crate::lib::totp::check_second_factor;
+use actix_web::{get, post, web, Error, HttpRequest, HttpResponse, ResponseError};
+use chrono::{Duration, Utc};
 use datagn::DatabasePool;
+use shared::{Login, PasswordChange, Profile, StorageUsage};
 
 #[post("/user/create")]
 pub async fn create_user(
+    req: HttpRequest,
     body: web::Json<User>,
     data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+    throttle: web::Data<LoginThrottle>,
 ) -> Result<HttpResponse, Error> {
     let mut database = data.get_ref().clone();
+    let ip_key = format!("signup:{}", ip_of(&req));
+    if let Some(seconds) = throttle.retry_after(&ip_key) {
+        return Ok(TooManyRequests(seconds).error_response());
+    }
+    throttle.hit(&ip_key, config.signup_attempts);
     if body.name.is_empty() || body.password.is_empty() {
         return Ok(HttpResponse::BadRequest().body("Name or password cannot be empty"));
     }
+    if body.name.contains('/') || body.name.starts_with('.') {
+        return Ok(HttpResponse::BadRequest().body("This name can't be used"));
+    }
+    if get_user_by_name(&mut database, body.name.clone())
+        .await
+        .is_some()
+    {
+        return Ok(HttpResponse::Conflict().body("A user already has this name"));
+    }
     match insert_user(
         &mut database,
         body.name.clone(),
         body.clone().email.unwrap_or_default(),
         body.password.clone(),
+        config.hash_cost(),
     )
     .await
     {
-        Ok(_) => {
+        Ok(n) if n > 0 => {
+            // The admin of the config is only promoted when the server starts
+            if let Some(user) = get_user_by_name(&mut database, body.name.clone()).await {
+                promote_first_user(&mut database, user.id.unwrap_or_default()).await;
+            }
             let e = create_home(body.name.clone()).await;
             Ok(HttpResponse::Ok().body(e.body))
         }
-        Err(_) => Ok(HttpResponse::BadRequest().body("Bad Request")),
+        _ => Ok(HttpResponse::InternalServerError().body("Error on creation of the user")),
     }
 }
 
 #[post("/user/login")]
-pub async fn login_user(body: web::Json<User>, data: web::Data<DatabasePool>) -> HttpResponse {
+pub async fn login_user(
+    req: HttpRequest,
+    body: web::Json<Login>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+    throttle: web::Data<LoginThrottle>,
+) -> HttpResponse {
     let mut database = data.get_ref().clone();
     if body.name.is_empty() || body.password.is_empty() {
         return HttpResponse::BadRequest().body("Name or password cannot be empty");
     }
+    let user_key = format!("user:{}", body.name);
+    if let Some(seconds) = throttle
+        .retry_after(&format!("ip:{}", ip_of(&req)))
+        .or_else(|| throttle.retry_after(&user_key))
+    {
+        return TooManyRequests(seconds).error_response();
+    }
     let token = generate_token();
     if cfg!(debug_assertions) {
         println!("name : {}, password: {}", body.name, body.password);
     }
-    if let Some(id) = get_id_of_user(&mut database, body.name.clone(), body.password.clone()).await
+    if let Some(id) = get_id_of_user(
+        &mut database,
+        body.name.clone(),
+        body.password.clone(),
+        config.hash_cost(),
+    )
+    .await
     {
-        update_token(&mut database, token.clone(), id.to_owned()).await;
-        if cfg!(debug_assertions) {
-            println!("{}", valid_session(&mut database, token.clone()).await);
+        match get_user_by_id(&mut database, id).await {
+            Some(e) if e.disabled => {
+                return HttpResponse::Forbidden().body("This account is disabled")
+            }
+            Some(e) if e.must_reset => {
+                return HttpResponse::Forbidden().body("You must change your password")
+            }
+            Some(e) if e.totp_enabled => {
+                let valid = match &body.code {
+                    Some(code) => check_second_factor(&mut database, &e, code).await,
+                    None => false,
+                };
+                if !valid {
+                    if body.code.is_some() {
+                        login_failed(&mut database, &throttle, &config, &body.name, &req).await;
+                    }
+                    // The client asks the code and sends the login again
+                    return HttpResponse::Unauthorized()
+                        .header("WWW-Authenticate", "TOTP")
+                        .body("A valid code of the authenticator is required");
+                }
+            }
+            _ => {}
+        }
+        delete_expired_sessions(&mut database, id).await;
+        let now = Utc::now();
+        let session = Session {
+            id: 0,
+            user_id: id,
+            token_hash: hash_token(&token),
+            created: now.to_rfc3339(),
+            last_seen: now.to_rfc3339(),
+            expire: (now + Duration::days(config.session_days.clamp(1, MAX_DAYS))).to_rfc3339(),
+            user_agent: req
+                .headers()
+                .get("User-Agent")
+                .and_then(|e| e.to_str().ok())
+                .map(String::from),
+            ip: Some(ip_of(&req)).filter(|e| !e.is_empty()),
+        };
+        if !insert_session(&mut database, &session).await {
+            return HttpResponse::InternalServerError().body("Error on creation of the session");
         }
+        set_last_login(&mut database, id, now.to_rfc3339()).await;
+        throttle.clear(&user_key);
+        insert(
+            &mut database,
+            NewLog::new(id, ActionType::Login).request(&req),
+        )
+        .await;
         HttpResponse::Ok().body(&token)
     } else {
+        login_failed(&mut database, &throttle, &config, &body.name, &req).await;
         HttpResponse::BadRequest().body("No user was found")
     }
 }
+
+/// Counts the failure for the IP and for the account, which are locked after too many of them.
+/// Only the failures on an existing account are logged, the log needs a user.
+async fn login_failed(
+    database: &mut DatabasePool,
+    throttle: &LoginThrottle,
+    config: &Config,
+    name: &str,
+    req: &HttpRequest,
+) {
+    throttle.hit(&format!("ip:{}", ip_of(req)), config.login_ip_attempts);
+    let locked = throttle.hit(&format!("user:{}", name), config.login_attempts);
+    if let Some(user) = get_user_by_name(database, name.to_string()).await {
+        let id = user.id.unwrap_or_default();
+        let log = NewLog::new(id, ActionType::LoginFailed)
+            .request(req)
+            .success(false);
+        insert(database, log).await;
+        if locked {
+            insert(database, NewLog::new(id, ActionType::Lockout).request(req)).await;
+        }
+    }
+}
+
+/// Changes the password with the current one, which also works after a reset by an admin. It
+/// is throttled like the login, and needs the code when two-factor authentication is enabled.
+#[post("/user/password")]
+pub async fn change_password(
+    req: HttpRequest,
+    body: web::Json<PasswordChange>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+    throttle: web::Data<LoginThrottle>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    if body.new_password.is_empty() {
+        return HttpResponse::BadRequest().body("The password cannot be empty");
+    }
+    let user_key = format!("user:{}", body.name);
+    if let Some(seconds) = throttle
+        .retry_after(&format!("ip:{}", ip_of(&req)))
+        .or_else(|| throttle.retry_after(&user_key))
+    {
+        return TooManyRequests(seconds).error_response();
+    }
+    let id = match get_id_of_user(
+        &mut database,
+        body.name.clone(),
+        body.password.clone(),
+        config.hash_cost(),
+    )
+    .await
+    {
+        Some(e) => e,
+        None => {
+            login_failed(&mut database, &throttle, &config, &body.name, &req).await;
+            return HttpResponse::BadRequest().body("No user was found");
+        }
+    };
+    match get_user_by_id(&mut database, id).await {
+        Some(e) if e.disabled => return HttpResponse::Forbidden().body("This account is disabled"),
+        Some(e) if e.totp_enabled => {
+            let valid = match &body.code {
+                Some(code) => check_second_factor(&mut database, &e, code).await,
+                None => false,
+            };
+            if !valid {
+                if body.code.is_some() {
+                    login_failed(&mut database, &throttle, &config, &body.name, &req).await;
+                }
+                return HttpResponse::Unauthorized()
+                    .header("WWW-Authenticate", "TOTP")
+                    .body("A valid code of the authenticator is required");
+            }
+        }
+        _ => {}
+    }
+
+    if set_password(
+        &mut database,
+        id,
+        body.new_password.clone(),
+        config.hash_cost(),
+    )
+    .await
+    {
+        throttle.clear(&user_key);
+        // The old password may have been stolen
+        delete_sessions_of_user(&mut database, id).await;
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::InternalServerError().body("Error on update of the password")
+    }
+}
+
+#[get("/user/me")]
+pub async fn get_profile(req: HttpRequest, data: web::Data<DatabasePool>) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&Profile {
+            name: user.name,
+            role: user.role.format(),
+            totp: user.totp_enabled,
+        })
+        .unwrap_or_default(),
+    )
+}
+
+#[get("/user/usage")]
+pub async fn get_usage(
+    req: HttpRequest,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+
+    let usage = home_usage(&user).await;
+    let quota = quota_of(&mut database, &user, &config).await;
+    HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&StorageUsage {
+            bytes: usage.bytes,
+            files: usage.files,
+            quota_bytes: quota.bytes,
+            quota_files: quota.files,
+        })
+        .unwrap_or_default(),
+    )
+}
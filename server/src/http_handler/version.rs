@@ -0,0 +1,229 @@
+//! Previous revisions of overwritten files, stored in `./versions/{user id}/{id}`.
+
+use crate::lib::config::{Config, MAX_DAYS};
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::{ActionType, NewLog};
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::db::version::delete::delete_version;
+use crate::lib::db::version::get::{get_version, get_versions_of_file};
+use crate::lib::db::version::insert::insert as insert_version;
+use crate::lib::db::version::prune::{prune, Retention};
+use crate::lib::file::inhome;
+use crate::lib::file::stream::{stream_named_file, Disposition};
+use crate::lib::file::version::{keep_previous, record_uploader, relative_path};
+use crate::lib::http::get_args;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use datagn::DatabasePool;
+use logger::error;
+use shared::VersionItem;
+
+#[get("/versions/{path:.*}")]
+pub async fn list_versions(
+    req: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+
+    let versions: Vec<VersionItem> =
+        get_versions_of_file(&mut database, user_id, relative_path(&path.0))
+            .await
+            .into_iter()
+            .map(|(version, uploader)| VersionItem {
+                id: version.uid,
+                date: version.date,
+                size: version.size as u64,
+                uploader,
+            })
+            .collect();
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&versions).unwrap_or_default())
+}
+
+#[get("/version/{id}")]
+pub async fn download_version(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let version = match get_version(&mut database, id.0.clone(), user.id.unwrap_or_default()).await
+    {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No version was found"),
+    };
+
+    let name = version
+        .path
+        .rsplit('/')
+        .next()
+        .unwrap_or("file")
+        .to_string();
+    stream_named_file(&req, version.version_path(), name, Disposition::Attachment).await
+}
+
+#[post("/version/{id}/restore")]
+pub async fn restore_version(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+    let version = match get_version(&mut database, id.0.clone(), user_id).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No version was found"),
+    };
+    let full_path = format!("./home/{}/{}", user.name, version.path);
+    if !inhome(full_path.clone()) {
+        return HttpResponse::BadRequest().body("Stay at home please");
+    }
+
+    // The restored version must not be seen by the prune done when the current file is kept
+    delete_version(&mut database, version.uid.clone()).await;
+    if let Err(e) = keep_previous(
+        &mut database,
+        user_id,
+        version.path.clone(),
+        &full_path,
+        config.version_retention(),
+    )
+    .await
+    {
+        insert_version(&mut database, &version).await;
+        return HttpResponse::InternalServerError().body(e.to_string());
+    }
+
+    if let Some(parent) = std::path::Path::new(&full_path).parent() {
+        let _ = async_std::fs::create_dir_all(parent).await;
+    }
+    if async_std::fs::rename(version.version_path(), &full_path)
+        .await
+        .is_err()
+    {
+        if let Err(e) = async_std::fs::copy(version.version_path(), &full_path).await {
+            if cfg!(feature = "log") {
+                error(format!(
+                    "Can't restore the version {} : {:?}",
+                    version.uid, e
+                ));
+            }
+            insert_version(&mut database, &version).await;
+            return HttpResponse::InternalServerError().body("Error on restoring the version");
+        }
+        let _ = async_std::fs::remove_file(version.version_path()).await;
+    }
+    record_uploader(&mut database, user_id, &version.path, version.uploader).await;
+    let log = NewLog::new(user_id, ActionType::Restore)
+        .path(&version.path)
+        .bytes(version.size as u64)
+        .request(&req);
+    insert(&mut database, log).await;
+
+    HttpResponse::Ok().body(version.path)
+}
+
+#[delete("/version/{id}")]
+pub async fn delete_one_version(
+    req: HttpRequest,
+    id: web::Path<String>,
+    data: web::Data<DatabasePool>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+    let version = match get_version(&mut database, id.0.clone(), user_id).await {
+        Some(e) => e,
+        None => return HttpResponse::NotFound().body("No version was found"),
+    };
+
+    match async_std::fs::remove_file(version.version_path()).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(_) => return HttpResponse::InternalServerError().body("Can't delete the version"),
+    }
+    delete_version(&mut database, version.uid).await;
+    let log = NewLog::new(user_id, ActionType::Delete)
+        .path(&version.path)
+        .bytes(version.size as u64)
+        .request(&req);
+    insert(&mut database, log).await;
+
+    HttpResponse::Ok().body("Deleted")
+}
+
+/// Applies the retention of the config, or the one given by `?keep=` and `?days=`
+#[delete("/versions/{path:.*}")]
+pub async fn prune_versions(
+    req: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<DatabasePool>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let mut database = data.get_ref().clone();
+    let user = match from_headers_get_user(&mut database, req.clone()).await {
+        Some(e) => e,
+        None => return HttpResponse::BadRequest().body("Error on token"),
+    };
+    let user_id = match user.id {
+        Some(e) => e,
+        None => return HttpResponse::InternalServerError().body("Can't get user"),
+    };
+
+    let args = get_args(req.clone());
+    let retention = if args.contains_key("keep") || args.contains_key("days") {
+        let keep = match args.get("keep").map(|e| e.parse::<i64>()) {
+            None => 0,
+            Some(Ok(e)) if e >= 0 => e,
+            _ => return HttpResponse::BadRequest().body("keep must be a positive number"),
+        };
+        let days = match args.get("days").map(|e| e.parse::<i64>()) {
+            None => 0,
+            Some(Ok(e)) if (0..=MAX_DAYS).contains(&e) => e,
+            _ => {
+                return HttpResponse::BadRequest()
+                    .body(format!("days must be between 0 and {}", MAX_DAYS))
+            }
+        };
+        Retention { keep, days }
+    } else {
+        config.version_retention()
+    };
+
+    let relative = relative_path(&path.0);
+    let pruned = prune(&mut database, user_id, relative.clone(), retention).await;
+    if pruned > 0 {
+        let log = NewLog::new(user_id, ActionType::Delete)
+            .path(&relative)
+            .request(&req);
+        insert(&mut database, log).await;
+    }
+    HttpResponse::Ok().body(pruned.to_string())
+}
@@ -0,0 +1,303 @@
+use crate::lib::file::file_trait::TraitFolder;
+use flate2::read::GzDecoder;
+use shared::Folder;
+use std::convert::TryFrom;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// 9999-12-31 23:59:59
+const MAX_MTIME: u64 = 253_402_300_799;
+
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    Targz,
+}
+
+pub struct Limit {
+    /// Maximum number of bytes written on the disk
+    pub size: u64,
+    /// Maximum ratio between the extracted size and the size of the archive
+    pub ratio: u64,
+    /// Maximum number of entries, folders included
+    pub entries: u64,
+    /// Maximum number of files
+    pub files: u64,
+}
+
+pub fn archive_format(path: &str) -> Option<ArchiveFormat> {
+    let path = path.to_lowercase();
+    if path.ends_with(".zip") {
+        Some(ArchiveFormat::Zip)
+    } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+        Some(ArchiveFormat::Targz)
+    } else if path.ends_with(".tar") {
+        Some(ArchiveFormat::Tar)
+    } else {
+        None
+    }
+}
+
+/// Refuses absolute paths and `..`, so an entry can't be written outside of the target (zip-slip)
+fn safe_path(name: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(e) => path.push(e),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if path.as_os_str().is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+fn is_selected(name: &str, selection: Option<&[String]>) -> bool {
+    let name = name.trim_end_matches('/');
+    match selection {
+        None => true,
+        Some(selection) => selection.iter().any(|e| {
+            let e = e.trim_end_matches('/');
+            name == e || name.starts_with(&format!("{}/", e))
+        }),
+    }
+}
+
+fn entry_folder(name: &str, size: u64, modified: String, is_dir: bool) -> Folder {
+    let ftype = if is_dir {
+        String::from("Folder")
+    } else {
+        mime_guess::from_path(name)
+            .first_or_octet_stream()
+            .to_string()
+    };
+    Folder::new(
+        true,
+        name.trim_end_matches('/').to_string(),
+        size,
+        modified.clone(),
+        modified,
+        ftype,
+    )
+}
+
+fn tar_entries(path: &Path, gzip: bool) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = if gzip {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+/// Lists at most `max_entries` entries of the archive
+pub fn list(path: &Path, max_entries: u64) -> io::Result<Vec<Folder>> {
+    let mut content: Vec<Folder> = Vec::new();
+    let max_entries = usize::try_from(max_entries).unwrap_or(usize::MAX);
+    match archive_format(&path.to_string_lossy()) {
+        Some(ArchiveFormat::Zip) => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+            for i in 0..archive.len().min(max_entries) {
+                let entry = archive.by_index(i)?;
+                let date = entry.last_modified();
+                content.push(entry_folder(
+                    entry.name(),
+                    entry.size(),
+                    format!(
+                        "{:02}-{:02}-{} {:02}:{:02}:{:02}",
+                        date.day(),
+                        date.month(),
+                        date.year(),
+                        date.hour(),
+                        date.minute(),
+                        date.second()
+                    ),
+                    entry.is_dir(),
+                ));
+            }
+        }
+        Some(format) => {
+            let mut archive = tar_entries(path, matches!(format, ArchiveFormat::Targz))?;
+            for entry in archive.entries()?.take(max_entries) {
+                let entry = entry?;
+                let header = entry.header();
+                // The date comes from the archive, it is kept before the year 10000
+                let mtime = header.mtime().unwrap_or_default().min(MAX_MTIME);
+                let modified =
+                    time::PrimitiveDateTime::from(UNIX_EPOCH + Duration::from_secs(mtime))
+                        .format("%d-%m-%Y %T");
+                content.push(entry_folder(
+                    &entry.path()?.to_string_lossy(),
+                    header.size().unwrap_or_default(),
+                    modified,
+                    header.entry_type().is_dir(),
+                ));
+            }
+        }
+        None => content.push(Folder::error(String::from("Not an archive"))),
+    }
+    Ok(content)
+}
+
+struct Extractor<'a> {
+    target: &'a Path,
+    limit: u64,
+    written: u64,
+    entries_left: u64,
+    files_left: u64,
+    created: Vec<PathBuf>,
+}
+
+impl<'a> Extractor<'a> {
+    /// An archive of millions of empty files is small, so the entries are counted too
+    fn count(&mut self, is_file: bool) -> io::Result<()> {
+        let too_many = || io::Error::new(io::ErrorKind::Other, "The archive has too many entries");
+        self.entries_left = self.entries_left.checked_sub(1).ok_or_else(too_many)?;
+        if is_file {
+            self.files_left = self.files_left.checked_sub(1).ok_or_else(too_many)?;
+        }
+        Ok(())
+    }
+
+    fn create_dirs(&mut self, path: &Path) -> io::Result<()> {
+        // Only the highest missing folder is remembered, removing it removes the others
+        if let Some(missing) = path
+            .ancestors()
+            .take_while(|e| *e != self.target && !e.exists())
+            .last()
+        {
+            self.created.push(missing.to_path_buf());
+            fs::create_dir_all(path)?;
+        }
+        Ok(())
+    }
+
+    fn create_dir(&mut self, name: &str) -> io::Result<()> {
+        self.count(false)?;
+        match safe_path(name) {
+            Some(e) => self.create_dirs(&self.target.join(e)),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, "Bad entry name")),
+        }
+    }
+
+    fn create_file(&mut self, name: &str, reader: &mut dyn Read) -> io::Result<()> {
+        self.count(true)?;
+        let path = match safe_path(name) {
+            Some(e) => self.target.join(e),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "Bad entry name")),
+        };
+        if let Some(parent) = path.parent() {
+            self.create_dirs(parent)?;
+        }
+        // Never overwrite a file of the user
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        self.created.push(path);
+
+        // The sizes written in the archive can lie, so only the bytes really written are trusted
+        let remaining = self.limit - self.written;
+        let n = io::copy(&mut reader.take(remaining.saturating_add(1)), &mut file)?;
+        if n > remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "The archive is too big to be extracted",
+            ));
+        }
+        self.written += n;
+        Ok(())
+    }
+
+    fn rollback(&self) {
+        for path in self.created.iter().rev() {
+            if path.is_dir() {
+                let _ = fs::remove_dir_all(path);
+            } else {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+fn extract_entries(
+    path: &Path,
+    extractor: &mut Extractor,
+    selection: Option<&[String]>,
+) -> io::Result<()> {
+    match archive_format(&path.to_string_lossy()) {
+        Some(ArchiveFormat::Zip) => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                let name = entry.name().to_string();
+                if !is_selected(&name, selection) {
+                    continue;
+                }
+                if entry.is_dir() {
+                    extractor.create_dir(&name)?;
+                } else {
+                    extractor.create_file(&name, &mut entry)?;
+                }
+            }
+        }
+        Some(format) => {
+            let mut archive = tar_entries(path, matches!(format, ArchiveFormat::Targz))?;
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let name = entry.path()?.to_string_lossy().to_string();
+                if !is_selected(&name, selection) {
+                    continue;
+                }
+                let entry_type = entry.header().entry_type();
+                // Links and devices are ignored, they could point outside of the home
+                if entry_type.is_dir() {
+                    extractor.create_dir(&name)?;
+                } else if entry_type.is_file() {
+                    extractor.create_file(&name, &mut entry)?;
+                }
+            }
+        }
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Not an archive",
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Extracts the archive (or the selected entries) into `target` and returns the extracted size.
+/// Nothing is kept if the extraction fails.
+pub fn extract(
+    path: &Path,
+    target: &Path,
+    selection: Option<&[String]>,
+    limit: Limit,
+) -> io::Result<u64> {
+    let archive_size = fs::metadata(path)?.len();
+    let mut extractor = Extractor {
+        target,
+        limit: limit
+            .size
+            .min(archive_size.max(1).saturating_mul(limit.ratio)),
+        written: 0,
+        entries_left: limit.entries,
+        files_left: limit.files,
+        created: Vec::new(),
+    };
+    match extract_entries(path, &mut extractor, selection) {
+        Ok(_) => Ok(extractor.written),
+        Err(e) => {
+            extractor.rollback();
+            Err(e)
+        }
+    }
+}
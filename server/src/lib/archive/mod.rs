@@ -1,30 +1,55 @@
-use crate::lib::file::{get_file_as_byte_vec, get_file_preview};
-use actix_web::http::ContentEncoding;
-use actix_web::{dev::BodyEncoding, HttpResponse};
-use async_std::fs as afs;
+pub mod extract;
+pub mod zip_stream;
+
+use crate::lib::archive::zip_stream::ZipStream;
+use crate::lib::file::file_trait::TraitFolder;
+use crate::lib::file::stream::{stream_file, Disposition};
+use crate::lib::file::{get_file_preview, inhome};
+use actix_web::web::{self, Bytes};
+use actix_web::{HttpRequest, HttpResponse};
+use flate2::write::GzEncoder;
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::executor::block_on;
+use futures::SinkExt;
 use logger::error;
-use std::fs::File;
-use std::path::PathBuf;
-use zip::write::FileOptions;
-use zip::CompressionMethod;
-use zip_extensions::zip_create_from_directory_with_options;
+use shared::{FType, Folder, JsonStruct};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const CHUNK_SIZE: usize = 64 * 1024;
 
 pub enum DownloadEnum {
     Preview,
     Download,
-    Archive(ArchiveType),
+    Archive(ArchiveType, Compression),
 }
 pub enum ArchiveType {
     Targz,
     Zip,
 }
+pub enum Compression {
+    Store,
+    Deflate,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "store" => Compression::Store,
+            "bzip2" => Compression::Bzip2,
+            _ => Compression::Deflate,
+        }
+    }
+}
 
-pub async fn download(path: String, atype: DownloadEnum) -> HttpResponse {
+pub async fn download(req: &HttpRequest, path: String, atype: DownloadEnum) -> HttpResponse {
     match atype {
         DownloadEnum::Preview => {
             if let Ok(metadata) = async_std::fs::metadata(path.clone()).await {
                 if metadata.is_file() {
-                    get_file_preview(path.clone()).await
+                    get_file_preview(req, path.clone()).await
                 } else {
                     return HttpResponse::Ok().body("Bad file");
                 }
@@ -32,13 +57,13 @@ pub async fn download(path: String, atype: DownloadEnum) -> HttpResponse {
                 return HttpResponse::Ok().body("Bad file");
             }
         }
-        DownloadEnum::Download => download_file(path.clone()).await,
-        DownloadEnum::Archive(archivetype) => {
+        DownloadEnum::Download => download_file(req, path.clone()).await,
+        DownloadEnum::Archive(archivetype, compression) => {
             if let Ok(e) = async_std::fs::metadata(path.clone()).await {
                 if e.is_dir() {
                     return match archivetype {
                         ArchiveType::Targz => get_tar(path.clone()).await,
-                        ArchiveType::Zip => get_zip(path.clone()).await,
+                        ArchiveType::Zip => get_zip(path.clone(), compression).await,
                     };
                 } else {
                     return HttpResponse::Ok().body("Bad file");
@@ -50,133 +75,224 @@ pub async fn download(path: String, atype: DownloadEnum) -> HttpResponse {
     }
 }
 
-pub async fn download_file(path: String) -> HttpResponse {
-    if let Ok(e) = afs::File::open(path.clone()).await {
-        if let Ok(e) = e.metadata().await {
-            if e.is_file() {
-                let buf = afs::read(path.clone()).await.unwrap();
-                HttpResponse::Ok()
-                    .header("Access-Control-Allow-Origin", "*")
-                    .header("charset", "utf-8")
-                    .header(
-                        "Content-Disposition",
-                        format!(
-                            "attachment; filename=\"{}\"",
-                            path.clone().split('/').last().unwrap_or("file")
-                        ),
-                    )
-                    .content_type(
-                        mime_guess::from_ext(path.split('/').last().unwrap_or(""))
-                            .first_or_octet_stream()
-                            .to_string(),
-                    )
-                    .body(buf)
-            } else {
-                HttpResponse::BadRequest().body("Bad File")
+pub async fn download_file(req: &HttpRequest, path: String) -> HttpResponse {
+    stream_file(req, path, Disposition::Attachment).await
+}
+
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+struct ChannelWriter {
+    sender: Sender<io::Result<Bytes>>,
+    buffer: Vec<u8>,
+}
+
+impl ChannelWriter {
+    fn send(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = Bytes::from(std::mem::replace(
+            &mut self.buffer,
+            Vec::with_capacity(CHUNK_SIZE),
+        ));
+        block_on(self.sender.send(Ok(chunk)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "The client is gone"))
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.send()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
+
+/// Symlinks are skipped, they could point out of the home or make the walk loop
+fn walk(entry: Entry, entries: &mut Vec<Entry>) {
+    let metadata = match std::fs::symlink_metadata(&entry.path) {
+        Ok(e) if !e.file_type().is_symlink() => e,
+        _ => return,
+    };
+    if metadata.is_dir() {
+        if let Ok(readdir) = std::fs::read_dir(&entry.path) {
+            let name = entry.name.clone();
+            entries.push(entry);
+            for dentry in readdir.flatten() {
+                walk(
+                    Entry {
+                        name: format!("{}/{}", name, dentry.file_name().to_string_lossy()),
+                        path: dentry.path(),
+                    },
+                    entries,
+                );
             }
-        } else {
-            HttpResponse::BadRequest().body("Bad File")
         }
     } else {
-        HttpResponse::BadRequest().body("Bad File")
+        entries.push(entry);
     }
 }
 
-pub async fn get_zip(path: String) -> HttpResponse {
-    println!("{}", path.clone());
-    HttpResponse::Ok()
-        .header("Access-Control-Allow-Origin", "*")
-        .header("charset", "utf-8")
-        .header(
-            "Content-Disposition",
-            format!(
-                "\"attachment\";filename=\"{}.zip\"",
-                path.clone().split('/').last().unwrap_or("default_name")
-            ),
-        )
-        .content_type("application/zip")
-        .encoding(ContentEncoding::Gzip)
-        .body(get_file_as_byte_vec(path.clone(), ArchiveType::Zip).await)
+fn write_zip(
+    entries: Vec<Entry>,
+    writer: ChannelWriter,
+    compression: Compression,
+) -> io::Result<ChannelWriter> {
+    let mut zip = ZipStream::new(writer, compression);
+    for entry in entries {
+        let metadata = std::fs::symlink_metadata(&entry.path)?;
+        if metadata.is_dir() {
+            zip.add_directory(
+                &entry.name,
+                metadata.modified().unwrap_or_else(|_| SystemTime::now()),
+            )?;
+        } else {
+            zip.add_file(&entry.name, &entry.path)?;
+        }
+    }
+    zip.finish()
 }
 
-pub async fn get_tar(path: String) -> HttpResponse {
+fn write_tar(entries: Vec<Entry>, writer: ChannelWriter) -> io::Result<ChannelWriter> {
+    let mut tar = tar::Builder::new(GzEncoder::new(writer, flate2::Compression::default()));
+    for entry in entries {
+        if std::fs::symlink_metadata(&entry.path)?.is_dir() {
+            tar.append_dir(&entry.name, &entry.path)?;
+        } else {
+            tar.append_path_with_name(&entry.path, &entry.name)?;
+        }
+    }
+    tar.into_inner()?.finish()
+}
+
+/// Builds the archive in a thread and sends it chunk by chunk.
+/// The channel is bounded, so a slow client slows the thread down instead of filling the memory.
+pub fn archive_stream(
+    roots: Vec<Entry>,
+    atype: ArchiveType,
+    compression: Compression,
+) -> Receiver<io::Result<Bytes>> {
+    let (sender, receiver) = channel::<io::Result<Bytes>>(4);
+    std::thread::spawn(move || {
+        let mut entries: Vec<Entry> = Vec::new();
+        for root in roots {
+            walk(root, &mut entries);
+        }
+        let writer = ChannelWriter {
+            sender: sender.clone(),
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        };
+        let result = match atype {
+            ArchiveType::Zip => write_zip(entries, writer, compression),
+            ArchiveType::Targz => write_tar(entries, writer),
+        }
+        .and_then(|mut e| e.flush());
+        if let Err(e) = result {
+            if cfg!(feature = "log") {
+                error(format!("Error on archive : {:?}", e));
+            }
+            let mut sender = sender;
+            let _ = block_on(sender.send(Err(e)));
+        }
+    });
+    receiver
+}
+
+pub fn archive_response(
+    name: &str,
+    roots: Vec<Entry>,
+    atype: ArchiveType,
+    compression: Compression,
+) -> HttpResponse {
+    let (extension, content_type) = match atype {
+        ArchiveType::Zip => ("zip", "application/zip"),
+        ArchiveType::Targz => ("tar.gz", "application/gzip"),
+    };
     HttpResponse::Ok()
         .header("Access-Control-Allow-Origin", "*")
         .header("charset", "utf-8")
         .header(
             "Content-Disposition",
-            format!(
-                "\"attachment\";filename=\"{}.tar.gz\"",
-                path.clone().split('/').last().unwrap_or("default_name")
-            ),
+            format!("attachment; filename=\"{}.{}\"", name, extension),
         )
-        .content_type("application/x-tar")
-        .encoding(ContentEncoding::Gzip)
-        .body(get_file_as_byte_vec(path.clone(), ArchiveType::Targz).await)
+        .content_type(content_type)
+        .streaming(archive_stream(roots, atype, compression))
 }
 
-async fn async_zip_archive(name: String, dir: String) -> afs::File {
-    let file_name = format!("./temp/{}.zip", name);
-    File::create(file_name.clone()).unwrap();
-    if cfg!(debug_assertions) {
-        println!("filename => {}", dir);
+fn archive_name(path: &str) -> String {
+    match path.trim_end_matches('/').rsplit('/').next() {
+        Some(e) if !e.is_empty() && e != "." => e.to_string(),
+        _ => String::from("default_name"),
     }
-    match async_std::task::block_on(async {
-        zip_create_from_directory_with_options(
-            &PathBuf::from(file_name),
-            &PathBuf::from(dir),
-            FileOptions::default().compression_method(CompressionMethod::Bzip2),
-        )
-    }) {
-        Ok(_) => {}
-        Err(e) => {
-            if cfg!(all(feature = "log")) {
-                match e {
-                    zip::result::ZipError::Io(_) => error("I/O Error"),
-                    zip::result::ZipError::InvalidArchive(_) => error("Invalid Archive"),
-                    zip::result::ZipError::UnsupportedArchive(_) => error("Unsupported Archive"),
-                    zip::result::ZipError::FileNotFound => error("File not found"),
-                }
-            }
-        }
-    };
+}
 
-    afs::File::open(format!("./temp/{}.zip", name))
-        .await
-        .expect("Error")
+pub async fn get_zip(path: String, compression: Compression) -> HttpResponse {
+    let name = archive_name(&path);
+    archive_response(
+        &name.clone(),
+        vec![Entry {
+            name,
+            path: PathBuf::from(path),
+        }],
+        ArchiveType::Zip,
+        compression,
+    )
 }
 
-async fn async_tar_archive(name: String, dir: String) -> afs::File {
-    let file_name = format!("./temp/{}.tar.gz", name);
-    if cfg!(debug_assertions) {
-        println!("{} dir : {}", file_name, dir);
-    }
-    File::create(&file_name).expect("Error");
-    let file = afs::File::open(&file_name);
-    tar::Builder::new(File::open(&file_name).expect("no file found"))
-        .append_dir_all(file_name.as_str(), dir.clone().as_str())
-        .expect("Error");
-    file.await.expect("Error")
-}
-
-pub async fn random_archive(extention: String, dir: String) -> afs::File {
-    let name: String = random_name();
-    let dir: &str = dir.as_ref();
-    if extention == *"tar.gz" {
-        async_tar_archive(name, dir.to_string()).await
-    } else {
-        async_zip_archive(name, dir.to_string()).await
+pub async fn get_tar(path: String) -> HttpResponse {
+    let name = archive_name(&path);
+    archive_response(
+        &name.clone(),
+        vec![Entry {
+            name,
+            path: PathBuf::from(path),
+        }],
+        ArchiveType::Targz,
+        Compression::Deflate,
+    )
+}
+
+pub async fn get_archive_content(path: String, max_entries: u64) -> HttpResponse {
+    if !inhome(path.clone()) {
+        return HttpResponse::BadRequest().body("Stay at home please");
     }
+    let result = match web::block(move || extract::list(&PathBuf::from(path), max_entries)).await {
+        Ok(content) => JsonStruct {
+            result: true,
+            lenght: content.len() as i64,
+            ftype: FType::Folder,
+            content,
+        },
+        Err(e) => JsonStruct {
+            result: false,
+            lenght: 1,
+            ftype: FType::Error,
+            content: vec![Folder::error(e.to_string())],
+        },
+    };
+    HttpResponse::Ok()
+        .header("Access-Control-Allow-Origin", "*")
+        .header("charset", "utf-8")
+        .content_type("application/json")
+        .body(serde_json::to_string(&result).unwrap_or_default())
 }
 
-fn random_name() -> String {
-    use rand::Rng;
-    let charset: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCEDFGHIJKLMNOPQRSTUVWXYZ123456789";
-    let mut rng = rand::thread_rng();
-    (0..10)
-        .map(|_| {
-            let idx = rng.gen_range(0..charset.len());
-            charset[idx] as char
-        })
-        .collect()
+/// Writes a folder in a tar.gz file, its content is under `name` in the archive
+pub fn archive_folder(path: &Path, name: &str, target: &Path) -> io::Result<()> {
+    let file = std::fs::File::create(target)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, flate2::Compression::default()));
+    // A link is stored as a link, what it points to stays out of the archive
+    tar.follow_symlinks(false);
+    tar.append_dir_all(name, path)?;
+    tar.into_inner()?.finish()?;
+    Ok(())
 }
@@ -0,0 +1,384 @@
+//! A zip writer which never seeks back, so the archive can be sent while it is produced.
+//!
+//! Sizes and checksums of every file are written after its data (data descriptor), and Zip64
+//! records are used as soon as a file, an offset or the central directory doesn't fit in 32 bits.
+
+use crate::lib::archive::Compression;
+use chrono::{DateTime, Datelike, Local, Timelike};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR: u32 = 0x0807_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const ZIP64_END: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR: u32 = 0x0706_4b50;
+const END: u32 = 0x0605_4b50;
+
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+const FLAG_UTF8: u16 = 0x0800;
+const ZIP64_LIMIT: u64 = 0xFFFF_FFFF;
+// Deflate and bzip2 can make incompressible data bigger and the file can grow while it is read,
+// a file of half the limit already gets Zip64. A file without it which ends up too big is an
+// error, not a corrupt archive.
+const ZIP64_THRESHOLD: u64 = 0x8000_0000;
+
+struct CountWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct CentralEntry {
+    name: String,
+    method: u16,
+    flags: u16,
+    time: u16,
+    date: u16,
+    crc: u32,
+    compressed: u64,
+    size: u64,
+    offset: u64,
+    external: u32,
+}
+
+pub struct ZipStream<W: Write> {
+    inner: CountWriter<W>,
+    entries: Vec<CentralEntry>,
+    compression: Compression,
+    /// Size from which a file gets Zip64 sizes
+    zip64_threshold: u64,
+}
+
+fn dos_time(time: SystemTime) -> (u16, u16) {
+    let time: DateTime<Local> = DateTime::from(time);
+    if time.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let date =
+        (((time.year() - 1980) as u16) << 9) | ((time.month() as u16) << 5) | time.day() as u16;
+    let clock =
+        ((time.hour() as u16) << 11) | ((time.minute() as u16) << 5) | (time.second() as u16 / 2);
+    (clock, date)
+}
+
+/// Values which don't fit are replaced by 0xFFFFFFFF and written in a Zip64 field instead
+fn clamp(value: u64) -> u32 {
+    if value >= ZIP64_LIMIT {
+        0xFFFF_FFFF
+    } else {
+        value as u32
+    }
+}
+
+fn copy<R: Read, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    hasher: &mut crc32fast::Hasher,
+) -> io::Result<u64> {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut size: u64 = 0;
+    loop {
+        let n = input.read(&mut buf)?;
+        if n == 0 {
+            return Ok(size);
+        }
+        hasher.update(&buf[..n]);
+        output.write_all(&buf[..n])?;
+        size += n as u64;
+    }
+}
+
+fn version_needed(method: u16, zip64: bool) -> u16 {
+    match method {
+        12 => 46,
+        _ if zip64 => 45,
+        _ => 20,
+    }
+}
+
+impl<W: Write> ZipStream<W> {
+    pub fn new(inner: W, compression: Compression) -> Self {
+        Self {
+            inner: CountWriter { inner, count: 0 },
+            entries: Vec::new(),
+            compression,
+            zip64_threshold: ZIP64_THRESHOLD,
+        }
+    }
+
+    fn write_local_header(&mut self, entry: &CentralEntry, zip64: bool) -> io::Result<()> {
+        let mut buf: Vec<u8> = Vec::with_capacity(30 + entry.name.len() + 20);
+        buf.extend(&LOCAL_HEADER.to_le_bytes());
+        buf.extend(&version_needed(entry.method, zip64).to_le_bytes());
+        buf.extend(&entry.flags.to_le_bytes());
+        buf.extend(&entry.method.to_le_bytes());
+        buf.extend(&entry.time.to_le_bytes());
+        buf.extend(&entry.date.to_le_bytes());
+        // crc and sizes are in the data descriptor
+        buf.extend(&0u32.to_le_bytes());
+        let size: u32 = if zip64 { 0xFFFF_FFFF } else { 0 };
+        buf.extend(&size.to_le_bytes());
+        buf.extend(&size.to_le_bytes());
+        buf.extend(&(entry.name.len() as u16).to_le_bytes());
+        buf.extend(&(if zip64 { 20u16 } else { 0u16 }).to_le_bytes());
+        buf.extend(entry.name.as_bytes());
+        if zip64 {
+            buf.extend(&1u16.to_le_bytes());
+            buf.extend(&16u16.to_le_bytes());
+            buf.extend(&0u64.to_le_bytes());
+            buf.extend(&0u64.to_le_bytes());
+        }
+        self.inner.write_all(&buf)
+    }
+
+    pub fn add_directory(&mut self, name: &str, modified: SystemTime) -> io::Result<()> {
+        let (time, date) = dos_time(modified);
+        let entry = CentralEntry {
+            name: format!("{}/", name.trim_end_matches('/')),
+            method: 0,
+            flags: FLAG_UTF8,
+            time,
+            date,
+            crc: 0,
+            compressed: 0,
+            size: 0,
+            offset: self.inner.count,
+            external: (0o40755 << 16) | 0x10,
+        };
+        self.write_local_header(&entry, false)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn add_file(&mut self, name: &str, path: &Path) -> io::Result<()> {
+        let mut file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let zip64 = metadata.len() >= self.zip64_threshold;
+        let (time, date) = dos_time(metadata.modified().unwrap_or_else(|_| SystemTime::now()));
+        let method = match self.compression {
+            Compression::Store => 0,
+            Compression::Deflate => 8,
+            Compression::Bzip2 => 12,
+        };
+        let mut entry = CentralEntry {
+            name: name.to_string(),
+            method,
+            flags: FLAG_UTF8 | FLAG_DATA_DESCRIPTOR,
+            time,
+            date,
+            crc: 0,
+            compressed: 0,
+            size: 0,
+            offset: self.inner.count,
+            external: 0o100644 << 16,
+        };
+        self.write_local_header(&entry, zip64)?;
+
+        let start = self.inner.count;
+        let mut hasher = crc32fast::Hasher::new();
+        let size = match self.compression {
+            Compression::Store => copy(&mut file, &mut self.inner, &mut hasher)?,
+            Compression::Deflate => {
+                let mut encoder = flate2::write::DeflateEncoder::new(
+                    &mut self.inner,
+                    flate2::Compression::default(),
+                );
+                let size = copy(&mut file, &mut encoder, &mut hasher)?;
+                encoder.finish()?;
+                size
+            }
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(&mut self.inner, bzip2::Compression::default());
+                let size = copy(&mut file, &mut encoder, &mut hasher)?;
+                encoder.finish()?;
+                size
+            }
+        };
+        entry.crc = hasher.finalize();
+        entry.size = size;
+        entry.compressed = self.inner.count - start;
+        if !zip64 && (entry.size >= ZIP64_LIMIT || entry.compressed >= ZIP64_LIMIT) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} got too big for a zip without Zip64", name),
+            ));
+        }
+
+        let mut descriptor: Vec<u8> = Vec::with_capacity(24);
+        descriptor.extend(&DATA_DESCRIPTOR.to_le_bytes());
+        descriptor.extend(&entry.crc.to_le_bytes());
+        if zip64 {
+            descriptor.extend(&entry.compressed.to_le_bytes());
+            descriptor.extend(&entry.size.to_le_bytes());
+        } else {
+            descriptor.extend(&(entry.compressed as u32).to_le_bytes());
+            descriptor.extend(&(entry.size as u32).to_le_bytes());
+        }
+        self.inner.write_all(&descriptor)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        let start = self.inner.count;
+        for entry in &self.entries {
+            let mut extra: Vec<u8> = Vec::new();
+            if entry.size >= ZIP64_LIMIT {
+                extra.extend(&entry.size.to_le_bytes());
+            }
+            if entry.compressed >= ZIP64_LIMIT {
+                extra.extend(&entry.compressed.to_le_bytes());
+            }
+            if entry.offset >= ZIP64_LIMIT {
+                extra.extend(&entry.offset.to_le_bytes());
+            }
+            let zip64 = !extra.is_empty();
+
+            let mut buf: Vec<u8> = Vec::with_capacity(46 + entry.name.len() + 28);
+            buf.extend(&CENTRAL_HEADER.to_le_bytes());
+            // Made by Unix, version 4.6
+            buf.extend(&((3u16 << 8) | 46).to_le_bytes());
+            buf.extend(&version_needed(entry.method, zip64).to_le_bytes());
+            buf.extend(&entry.flags.to_le_bytes());
+            buf.extend(&entry.method.to_le_bytes());
+            buf.extend(&entry.time.to_le_bytes());
+            buf.extend(&entry.date.to_le_bytes());
+            buf.extend(&entry.crc.to_le_bytes());
+            buf.extend(&clamp(entry.compressed).to_le_bytes());
+            buf.extend(&clamp(entry.size).to_le_bytes());
+            buf.extend(&(entry.name.len() as u16).to_le_bytes());
+            buf.extend(&(if zip64 { extra.len() as u16 + 4 } else { 0 }).to_le_bytes());
+            // comment length, disk number and internal attributes
+            buf.extend(&[0u8; 6]);
+            buf.extend(&entry.external.to_le_bytes());
+            buf.extend(&clamp(entry.offset).to_le_bytes());
+            buf.extend(entry.name.as_bytes());
+            if zip64 {
+                buf.extend(&1u16.to_le_bytes());
+                buf.extend(&(extra.len() as u16).to_le_bytes());
+                buf.extend(&extra);
+            }
+            self.inner.write_all(&buf)?;
+        }
+        let end = self.inner.count;
+        let count = self.entries.len() as u64;
+        let size = end - start;
+
+        let mut buf: Vec<u8> = Vec::with_capacity(98);
+        if count >= 0xFFFF || size >= ZIP64_LIMIT || start >= ZIP64_LIMIT {
+            buf.extend(&ZIP64_END.to_le_bytes());
+            buf.extend(&44u64.to_le_bytes());
+            buf.extend(&((3u16 << 8) | 46).to_le_bytes());
+            buf.extend(&45u16.to_le_bytes());
+            buf.extend(&0u32.to_le_bytes());
+            buf.extend(&0u32.to_le_bytes());
+            buf.extend(&count.to_le_bytes());
+            buf.extend(&count.to_le_bytes());
+            buf.extend(&size.to_le_bytes());
+            buf.extend(&start.to_le_bytes());
+
+            buf.extend(&ZIP64_LOCATOR.to_le_bytes());
+            buf.extend(&0u32.to_le_bytes());
+            buf.extend(&end.to_le_bytes());
+            buf.extend(&1u32.to_le_bytes());
+        }
+        buf.extend(&END.to_le_bytes());
+        buf.extend(&0u16.to_le_bytes());
+        buf.extend(&0u16.to_le_bytes());
+        buf.extend(&(count.min(0xFFFF) as u16).to_le_bytes());
+        buf.extend(&(count.min(0xFFFF) as u16).to_le_bytes());
+        buf.extend(&clamp(size).to_le_bytes());
+        buf.extend(&clamp(start).to_le_bytes());
+        buf.extend(&0u16.to_le_bytes());
+        self.inner.write_all(&buf)?;
+        self.inner.flush()?;
+
+        Ok(self.inner.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::db::user::token::generate_token;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    fn sample(name: &str, content: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("opencloud-zip-{}", generate_token()));
+        std::fs::create_dir_all(&dir).expect("Can't create the folder");
+        let path = dir.join(name);
+        std::fs::write(&path, content).expect("Can't write the file");
+        path
+    }
+
+    fn content() -> Vec<u8> {
+        // Text which compresses, then bytes which don't
+        let mut content = b"OpenCloud ".repeat(5000);
+        let mut x: u32 = 1;
+        for _ in 0..50_000 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            content.push((x >> 16) as u8);
+        }
+        content
+    }
+
+    /// Writes a folder and a file, then reads them back with the zip crate
+    fn round_trip(compression: Compression, zip64_threshold: u64) {
+        let content = content();
+        let path = sample("file.bin", &content);
+        let mut zip = ZipStream::new(Vec::new(), compression);
+        zip.zip64_threshold = zip64_threshold;
+        zip.add_directory("folder", SystemTime::now())
+            .expect("Can't add the folder");
+        zip.add_file("folder/file.bin", &path)
+            .expect("Can't add the file");
+        let bytes = zip.finish().expect("Can't finish the zip");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap_or(&path));
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).expect("Bad zip");
+        assert_eq!(archive.len(), 2);
+        assert!(archive.by_name("folder/").expect("No folder").is_dir());
+        let mut file = archive.by_name("folder/file.bin").expect("No file");
+        assert_eq!(file.size(), content.len() as u64);
+        let mut read = Vec::new();
+        file.read_to_end(&mut read).expect("Bad data");
+        assert_eq!(read, content);
+    }
+
+    #[test]
+    fn store() {
+        round_trip(Compression::Store, ZIP64_THRESHOLD);
+    }
+
+    #[test]
+    fn deflate() {
+        round_trip(Compression::Deflate, ZIP64_THRESHOLD);
+    }
+
+    #[test]
+    fn bzip2() {
+        round_trip(Compression::Bzip2, ZIP64_THRESHOLD);
+    }
+
+    #[test]
+    fn zip64() {
+        round_trip(Compression::Store, 0);
+        round_trip(Compression::Deflate, 0);
+    }
+}
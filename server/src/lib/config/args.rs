@@ -0,0 +1,223 @@
+//! Arguments of the command line, they come before the environment and the config file.
+
+use std::fmt;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: server [check-config] [options]
+
+Commands:
+    check-config        Validate the config and exit
+
+Options:
+    --config <path>     Config file, <data-dir>/config.yaml by default
+    --bind <ip[:port]>  Address the server listens on
+    --data-dir <path>   Folder of the database, the homes and the other data
+    -h, --help          Print this message
+
+Every field of the config can be set with an OPENCLOUD_<FIELD> environment variable,
+OPENCLOUD_SERVER_PORT=8080 for example. The flags come first, then the environment,
+then the config file.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Serve,
+    CheckConfig,
+    Help,
+}
+
+#[derive(Debug)]
+pub struct Args {
+    pub command: Command,
+    /// Absolute, the working directory changes to the data folder
+    pub config: Option<PathBuf>,
+    pub bind: Option<String>,
+    pub data_dir: Option<PathBuf>,
+}
+
+/// Parts of `--bind`, a missing one keeps the value of the config
+#[derive(Debug)]
+pub struct Bind {
+    pub ip: Option<String>,
+    pub port: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct ArgsError(String);
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn absolute(path: String) -> PathBuf {
+    let path = PathBuf::from(path);
+    match std::env::current_dir() {
+        Ok(e) if path.is_relative() => e.join(path),
+        _ => path,
+    }
+}
+
+impl Args {
+    /// Reads the arguments, without the name of the program. `OPENCLOUD_CONFIG` and
+    /// `OPENCLOUD_DATA_DIR` stand for the flags which aren't given.
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, ArgsError> {
+        let mut parsed = Args {
+            command: Command::Serve,
+            config: None,
+            bind: None,
+            data_dir: None,
+        };
+        while let Some(arg) = args.next() {
+            // `--flag=value` is the same as `--flag value`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .filter(|e| !e.is_empty())
+                    .ok_or_else(|| ArgsError(format!("{} needs a value", flag)))
+            };
+            match flag.as_str() {
+                "--config" => parsed.config = Some(absolute(value()?)),
+                "--bind" => parsed.bind = Some(value()?),
+                "--data-dir" => parsed.data_dir = Some(absolute(value()?)),
+                "-h" | "--help" => parsed.command = Command::Help,
+                "check-config" if parsed.command == Command::Serve => {
+                    parsed.command = Command::CheckConfig
+                }
+                _ => return Err(ArgsError(format!("Unknown argument {}", arg))),
+            }
+        }
+        if parsed.config.is_none() {
+            parsed.config = std::env::var("OPENCLOUD_CONFIG").ok().map(absolute);
+        }
+        if parsed.data_dir.is_none() {
+            parsed.data_dir = std::env::var("OPENCLOUD_DATA_DIR").ok().map(absolute);
+        }
+        Ok(parsed)
+    }
+
+    /// Path of the config file, relative to the data folder when it isn't given
+    pub fn config_path(&self) -> PathBuf {
+        match (&self.config, &self.data_dir) {
+            (Some(config), _) => config.clone(),
+            (None, Some(data_dir)) => data_dir.join("config.yaml"),
+            (None, None) => PathBuf::from("./config.yaml"),
+        }
+    }
+
+    /// `ip`, `ip:port`, `[ipv6]:port` or `:port`
+    pub fn bind_address(&self) -> Result<Option<Bind>, ArgsError> {
+        let bind = match &self.bind {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+        let bad = || ArgsError(format!("{} isn't an address", bind));
+        if let Ok(address) = bind.parse::<std::net::SocketAddr>() {
+            return Ok(Some(Bind {
+                ip: Some(address.ip().to_string()),
+                port: Some(i64::from(address.port())),
+            }));
+        }
+        match bind.rsplit_once(':') {
+            // More than one `:` without brackets is an IPv6 without port
+            Some((ip, _)) if ip.contains(':') => Ok(Some(Bind {
+                ip: Some(bind.clone()),
+                port: None,
+            })),
+            Some((ip, port)) => {
+                let port = port.parse::<u16>().map_err(|_| bad())?;
+                Ok(Some(Bind {
+                    ip: Some(ip.to_string()).filter(|e| !e.is_empty()),
+                    port: Some(i64::from(port)),
+                }))
+            }
+            None => Ok(Some(Bind {
+                ip: Some(bind.clone()),
+                port: None,
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(args.iter().map(|e| e.to_string()))
+    }
+
+    fn bind(value: &str) -> Result<(Option<String>, Option<i64>), ArgsError> {
+        let args = parse(&["--bind", value])?;
+        let bind = args.bind_address()?.expect("No address");
+        Ok((bind.ip, bind.port))
+    }
+
+    #[test]
+    fn flags() {
+        let args = parse(&["check-config", "--config", "/etc/opencloud.yaml"]).expect("Bad args");
+        assert_eq!(args.command, Command::CheckConfig);
+        assert_eq!(args.config, Some(PathBuf::from("/etc/opencloud.yaml")));
+        assert_eq!(args.config_path(), PathBuf::from("/etc/opencloud.yaml"));
+
+        let args = parse(&["--config=/etc/opencloud.yaml", "--bind=:8080"]).expect("Bad args");
+        assert_eq!(args.command, Command::Serve);
+        assert_eq!(args.config, Some(PathBuf::from("/etc/opencloud.yaml")));
+        assert_eq!(args.bind.as_deref(), Some(":8080"));
+
+        let args = parse(&["--data-dir", "/var/lib/opencloud"]).expect("Bad args");
+        assert_eq!(args.data_dir, Some(PathBuf::from("/var/lib/opencloud")));
+        assert_eq!(args.command, Command::Serve);
+
+        assert_eq!(parse(&["-h"]).expect("Bad args").command, Command::Help);
+        assert_eq!(parse(&["--help"]).expect("Bad args").command, Command::Help);
+    }
+
+    #[test]
+    fn relative_paths() {
+        let args = parse(&["--config", "config.yaml"]).expect("Bad args");
+        let config = args.config.expect("No config");
+        assert!(config.is_absolute());
+        assert!(config.ends_with("config.yaml"));
+    }
+
+    #[test]
+    fn bad_flags() {
+        assert!(parse(&["--bind"]).is_err());
+        assert!(parse(&["--bind="]).is_err());
+        assert!(parse(&["--port", "8080"]).is_err());
+        let error = parse(&["--config"]).expect_err("No error");
+        assert_eq!(error.to_string(), "--config needs a value");
+    }
+
+    #[test]
+    fn addresses() {
+        let ip = |e: &str| Some(e.to_string());
+        assert_eq!(
+            bind("127.0.0.1:8080").ok(),
+            Some((ip("127.0.0.1"), Some(8080)))
+        );
+        assert_eq!(bind("0.0.0.0").ok(), Some((ip("0.0.0.0"), None)));
+        assert_eq!(bind("[::1]:8080").ok(), Some((ip("::1"), Some(8080))));
+        assert_eq!(bind("::1").ok(), Some((ip("::1"), None)));
+        assert_eq!(bind(":8080").ok(), Some((None, Some(8080))));
+        assert_eq!(
+            bind("localhost:8080").ok(),
+            Some((ip("localhost"), Some(8080)))
+        );
+        assert!(bind("localhost:http").is_err());
+        assert!(bind(":70000").is_err());
+        assert!(parse(&[])
+            .expect("Bad args")
+            .bind_address()
+            .expect("Bad address")
+            .is_none());
+    }
+}
@@ -0,0 +1,385 @@
+//! Builds the config from its sources. A field takes the first value found in: the flags of the
+//! command line, the `OPENCLOUD_<FIELD>` variables, the config file, the defaults.
+
+use crate::lib::config::args::Args;
+use crate::lib::config::{Config, MAX_DAYS};
+use crate::lib::default::default_config;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+const ENV_PREFIX: &str = "OPENCLOUD_";
+/// Variables of the flags, see `Args::parse`
+const ENV_ARGS: [&str; 2] = ["OPENCLOUD_CONFIG", "OPENCLOUD_DATA_DIR"];
+
+#[derive(Debug)]
+pub struct ConfigError {
+    /// Where the bad value comes from: the file, a variable or a flag
+    pub source: String,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: `{}`: {}", self.source, field, self.message),
+            None => write!(f, "{}: {}", self.source, self.message),
+        }
+    }
+}
+
+/// Values of the fields, with their source
+struct Layers {
+    defaults: Mapping,
+    values: Mapping,
+    sources: HashMap<String, String>,
+}
+
+impl Layers {
+    fn new() -> Result<Self, ConfigError> {
+        let defaults = match serde_yaml::to_value(default_config()) {
+            Ok(Value::Mapping(e)) => e,
+            _ => {
+                return Err(ConfigError {
+                    source: String::from("default"),
+                    field: None,
+                    message: String::from("can't be built"),
+                })
+            }
+        };
+        Ok(Self {
+            values: defaults.clone(),
+            defaults,
+            sources: HashMap::new(),
+        })
+    }
+
+    fn fields(&self) -> Vec<String> {
+        self.defaults
+            .iter()
+            .filter_map(|(k, _)| k.as_str().map(String::from))
+            .collect()
+    }
+
+    /// Checks the type of the value alone, so the error names its field
+    fn check(&self, field: &str, value: &Value) -> Result<(), String> {
+        let mut values = self.defaults.clone();
+        values.insert(Value::String(field.to_string()), value.clone());
+        serde_yaml::from_value::<Config>(Value::Mapping(values))
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn set(&mut self, field: &str, value: Value, source: String) -> Result<(), ConfigError> {
+        if let Err(message) = self.check(field, &value) {
+            return Err(ConfigError {
+                source,
+                field: Some(field.to_string()),
+                message,
+            });
+        }
+        self.values.insert(Value::String(field.to_string()), value);
+        self.sources.insert(field.to_string(), source);
+        Ok(())
+    }
+
+    fn source_of(&self, field: &str) -> String {
+        self.sources
+            .get(field)
+            .cloned()
+            .unwrap_or_else(|| String::from("default"))
+    }
+}
+
+fn read_file(layers: &mut Layers, path: &Path) -> Result<(), ConfigError> {
+    let source = path.display().to_string();
+    let error = |field: Option<String>, message: String| ConfigError {
+        source: source.clone(),
+        field,
+        message,
+    };
+    let content = std::fs::read_to_string(path).map_err(|e| error(None, e.to_string()))?;
+    // serde_yaml refuses a document without a value, such as a file of comments
+    if content
+        .lines()
+        .map(str::trim)
+        .all(|e| e.is_empty() || e.starts_with('#'))
+    {
+        return Ok(());
+    }
+    let file = match serde_yaml::from_str::<Value>(&content) {
+        Ok(Value::Mapping(e)) => e,
+        Ok(Value::Null) => Mapping::new(),
+        Ok(_) => return Err(error(None, String::from("isn't a mapping of fields"))),
+        Err(e) => return Err(error(None, e.to_string())),
+    };
+    let fields = layers.fields();
+    for (key, value) in file {
+        let field = match key.as_str() {
+            Some(e) if fields.iter().any(|f| f == e) => e.to_string(),
+            _ => {
+                let name = serde_yaml::to_string(&key).unwrap_or_default();
+                return Err(error(
+                    Some(name.trim_start_matches("---").trim().to_string()),
+                    String::from("isn't a field of the config"),
+                ));
+            }
+        };
+        layers.set(&field, value, source.clone())?;
+    }
+    Ok(())
+}
+
+fn read_env(layers: &mut Layers) -> Result<(), ConfigError> {
+    let fields = layers.fields();
+    let mut vars: Vec<(String, String)> = std::env::vars()
+        .filter(|(k, _)| k.starts_with(ENV_PREFIX) && !ENV_ARGS.contains(&k.as_str()))
+        .collect();
+    vars.sort();
+    for (name, raw) in vars {
+        let field = name[ENV_PREFIX.len()..].to_lowercase();
+        if !fields.contains(&field) {
+            return Err(ConfigError {
+                source: name,
+                field: None,
+                message: String::from("doesn't match a field of the config"),
+            });
+        }
+        // A variable has no quotes, `123` is also a valid password
+        let parsed =
+            serde_yaml::from_str::<Value>(&raw).unwrap_or_else(|_| Value::String(raw.clone()));
+        if layers.check(&field, &parsed).is_ok() {
+            layers.set(&field, parsed, name)?;
+        } else if layers.check(&field, &Value::String(raw.clone())).is_ok() {
+            layers.set(&field, Value::String(raw), name)?;
+        } else {
+            layers.set(&field, parsed, name)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_args(layers: &mut Layers, args: &Args) -> Result<(), ConfigError> {
+    let bind = args.bind_address().map_err(|e| ConfigError {
+        source: String::from("--bind"),
+        field: None,
+        message: e.to_string(),
+    })?;
+    if let Some(bind) = bind {
+        if let Some(ip) = bind.ip {
+            layers.set("server_ip", Value::String(ip), String::from("--bind"))?;
+        }
+        if let Some(port) = bind.port {
+            layers.set("server_port", Value::from(port), String::from("--bind"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Values which have the right type but can't work
+fn validate(config: &Config) -> Result<(), (&'static str, String)> {
+    if !(1..=65535).contains(&config.server_port) {
+        return Err(("server_port", String::from("must be between 1 and 65535")));
+    }
+    if let Some(port) = config.db_port {
+        if !(1..=65535).contains(&port) {
+            return Err(("db_port", String::from("must be between 1 and 65535")));
+        }
+    }
+    if config.server_ip.is_empty() {
+        return Err(("server_ip", String::from("is empty")));
+    }
+    if config.argon2_iterations == 0 {
+        return Err(("argon2_iterations", String::from("must be at least 1")));
+    }
+    if config.argon2_parallelism == 0 {
+        return Err(("argon2_parallelism", String::from("must be at least 1")));
+    }
+    // Argon2 needs 8 KiB per lane
+    let memory = config.argon2_parallelism.saturating_mul(8);
+    if config.argon2_memory_kib < memory {
+        return Err(("argon2_memory_kib", format!("must be at least {}", memory)));
+    }
+    if !(1..=MAX_DAYS).contains(&config.session_days) {
+        return Err((
+            "session_days",
+            format!("must be between 1 and {}", MAX_DAYS),
+        ));
+    }
+    // Dates further than that overflow
+    let days = [
+        ("trash_retention_days", config.trash_retention_days),
+        ("version_retention_days", config.version_retention_days),
+        ("upload_expire_days", config.upload_expire_days),
+        ("log_retention_days", config.log_retention_days),
+    ];
+    for (field, value) in days {
+        if !(0..=MAX_DAYS).contains(&value) {
+            return Err((field, format!("must be between 0 and {}", MAX_DAYS)));
+        }
+    }
+    if config.version_keep < 0 {
+        return Err(("version_keep", String::from("can't be negative")));
+    }
+    if config.log_max_rows < 0 {
+        return Err(("log_max_rows", String::from("can't be negative")));
+    }
+    // 0 would refuse every archive
+    let extract = [
+        ("max_extract_size", config.max_extract_size),
+        ("max_extract_ratio", config.max_extract_ratio),
+        ("max_extract_entries", config.max_extract_entries),
+    ];
+    for (field, value) in extract {
+        if value == 0 {
+            return Err((field, String::from("must be at least 1")));
+        }
+    }
+    let max_secs = MAX_DAYS as u64 * 24 * 60 * 60;
+    let secs = [
+        ("login_window_secs", config.login_window_secs),
+        ("lockout_secs", config.lockout_secs),
+    ];
+    for (field, value) in secs {
+        if value > max_secs {
+            return Err((field, format!("must be at most {}", max_secs)));
+        }
+    }
+    Ok(())
+}
+
+/// Builds the config, `path` is skipped if it doesn't exist
+pub fn load(args: &Args, path: &Path) -> Result<Config, ConfigError> {
+    let mut layers = Layers::new()?;
+    if path.exists() {
+        read_file(&mut layers, path)?;
+    }
+    read_env(&mut layers)?;
+    read_args(&mut layers, args)?;
+
+    let config: Config =
+        serde_yaml::from_value(Value::Mapping(layers.values.clone())).map_err(|e| ConfigError {
+            source: String::from("config"),
+            field: None,
+            message: e.to_string(),
+        })?;
+    validate(&config).map_err(|(field, message)| ConfigError {
+        source: layers.source_of(field),
+        field: Some(field.to_string()),
+        message,
+    })?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::db::user::token::generate_token;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// The variables are shared by the tests which run at the same time
+    static ENV: Mutex<()> = Mutex::new(());
+
+    struct Sources {
+        file: PathBuf,
+        vars: Vec<&'static str>,
+    }
+
+    impl Sources {
+        fn new(file: &str, vars: &[(&'static str, &str)]) -> Self {
+            let path = std::env::temp_dir().join(format!("opencloud-{}.yaml", generate_token()));
+            std::fs::write(&path, file).expect("Can't write the config");
+            for (name, value) in vars {
+                std::env::set_var(name, value);
+            }
+            Self {
+                file: path,
+                vars: vars.iter().map(|(name, _)| *name).collect(),
+            }
+        }
+
+        fn load(&self, args: &[&str]) -> Result<Config, ConfigError> {
+            let args = Args::parse(args.iter().map(|e| e.to_string())).expect("Bad args");
+            load(&args, &self.file)
+        }
+    }
+
+    impl Drop for Sources {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.file);
+            for name in &self.vars {
+                std::env::remove_var(name);
+            }
+        }
+    }
+
+    fn lock() -> std::sync::MutexGuard<'static, ()> {
+        ENV.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn precedence() {
+        let _env = lock();
+        let config = Sources::new("", &[]).load(&[]).expect("Bad config");
+        assert_eq!(config.server_port, default_config().server_port);
+        let config = Sources::new("# server_port: 1000\n", &[])
+            .load(&[])
+            .expect("Bad config");
+        assert_eq!(config.server_port, default_config().server_port);
+
+        let file = "server_port: 1000\nserver_ip: 10.0.0.1\nsession_days: 3\n";
+        let config = Sources::new(file, &[]).load(&[]).expect("Bad config");
+        assert_eq!(config.server_port, 1000);
+        assert_eq!(config.server_ip, "10.0.0.1");
+
+        let sources = Sources::new(file, &[("OPENCLOUD_SERVER_PORT", "2000")]);
+        let config = sources.load(&[]).expect("Bad config");
+        assert_eq!(config.server_port, 2000);
+        assert_eq!(config.server_ip, "10.0.0.1");
+        assert_eq!(config.session_days, 3);
+
+        // `:port` keeps the address of the file
+        let config = sources.load(&["--bind=:3000"]).expect("Bad config");
+        assert_eq!(config.server_port, 3000);
+        assert_eq!(config.server_ip, "10.0.0.1");
+
+        let config = sources.load(&["--bind", "[::1]:4000"]).expect("Bad config");
+        assert_eq!(config.server_port, 4000);
+        assert_eq!(config.server_ip, "::1");
+    }
+
+    #[test]
+    fn errors_name_the_field() {
+        let _env = lock();
+        let sources = Sources::new("server_port: 0\n", &[]);
+        let error = sources.load(&[]).expect_err("No error");
+        assert_eq!(error.field.as_deref(), Some("server_port"));
+        assert_eq!(error.source, sources.file.display().to_string());
+
+        let error = Sources::new("server_prot: 8080\n", &[])
+            .load(&[])
+            .expect_err("No error");
+        assert_eq!(error.field.as_deref(), Some("server_prot"));
+
+        let error = Sources::new("", &[("OPENCLOUD_SESSION_DAYS", "many")])
+            .load(&[])
+            .expect_err("No error");
+        assert_eq!(error.field.as_deref(), Some("session_days"));
+        assert_eq!(error.source, "OPENCLOUD_SESSION_DAYS");
+
+        // The flag is named for a value of the flag
+        let error = Sources::new("", &[])
+            .load(&["--bind", "localhost:0"])
+            .expect_err("No error");
+        assert_eq!(error.field.as_deref(), Some("server_port"));
+        assert_eq!(error.source, "--bind");
+
+        let error = Sources::new("max_extract_ratio: 0\n", &[])
+            .load(&[])
+            .expect_err("No error");
+        assert_eq!(error.field.as_deref(), Some("max_extract_ratio"));
+    }
+}
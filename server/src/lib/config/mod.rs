@@ -1,5 +1,15 @@
+use crate::lib::db::log::retention::LogRetention;
+use crate::lib::db::user::HashCost;
+use crate::lib::db::version::prune::Retention;
 use datagn::{config::DatabaseConfig, database::DatabaseType};
 use serde::{Deserialize, Serialize};
+
+pub mod args;
+pub mod load;
+
+/// Largest number of days of a retention, chrono panics far above it
+pub const MAX_DAYS: i64 = 100 * 366;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     pub server_ip: String,
@@ -11,9 +21,180 @@ pub struct Config {
     pub db_user: Option<String>,
     pub db_password: Option<String>,
     pub db_database: Option<String>,
+    #[serde(default = "default_max_extract_size")]
+    pub max_extract_size: u64,
+    #[serde(default = "default_max_extract_ratio")]
+    pub max_extract_ratio: u64,
+    /// Maximum number of entries extracted from an archive
+    #[serde(default = "default_max_extract_entries")]
+    pub max_extract_entries: u64,
+    /// Number of days before a deleted file is removed for good, 0 keeps it forever
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: i64,
+    /// Number of previous versions kept for a file, 0 keeps all of them
+    #[serde(default = "default_version_keep")]
+    pub version_keep: i64,
+    /// Number of days a previous version is kept, 0 keeps it forever
+    #[serde(default = "default_version_retention_days")]
+    pub version_retention_days: i64,
+    /// Number of days an unfinished upload is kept after its last chunk, 0 keeps it forever
+    #[serde(default = "default_upload_expire_days")]
+    pub upload_expire_days: i64,
+    /// Default quota of the users in bytes, none means unlimited
+    #[serde(default)]
+    pub quota_bytes: Option<i64>,
+    /// Default maximum number of files of the users, none means unlimited
+    #[serde(default)]
+    pub quota_files: Option<i64>,
+    /// Account made admin when the server starts or when it is created. Without it, the first
+    /// account becomes admin.
+    #[serde(default)]
+    pub admin: Option<String>,
+    /// Memory used to hash a password with Argon2id, in KiB
+    #[serde(default = "default_argon2_memory_kib")]
+    pub argon2_memory_kib: u32,
+    /// Number of passes of Argon2id
+    #[serde(default = "default_argon2_iterations")]
+    pub argon2_iterations: u32,
+    /// Number of lanes of Argon2id
+    #[serde(default = "default_argon2_parallelism")]
+    pub argon2_parallelism: u32,
+    /// Number of days a login lasts
+    #[serde(default = "default_session_days")]
+    pub session_days: i64,
+    /// Failed logins of an account before it is locked, 0 never locks it
+    #[serde(default = "default_login_attempts")]
+    pub login_attempts: u32,
+    /// Failed logins from an IP before it is locked, 0 never locks it
+    #[serde(default = "default_login_ip_attempts")]
+    pub login_ip_attempts: u32,
+    /// Accounts created from an IP before it is locked, 0 never locks it
+    #[serde(default = "default_signup_attempts")]
+    pub signup_attempts: u32,
+    /// Time during which the attempts are counted, in seconds
+    #[serde(default = "default_login_window_secs")]
+    pub login_window_secs: u64,
+    /// Time an account or an IP stays locked, in seconds
+    #[serde(default = "default_lockout_secs")]
+    pub lockout_secs: u64,
+    /// Requests per second of an IP on the API, 0 disables the limit
+    #[serde(default = "default_api_rate")]
+    pub api_rate: u32,
+    /// Requests an IP can send at once before the rate applies
+    #[serde(default = "default_api_burst")]
+    pub api_burst: u32,
+    /// IPs of the reverse proxies in front of the server. `X-Forwarded-For` is only read on
+    /// their requests, else a client could pick the IP it is counted as.
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+    /// Days an entry stays in the log before it is archived, 0 keeps it forever
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: i64,
+    /// Number of entries kept in the log, the oldest are archived. 0 keeps all of them
+    #[serde(default = "default_log_max_rows")]
+    pub log_max_rows: i64,
+}
+
+pub fn default_max_extract_size() -> u64 {
+    10 * 1024 * 1024 * 1024
+}
+
+pub fn default_max_extract_ratio() -> u64 {
+    100
+}
+
+pub fn default_max_extract_entries() -> u64 {
+    10_000
+}
+
+pub fn default_trash_retention_days() -> i64 {
+    30
+}
+
+pub fn default_upload_expire_days() -> i64 {
+    7
+}
+
+pub fn default_version_keep() -> i64 {
+    10
+}
+
+pub fn default_version_retention_days() -> i64 {
+    0
+}
+
+pub fn default_argon2_memory_kib() -> u32 {
+    19 * 1024
+}
+
+pub fn default_argon2_iterations() -> u32 {
+    2
+}
+
+pub fn default_argon2_parallelism() -> u32 {
+    1
+}
+
+pub fn default_session_days() -> i64 {
+    30
+}
+
+pub fn default_login_attempts() -> u32 {
+    5
+}
+
+pub fn default_login_ip_attempts() -> u32 {
+    20
+}
+
+pub fn default_signup_attempts() -> u32 {
+    5
+}
+
+pub fn default_login_window_secs() -> u64 {
+    15 * 60
+}
+
+pub fn default_lockout_secs() -> u64 {
+    15 * 60
+}
+
+pub fn default_api_rate() -> u32 {
+    20
+}
+
+pub fn default_api_burst() -> u32 {
+    100
+}
+
+pub fn default_log_retention_days() -> i64 {
+    180
+}
+
+pub fn default_log_max_rows() -> i64 {
+    100_000
 }
 
 impl Config {
+    pub fn version_retention(&self) -> Retention {
+        Retention {
+            keep: self.version_keep,
+            days: self.version_retention_days,
+        }
+    }
+    pub fn log_retention(&self) -> LogRetention {
+        LogRetention {
+            days: self.log_retention_days,
+            max_rows: self.log_max_rows,
+        }
+    }
+    pub fn hash_cost(&self) -> HashCost {
+        HashCost {
+            memory_kib: self.argon2_memory_kib,
+            iterations: self.argon2_iterations,
+            parallelism: self.argon2_parallelism,
+        }
+    }
     pub fn get_server(&self) -> String {
         format!("{}:{}", self.get_server_ip(), self.get_server_port())
     }
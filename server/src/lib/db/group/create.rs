@@ -0,0 +1,28 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    // "Group" is a keyword of SQL
+    for table in &[
+        "CREATE TABLE IF NOT EXISTS UserGroup (
+        id              INTEGER PRIMARY KEY,
+        name            TEXT NOT NULL,
+        quota_bytes     INTEGER,
+        quota_files     INTEGER,
+        date            TEXT NOT NULL
+        )",
+        "CREATE TABLE IF NOT EXISTS GroupMember (
+        group_id        INTEGER NOT NULL,
+        user_id         INTEGER NOT NULL
+        )",
+    ] {
+        match database.execute(table).await {
+            Ok(_) => {}
+            Err(e) => {
+                if cfg!(feature = "log") {
+                    error(e);
+                }
+            }
+        };
+    }
+}
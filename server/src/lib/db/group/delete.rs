@@ -0,0 +1,29 @@
+use datagn::DatabasePool;
+
+/// Removes the group, its members and the folders shared with it
+pub async fn delete_group(database: &mut DatabasePool, id: i32) -> bool {
+    for query in &[
+        "DELETE FROM GroupMember WHERE group_id=?1",
+        "DELETE FROM Permission WHERE grantee_type='group' AND grantee_id=?1",
+        "DELETE FROM UserGroup WHERE id=?1",
+    ] {
+        if database
+            .execute_with_bind(query, &[id.to_string()])
+            .await
+            .is_err()
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub async fn remove_member(database: &mut DatabasePool, group_id: i32, user_id: i32) -> bool {
+    database
+        .execute_with_bind(
+            "DELETE FROM GroupMember WHERE group_id=?1 AND user_id=?2",
+            &[group_id.to_string(), user_id.to_string()],
+        )
+        .await
+        .is_ok()
+}
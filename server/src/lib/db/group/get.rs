@@ -0,0 +1,123 @@
+use crate::lib::db::group::model::Group;
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
+
+pub async fn get_groups(database: &mut DatabasePool) -> Vec<Group> {
+    let query = match database
+        .execute_and_fetch_all(
+            "SELECT id, name, quota_bytes, quota_files, date FROM UserGroup ORDER BY name",
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_groups : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut groups: Vec<Group> = Vec::new();
+    for row in query {
+        groups.push(Group {
+            id: row.try_get("id").unwrap_or_default(),
+            name: row.try_get("name").unwrap_or_default(),
+            quota_bytes: row.try_get("quota_bytes").unwrap_or_default(),
+            quota_files: row.try_get("quota_files").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        });
+    }
+    groups
+}
+
+pub async fn get_group(database: &mut DatabasePool, id: i32) -> Option<Group> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT id, name, quota_bytes, quota_files, date FROM UserGroup WHERE id=?1",
+            &[id.to_string()],
+        )
+        .await
+    {
+        Ok(row) => Some(Group {
+            id: row.try_get("id").ok()?,
+            name: row.try_get("name").ok()?,
+            quota_bytes: row.try_get("quota_bytes").unwrap_or_default(),
+            quota_files: row.try_get("quota_files").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        }),
+        Err(_) => None,
+    }
+}
+
+pub async fn get_group_by_name(database: &mut DatabasePool, name: String) -> Option<Group> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT id, name, quota_bytes, quota_files, date FROM UserGroup WHERE name=?1",
+            &[name],
+        )
+        .await
+    {
+        Ok(row) => Some(Group {
+            id: row.try_get("id").ok()?,
+            name: row.try_get("name").ok()?,
+            quota_bytes: row.try_get("quota_bytes").unwrap_or_default(),
+            quota_files: row.try_get("quota_files").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        }),
+        Err(_) => None,
+    }
+}
+
+pub async fn get_groups_of_user(database: &mut DatabasePool, user_id: i32) -> Vec<Group> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT id, name, quota_bytes, quota_files, date FROM UserGroup JOIN GroupMember ON GroupMember.group_id = id WHERE GroupMember.user_id=?1 ORDER BY name",
+            &[user_id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_groups_of_user : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut groups: Vec<Group> = Vec::new();
+    for row in query {
+        groups.push(Group {
+            id: row.try_get("id").unwrap_or_default(),
+            name: row.try_get("name").unwrap_or_default(),
+            quota_bytes: row.try_get("quota_bytes").unwrap_or_default(),
+            quota_files: row.try_get("quota_files").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        });
+    }
+    groups
+}
+
+/// Names of the members of a group
+pub async fn get_members(database: &mut DatabasePool, group_id: i32) -> Vec<String> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT User.name FROM User JOIN GroupMember ON GroupMember.user_id = User.id WHERE GroupMember.group_id=?1 ORDER BY User.name",
+            &[group_id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_members : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut members: Vec<String> = Vec::new();
+    for row in query {
+        members.push(row.try_get("name").unwrap_or_default());
+    }
+    members
+}
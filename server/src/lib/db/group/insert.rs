@@ -0,0 +1,27 @@
+use crate::lib::db::group::model::Group;
+use datagn::DatabasePool;
+
+pub async fn insert(database: &mut DatabasePool, group: &Group) -> bool {
+    database
+        .execute_with_bind(
+            "INSERT INTO UserGroup (name,quota_bytes,quota_files,date) VALUES(?1, NULLIF(?2, ''), NULLIF(?3, ''), ?4)",
+            &[
+                group.name.clone(),
+                group.quota_bytes.map(|e| e.to_string()).unwrap_or_default(),
+                group.quota_files.map(|e| e.to_string()).unwrap_or_default(),
+                group.date.clone(),
+            ],
+        )
+        .await
+        .is_ok()
+}
+
+pub async fn add_member(database: &mut DatabasePool, group_id: i32, user_id: i32) -> bool {
+    database
+        .execute_with_bind(
+            "INSERT INTO GroupMember (group_id,user_id) SELECT ?1, ?2 WHERE NOT EXISTS (SELECT 1 FROM GroupMember WHERE group_id=?1 AND user_id=?2)",
+            &[group_id.to_string(), user_id.to_string()],
+        )
+        .await
+        .is_ok()
+}
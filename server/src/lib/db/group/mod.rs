@@ -0,0 +1,6 @@
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod insert;
+pub mod model;
+pub mod update;
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Group {
+    pub id: i32,
+    pub name: String,
+    /// Quota of the members who don't have their own, a negative value means unlimited
+    pub quota_bytes: Option<i64>,
+    pub quota_files: Option<i64>,
+    pub date: String,
+}
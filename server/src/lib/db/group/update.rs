@@ -0,0 +1,17 @@
+use crate::lib::db::group::model::Group;
+use datagn::DatabasePool;
+
+pub async fn update_group(database: &mut DatabasePool, group: &Group) -> bool {
+    database
+        .execute_with_bind(
+            "UPDATE UserGroup SET name=?2, quota_bytes=NULLIF(?3, ''), quota_files=NULLIF(?4, '') WHERE id=?1",
+            &[
+                group.id.to_string(),
+                group.name.clone(),
+                group.quota_bytes.map(|e| e.to_string()).unwrap_or_default(),
+                group.quota_files.map(|e| e.to_string()).unwrap_or_default(),
+            ],
+        )
+        .await
+        .is_ok()
+}
@@ -1,5 +1,8 @@
+use crate::lib::db::log::model::log_date;
+use chrono::{DateTime, Utc};
 use datagn::DatabasePool;
 use logger::error;
+use sqlx::Row;
 
 pub async fn create(database: &mut DatabasePool) {
     match database
@@ -20,4 +23,41 @@ pub async fn create(database: &mut DatabasePool) {
             }
         }
     };
+
+    // Owner of the file, the user who acted may only have a permission on it
+    let _ = database
+        .execute("ALTER TABLE Log ADD COLUMN owner_id INTEGER")
+        .await;
+    // Details of the action, the entries written before have none of them
+    for column in &[
+        "path TEXT",
+        "destination TEXT",
+        "bytes INTEGER",
+        "ip TEXT",
+        "user_agent TEXT",
+        "success INTEGER",
+    ] {
+        let _ = database
+            .execute(&format!("ALTER TABLE Log ADD COLUMN {}", column))
+            .await;
+    }
+
+    // The dates were written in RFC 2822, which can't be sorted nor compared
+    if let Ok(rows) = database
+        .execute_and_fetch_all("SELECT id, date FROM Log WHERE date NOT LIKE '____-__-__T%'")
+        .await
+    {
+        for row in rows {
+            let id: i32 = row.try_get("id").unwrap_or_default();
+            let date: String = row.try_get("date").unwrap_or_default();
+            if let Ok(e) = DateTime::parse_from_rfc2822(&date) {
+                let _ = database
+                    .execute_with_bind(
+                        "UPDATE Log SET date=?1 WHERE id=?2",
+                        &[log_date(e.with_timezone(&Utc)), id.to_string()],
+                    )
+                    .await;
+            }
+        }
+    }
 }
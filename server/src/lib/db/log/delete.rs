@@ -1,13 +1,9 @@
-/*use sqlx::Executor;
+use datagn::DatabasePool;
 
-pub async fn _delete(id: i32) -> bool {
-    let mut conn = conn().await;
-    match conn
-        .execute(format!("delete from log where id = {}", id).as_str())
+/// Removes every entry up to `until`, once they are archived
+pub async fn delete_logs_until(database: &mut DatabasePool, until: i32) -> bool {
+    database
+        .execute_with_bind("DELETE FROM Log WHERE id <= ?1", &[until.to_string()])
         .await
-    {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+        .is_ok()
 }
-*/
@@ -1 +1,131 @@
+use crate::lib::db::log::model::{Log, LogQuery};
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
 
+/// The latest actions first, with the names of the user and of the owner
+pub async fn get_logs(database: &mut DatabasePool, query: &LogQuery) -> Vec<(Log, String, String)> {
+    let path = query
+        .path
+        .as_deref()
+        .map(|e| e.trim_matches('/').to_string())
+        .unwrap_or_default();
+    let rows = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT Log.id, Log.type, Log.date, Log.path, Log.destination, Log.bytes, Log.ip, Log.user_agent, COALESCE(Log.success, 1) AS success, COALESCE(u.name, '') AS user_name, COALESCE(o.name, '') AS owner_name FROM Log LEFT JOIN User u ON u.id = Log.user_id LEFT JOIN User o ON o.id = Log.owner_id
+            WHERE (NULLIF(?1,'') IS NULL OR Log.user_id = ?1 OR Log.owner_id = ?1)
+            AND (NULLIF(?2,'') IS NULL OR Log.type = ?2)
+            AND (NULLIF(?3,'') IS NULL OR Log.date >= ?3)
+            AND (NULLIF(?4,'') IS NULL OR substr(Log.date, 1, length(?4)) <= ?4)
+            AND (NULLIF(?5,'') IS NULL OR Log.path = ?5 OR substr(Log.path, 1, length(?5) + 1) = ?5 || '/')
+            AND (NULLIF(?6,'') IS NULL OR Log.id < ?6)
+            ORDER BY Log.id DESC LIMIT ?7",
+            &[
+                query.user_id.map(|e| e.to_string()).unwrap_or_default(),
+                query.action.clone().unwrap_or_default(),
+                query.from.clone().unwrap_or_default(),
+                query.to.clone().unwrap_or_default(),
+                path,
+                query.before.map(|e| e.to_string()).unwrap_or_default(),
+                query.limit.to_string(),
+            ],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_logs : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut logs: Vec<(Log, String, String)> = Vec::new();
+    for row in rows {
+        logs.push((
+            Log {
+                id: row.try_get("id").unwrap_or_default(),
+                action: row.try_get("type").unwrap_or_default(),
+                date: row.try_get("date").unwrap_or_default(),
+                path: row.try_get("path").unwrap_or_default(),
+                destination: row.try_get("destination").unwrap_or_default(),
+                bytes: row.try_get("bytes").unwrap_or_default(),
+                ip: row.try_get("ip").unwrap_or_default(),
+                user_agent: row.try_get("user_agent").unwrap_or_default(),
+                success: row.try_get("success").unwrap_or(true),
+            },
+            row.try_get("user_name").unwrap_or_default(),
+            row.try_get("owner_name").unwrap_or_default(),
+        ));
+    }
+    logs
+}
+
+/// Entries from `after` (excluded) to `until` (included), the oldest first
+pub async fn get_logs_range(
+    database: &mut DatabasePool,
+    after: i32,
+    until: i32,
+    limit: i64,
+) -> Vec<(Log, String, String)> {
+    let rows = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT Log.id, Log.type, Log.date, Log.path, Log.destination, Log.bytes, Log.ip, Log.user_agent, COALESCE(Log.success, 1) AS success, COALESCE(u.name, '') AS user_name, COALESCE(o.name, '') AS owner_name FROM Log LEFT JOIN User u ON u.id = Log.user_id LEFT JOIN User o ON o.id = Log.owner_id
+            WHERE Log.id > ?1 AND Log.id <= ?2 ORDER BY Log.id LIMIT ?3",
+            &[after.to_string(), until.to_string(), limit.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_logs_range : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut logs: Vec<(Log, String, String)> = Vec::new();
+    for row in rows {
+        logs.push((
+            Log {
+                id: row.try_get("id").unwrap_or_default(),
+                action: row.try_get("type").unwrap_or_default(),
+                date: row.try_get("date").unwrap_or_default(),
+                path: row.try_get("path").unwrap_or_default(),
+                destination: row.try_get("destination").unwrap_or_default(),
+                bytes: row.try_get("bytes").unwrap_or_default(),
+                ip: row.try_get("ip").unwrap_or_default(),
+                user_agent: row.try_get("user_agent").unwrap_or_default(),
+                success: row.try_get("success").unwrap_or(true),
+            },
+            row.try_get("user_name").unwrap_or_default(),
+            row.try_get("owner_name").unwrap_or_default(),
+        ));
+    }
+    logs
+}
+
+/// Last entry written before `date`
+pub async fn last_id_before(database: &mut DatabasePool, date: String) -> Option<i32> {
+    match database
+        .execute_and_fetch_one_with_bind("SELECT MAX(id) AS id FROM Log WHERE date < ?1", &[date])
+        .await
+    {
+        Ok(row) => row.try_get("id").ok(),
+        Err(_) => None,
+    }
+}
+
+/// Last entry which isn't one of the `keep` latest ones
+pub async fn last_id_over(database: &mut DatabasePool, keep: i64) -> Option<i32> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT id FROM Log ORDER BY id DESC LIMIT 1 OFFSET ?1",
+            &[keep.to_string()],
+        )
+        .await
+    {
+        Ok(row) => row.try_get("id").ok(),
+        Err(_) => None,
+    }
+}
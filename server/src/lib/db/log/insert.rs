@@ -1,14 +1,25 @@
-use crate::lib::db::log::model::ActionType;
+use crate::lib::db::log::model::{log_date, NewLog};
 use chrono::Utc;
 
 use datagn::DatabasePool;
 
-pub async fn insert(database: &mut DatabasePool, user_id: i32, action_type: ActionType) {
-    let date: String = Utc::now().to_rfc2822();
+pub async fn insert(database: &mut DatabasePool, log: NewLog) {
+    let date: String = log_date(Utc::now());
     database
         .execute_with_bind(
-            "INSERT INTO Log (type,user_id,date) VALUES(?1, ?2, ?3)",
-            &[action_type.format(), user_id.to_string(), date.clone()],
+            "INSERT INTO Log (type,user_id,owner_id,date,path,destination,bytes,ip,user_agent,success) VALUES(?1, ?2, ?3, ?4, NULLIF(?5,''), NULLIF(?6,''), NULLIF(?7,''), NULLIF(?8,''), NULLIF(?9,''), ?10)",
+            &[
+                log.action.format(),
+                log.user_id.to_string(),
+                log.owner_id.to_string(),
+                date,
+                log.path.unwrap_or_default(),
+                log.destination.unwrap_or_default(),
+                log.bytes.map(|e| e.to_string()).unwrap_or_default(),
+                log.ip.unwrap_or_default(),
+                log.user_agent.unwrap_or_default(),
+                i32::from(log.success).to_string(),
+            ],
         )
         .await
         .expect("Error");
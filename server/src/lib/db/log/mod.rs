@@ -3,4 +3,5 @@ pub mod delete;
 pub mod get;
 pub mod insert;
 pub mod model;
+pub mod retention;
 pub mod update;
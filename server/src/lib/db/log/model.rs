@@ -1,7 +1,30 @@
+use crate::lib::http::throttle::ip_of;
+use actix_web::HttpRequest;
+use chrono::{DateTime, SecondsFormat, Utc};
+use shared::LogItem;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ActionType {
     Delete,
     Upload,
     Get,
+    Extract,
+    Move,
+    Copy,
+    Rename,
+    Mkdir,
+    Restore,
+    Share,
+    /// Wrong password or code at login
+    LoginFailed,
+    /// Too many failed logins, the account is locked for a while
+    Lockout,
+    Login,
+    Logout,
+    /// A file shown in the browser
+    Preview,
+    /// A folder or a selection downloaded as an archive
+    Archive,
 }
 
 impl ActionType {
@@ -10,6 +33,143 @@ impl ActionType {
             ActionType::Delete => String::from("Delete"),
             ActionType::Upload => String::from("Upload"),
             ActionType::Get => String::from("Get"),
+            ActionType::Extract => String::from("Extract"),
+            ActionType::Move => String::from("Move"),
+            ActionType::Copy => String::from("Copy"),
+            ActionType::Rename => String::from("Rename"),
+            ActionType::Mkdir => String::from("Mkdir"),
+            ActionType::Restore => String::from("Restore"),
+            ActionType::Share => String::from("Share"),
+            ActionType::LoginFailed => String::from("LoginFailed"),
+            ActionType::Lockout => String::from("Lockout"),
+            ActionType::Login => String::from("Login"),
+            ActionType::Logout => String::from("Logout"),
+            ActionType::Preview => String::from("Preview"),
+            ActionType::Archive => String::from("Archive"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Log {
+    pub id: i32,
+    pub action: String,
+    /// RFC 3339 in UTC, see `log_date`
+    pub date: String,
+    pub path: Option<String>,
+    pub destination: Option<String>,
+    pub bytes: Option<i64>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+}
+
+impl Log {
+    /// Entry sent to the client, with the names of the user and of the owner
+    pub fn item(self, user: String, owner: String) -> LogItem {
+        LogItem {
+            id: self.id,
+            action: self.action,
+            user,
+            owner,
+            date: self.date,
+            path: self.path,
+            destination: self.destination,
+            bytes: self.bytes,
+            ip: self.ip,
+            user_agent: self.user_agent,
+            success: self.success,
         }
     }
 }
+
+/// Entry to log, the details are added by the handler when it knows them:
+/// `NewLog::new(user_id, ActionType::Move).request(&req).path(from).destination(to)`
+#[derive(Debug, Clone)]
+pub struct NewLog {
+    pub user_id: i32,
+    /// Owner of the file, the user who acted may only have a permission on it
+    pub owner_id: i32,
+    pub action: ActionType,
+    /// Relative to the home of the owner
+    pub path: Option<String>,
+    /// Target of a move, a copy or an extraction
+    pub destination: Option<String>,
+    pub bytes: Option<u64>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+}
+
+impl NewLog {
+    pub fn new(user_id: i32, action: ActionType) -> Self {
+        Self {
+            user_id,
+            owner_id: user_id,
+            action,
+            path: None,
+            destination: None,
+            bytes: None,
+            ip: None,
+            user_agent: None,
+            success: true,
+        }
+    }
+
+    pub fn owner(mut self, owner_id: i32) -> Self {
+        self.owner_id = owner_id;
+        self
+    }
+
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = Some(path.trim_matches('/').to_string()).filter(|e| !e.is_empty());
+        self
+    }
+
+    pub fn destination(mut self, path: &str) -> Self {
+        self.destination = Some(path.trim_matches('/').to_string()).filter(|e| !e.is_empty());
+        self
+    }
+
+    pub fn bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    /// IP and browser of the client
+    pub fn request(mut self, req: &HttpRequest) -> Self {
+        self.ip = Some(ip_of(req)).filter(|e| !e.is_empty());
+        self.user_agent = req
+            .headers()
+            .get("User-Agent")
+            .and_then(|e| e.to_str().ok())
+            .map(String::from);
+        self
+    }
+
+    pub fn success(mut self, success: bool) -> Self {
+        self.success = success;
+        self
+    }
+}
+
+/// Filters of a search in the log, the empty ones are ignored
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    /// Actions of this user or on their files
+    pub user_id: Option<i32>,
+    pub action: Option<String>,
+    /// Dates are compared as text, `2021-06` is the whole month
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// The file or anything in the folder
+    pub path: Option<String>,
+    /// Cursor: only the entries older than this id
+    pub before: Option<i32>,
+    pub limit: i64,
+}
+
+/// Every date has the same length, so the text order is the time order
+pub fn log_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
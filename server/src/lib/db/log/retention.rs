@@ -0,0 +1,152 @@
+use crate::lib::config::MAX_DAYS;
+use crate::lib::db::log::delete::delete_logs_until;
+use crate::lib::db::log::get::{get_logs_range, last_id_before, last_id_over};
+use crate::lib::db::log::model::log_date;
+use actix_web::web;
+use chrono::{Duration, Utc};
+use datagn::DatabasePool;
+use flate2::write::GzEncoder;
+use logger::{error, info};
+use std::fs::File;
+use std::io::Write;
+
+/// Folder of the expired entries, one gzipped JSON Lines file per rotation
+pub const LOG_ARCHIVE_FOLDER: &str = "./archive/logs";
+
+/// Interval between two rotations of the log
+const ROTATE_INTERVAL: u64 = 60 * 60;
+/// Entries read from the database at once
+const BATCH: i64 = 1000;
+
+/// A value of 0 disables the rule
+#[derive(Debug, Clone, Copy)]
+pub struct LogRetention {
+    /// Age of the oldest entry kept
+    pub days: i64,
+    /// Number of entries kept in the table
+    pub max_rows: i64,
+}
+
+/// Last entry which breaks a rule, every entry up to it is expired
+async fn last_expired(database: &mut DatabasePool, retention: LogRetention) -> Option<i32> {
+    let by_age = if retention.days > 0 {
+        last_id_before(
+            database,
+            log_date(
+                Utc::now()
+                    .checked_sub_signed(Duration::days(retention.days.min(MAX_DAYS)))
+                    .unwrap_or_else(Utc::now),
+            ),
+        )
+        .await
+    } else {
+        None
+    };
+    let by_rows = if retention.max_rows > 0 {
+        last_id_over(database, retention.max_rows).await
+    } else {
+        None
+    };
+    by_age.max(by_rows)
+}
+
+/// Last id of the entries in an archive, from its name `log-{id}-{date}.jsonl.gz`
+pub fn archive_id(name: &str) -> Option<i32> {
+    name.strip_prefix("log-")?.split('-').next()?.parse().ok()
+}
+
+/// Writes the expired entries to a file of `LOG_ARCHIVE_FOLDER`, then removes them from the
+/// table. Nothing is removed if the file can't be written. Returns the number of entries moved.
+pub async fn rotate(database: &mut DatabasePool, retention: LogRetention) -> usize {
+    let until = match last_expired(database, retention).await {
+        Some(e) => e,
+        None => return 0,
+    };
+    let name = format!(
+        "log-{}-{}.jsonl.gz",
+        until,
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    );
+    // The file only gets its name once complete, a partial file is never listed
+    let part = format!("{}/{}.part", LOG_ARCHIVE_FOLDER, name);
+    let opened = {
+        let part = part.clone();
+        web::block(move || {
+            std::fs::create_dir_all(LOG_ARCHIVE_FOLDER)?;
+            File::create(part).map(|e| GzEncoder::new(e, flate2::Compression::default()))
+        })
+        .await
+    };
+    let mut encoder = match opened {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Can't create the archive of the log : {:?}", e));
+            }
+            return 0;
+        }
+    };
+
+    let (mut after, mut count) = (0, 0);
+    loop {
+        let logs = get_logs_range(database, after, until, BATCH).await;
+        if logs.is_empty() {
+            break;
+        }
+        let mut lines = String::new();
+        for (log, user, owner) in logs {
+            after = log.id;
+            count += 1;
+            lines.push_str(&serde_json::to_string(&log.item(user, owner)).unwrap_or_default());
+            lines.push('\n');
+        }
+        encoder =
+            match web::block(move || encoder.write_all(lines.as_bytes()).map(|_| encoder)).await {
+                Ok(e) => e,
+                Err(e) => {
+                    if cfg!(feature = "log") {
+                        error(format!("Can't write the archive of the log : {:?}", e));
+                    }
+                    let _ = async_std::fs::remove_file(&part).await;
+                    return 0;
+                }
+            };
+    }
+
+    let target = format!("{}/{}", LOG_ARCHIVE_FOLDER, name);
+    let finished = {
+        let part = part.clone();
+        web::block(move || {
+            encoder.finish()?.sync_all()?;
+            std::fs::rename(part, target)
+        })
+        .await
+    };
+    if let Err(e) = finished {
+        if cfg!(feature = "log") {
+            error(format!("Can't write the archive of the log : {:?}", e));
+        }
+        let _ = async_std::fs::remove_file(&part).await;
+        return 0;
+    }
+    if !delete_logs_until(database, until).await {
+        if cfg!(feature = "log") {
+            error(format!("The log was archived in {} but not cleared", name));
+        }
+        return 0;
+    }
+    count
+}
+
+pub async fn retention_task(mut database: DatabasePool, retention: LogRetention) {
+    if retention.days <= 0 && retention.max_rows <= 0 {
+        return;
+    }
+    loop {
+        let count = rotate(&mut database, retention).await;
+        if count > 0 && cfg!(feature = "log") {
+            info(format!("{} entries of the log were archived", count));
+        }
+        async_std::task::sleep(std::time::Duration::from_secs(ROTATE_INTERVAL)).await;
+    }
+}
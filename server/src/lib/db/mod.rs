@@ -1,9 +1,25 @@
 use datagn::DatabasePool;
 
+pub mod group;
 pub mod log;
+pub mod permission;
+pub mod recovery;
+pub mod session;
+pub mod share;
+pub mod trash;
+pub mod upload;
 pub mod user;
+pub mod version;
 
 pub async fn create_db(database: &mut DatabasePool) {
     log::create::create(database).await;
     user::create::create(database).await;
+    upload::create::create(database).await;
+    trash::create::create(database).await;
+    version::create::create(database).await;
+    share::create::create(database).await;
+    permission::create::create(database).await;
+    group::create::create(database).await;
+    session::create::create(database).await;
+    recovery::create::create(database).await;
 }
@@ -0,0 +1,26 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS Permission (
+        id              INTEGER PRIMARY KEY,
+        owner_id        INTEGER NOT NULL,
+        path            TEXT NOT NULL,
+        grantee_type    TEXT NOT NULL,
+        grantee_id      INTEGER NOT NULL,
+        access          TEXT NOT NULL,
+        date            TEXT NOT NULL
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+}
@@ -0,0 +1,11 @@
+use datagn::DatabasePool;
+
+pub async fn delete_permission(database: &mut DatabasePool, id: i32, owner_id: i32) -> bool {
+    database
+        .execute_with_bind(
+            "DELETE FROM Permission WHERE id=?1 AND owner_id=?2",
+            &[id.to_string(), owner_id.to_string()],
+        )
+        .await
+        .is_ok()
+}
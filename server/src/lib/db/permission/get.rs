@@ -0,0 +1,96 @@
+use crate::lib::db::permission::model::{Access, GranteeType, Permission};
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
+
+/// Grants made by a user, with the name of the user or of the group which receives them
+pub async fn get_permissions_of_owner(
+    database: &mut DatabasePool,
+    owner_id: i32,
+) -> Vec<(Permission, String)> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT Permission.id, owner_id, path, grantee_type, grantee_id, access, Permission.date, COALESCE(User.name, UserGroup.name) AS grantee FROM Permission LEFT JOIN User ON grantee_type = 'user' AND User.id = grantee_id LEFT JOIN UserGroup ON grantee_type = 'group' AND UserGroup.id = grantee_id WHERE owner_id=?1 ORDER BY path, Permission.id",
+            &[owner_id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_permissions_of_owner : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut permissions: Vec<(Permission, String)> = Vec::new();
+    for row in query {
+        let access =
+            match Access::from_name(&row.try_get::<String, &str>("access").unwrap_or_default()) {
+                Some(e) => e,
+                None => continue,
+            };
+        permissions.push((
+            Permission {
+                id: row.try_get("id").unwrap_or_default(),
+                owner_id: row.try_get("owner_id").unwrap_or_default(),
+                path: row.try_get("path").unwrap_or_default(),
+                grantee_type: GranteeType::from_name(
+                    &row.try_get::<String, &str>("grantee_type")
+                        .unwrap_or_default(),
+                ),
+                grantee_id: row.try_get("grantee_id").unwrap_or_default(),
+                access,
+                date: row.try_get("date").unwrap_or_default(),
+            },
+            row.try_get("grantee").unwrap_or_default(),
+        ));
+    }
+    permissions
+}
+
+/// Grants received by a user or by one of their groups, with the name of the owner of the folder
+pub async fn get_permissions_for_user(
+    database: &mut DatabasePool,
+    user_id: i32,
+) -> Vec<(Permission, String)> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT Permission.id, owner_id, path, grantee_type, grantee_id, access, date, User.name AS owner FROM Permission JOIN User ON User.id = owner_id WHERE (grantee_type='user' AND grantee_id=?1) OR (grantee_type='group' AND grantee_id IN (SELECT group_id FROM GroupMember WHERE user_id=?1)) ORDER BY Permission.id",
+            &[user_id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_permissions_for_user : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut permissions: Vec<(Permission, String)> = Vec::new();
+    for row in query {
+        let access =
+            match Access::from_name(&row.try_get::<String, &str>("access").unwrap_or_default()) {
+                Some(e) => e,
+                None => continue,
+            };
+        permissions.push((
+            Permission {
+                id: row.try_get("id").unwrap_or_default(),
+                owner_id: row.try_get("owner_id").unwrap_or_default(),
+                path: row.try_get("path").unwrap_or_default(),
+                grantee_type: GranteeType::from_name(
+                    &row.try_get::<String, &str>("grantee_type")
+                        .unwrap_or_default(),
+                ),
+                grantee_id: row.try_get("grantee_id").unwrap_or_default(),
+                access,
+                date: row.try_get("date").unwrap_or_default(),
+            },
+            row.try_get("owner").unwrap_or_default(),
+        ));
+    }
+    permissions
+}
@@ -0,0 +1,19 @@
+use crate::lib::db::permission::model::Permission;
+use datagn::DatabasePool;
+
+pub async fn insert(database: &mut DatabasePool, permission: &Permission) -> bool {
+    database
+        .execute_with_bind(
+            "INSERT INTO Permission (owner_id,path,grantee_type,grantee_id,access,date) VALUES(?1, ?2, ?3, ?4, ?5, ?6)",
+            &[
+                permission.owner_id.to_string(),
+                permission.path.clone(),
+                permission.grantee_type.format(),
+                permission.grantee_id.to_string(),
+                permission.access.format(),
+                permission.date.clone(),
+            ],
+        )
+        .await
+        .is_ok()
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub enum Access {
+    /// The folder can be listed and downloaded
+    Read,
+    /// Files can also be uploaded, moved and deleted
+    Write,
+}
+
+impl Access {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "read" => Some(Access::Read),
+            "write" => Some(Access::Write),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            Access::Read => String::from("read"),
+            Access::Write => String::from("write"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum GranteeType {
+    User,
+    /// Every member of the group has the access
+    Group,
+}
+
+impl GranteeType {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "group" => GranteeType::Group,
+            _ => GranteeType::User,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            GranteeType::User => String::from("user"),
+            GranteeType::Group => String::from("group"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Permission {
+    pub id: i32,
+    pub owner_id: i32,
+    /// Shared folder, relative to the home of the owner
+    pub path: String,
+    pub grantee_type: GranteeType,
+    pub grantee_id: i32,
+    pub access: Access,
+    pub date: String,
+}
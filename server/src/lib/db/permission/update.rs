@@ -0,0 +1,27 @@
+use crate::lib::db::permission::model::Access;
+use datagn::DatabasePool;
+
+pub async fn set_access(database: &mut DatabasePool, id: i32, access: Access) -> bool {
+    database
+        .execute_with_bind(
+            "UPDATE Permission SET access=?2 WHERE id=?1",
+            &[id.to_string(), access.format()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Follows a folder moved by its owner, the grants of the folder and of its content are kept
+pub async fn move_permissions(
+    database: &mut DatabasePool,
+    owner_id: i32,
+    from: String,
+    to: String,
+) {
+    let _ = database
+        .execute_with_bind(
+            "UPDATE Permission SET path = ?3 || substr(path, length(?2) + 1) WHERE owner_id=?1 AND (path=?2 OR substr(path, 1, length(?2) + 1) = ?2 || '/')",
+            &[owner_id.to_string(), from, to],
+        )
+        .await;
+}
@@ -0,0 +1,22 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS RecoveryCode (
+        id              INTEGER PRIMARY KEY,
+        user_id         INTEGER NOT NULL,
+        code_hash       TEXT NOT NULL
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+}
@@ -0,0 +1,24 @@
+use crate::lib::db::recovery::get::get_code_id;
+use datagn::DatabasePool;
+
+pub async fn delete_codes(database: &mut DatabasePool, user_id: i32) -> bool {
+    database
+        .execute_with_bind(
+            "DELETE FROM RecoveryCode WHERE user_id=?1",
+            &[user_id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Removes the code if the user has it, a recovery code only works once
+pub async fn use_code(database: &mut DatabasePool, user_id: i32, code: &str) -> bool {
+    let id = match get_code_id(database, user_id, code).await {
+        Some(e) => e,
+        None => return false,
+    };
+    database
+        .execute_with_bind("DELETE FROM RecoveryCode WHERE id=?1", &[id.to_string()])
+        .await
+        .is_ok()
+}
@@ -0,0 +1,16 @@
+use crate::lib::db::session::model::hash_token;
+use datagn::DatabasePool;
+use sqlx::Row;
+
+pub async fn get_code_id(database: &mut DatabasePool, user_id: i32, code: &str) -> Option<i32> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT id FROM RecoveryCode WHERE user_id=?1 AND code_hash=?2",
+            &[user_id.to_string(), hash_token(code)],
+        )
+        .await
+    {
+        Ok(row) => row.try_get("id").ok(),
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,23 @@
+use crate::lib::db::recovery::delete::delete_codes;
+use crate::lib::db::session::model::hash_token;
+use datagn::DatabasePool;
+
+/// Replaces the recovery codes of the user
+pub async fn replace_codes(database: &mut DatabasePool, user_id: i32, codes: &[String]) -> bool {
+    if !delete_codes(database, user_id).await {
+        return false;
+    }
+    for code in codes {
+        if database
+            .execute_with_bind(
+                "INSERT INTO RecoveryCode (user_id,code_hash) VALUES(?1, ?2)",
+                &[user_id.to_string(), hash_token(code)],
+            )
+            .await
+            .is_err()
+        {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,27 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS Session (
+        id              INTEGER PRIMARY KEY,
+        user_id         INTEGER NOT NULL,
+        token_hash      TEXT NOT NULL,
+        created         TEXT NOT NULL,
+        last_seen       TEXT NOT NULL,
+        expire          TEXT NOT NULL,
+        user_agent      TEXT,
+        ip              TEXT
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+}
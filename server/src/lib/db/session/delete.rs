@@ -0,0 +1,33 @@
+use crate::lib::db::session::get::get_sessions_of_user;
+use datagn::DatabasePool;
+
+/// None on a database error, false if the user has no such session
+pub async fn delete_session(database: &mut DatabasePool, id: i32, user_id: i32) -> Option<bool> {
+    database
+        .execute_with_bind(
+            "DELETE FROM Session WHERE id=?1 AND user_id=?2",
+            &[id.to_string(), user_id.to_string()],
+        )
+        .await
+        .ok()
+        .map(|e| e.rows_affected() > 0)
+}
+
+/// Logs the user out everywhere
+pub async fn delete_sessions_of_user(database: &mut DatabasePool, user_id: i32) -> bool {
+    database
+        .execute_with_bind(
+            "DELETE FROM Session WHERE user_id=?1",
+            &[user_id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+pub async fn delete_expired_sessions(database: &mut DatabasePool, user_id: i32) {
+    for session in get_sessions_of_user(database, user_id).await {
+        if session.is_expired() {
+            delete_session(database, session.id, user_id).await;
+        }
+    }
+}
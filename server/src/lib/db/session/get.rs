@@ -0,0 +1,90 @@
+use crate::lib::db::session::model::{hash_token, Session};
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
+
+pub async fn get_session_by_token(database: &mut DatabasePool, token: &str) -> Option<Session> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT id, user_id, token_hash, created, last_seen, expire, user_agent, ip FROM Session WHERE token_hash=?1",
+            &[hash_token(token)],
+        )
+        .await
+    {
+        Ok(row) => Some(Session {
+            id: row.try_get("id").ok()?,
+            user_id: row.try_get("user_id").ok()?,
+            token_hash: row.try_get("token_hash").ok()?,
+            created: row.try_get("created").unwrap_or_default(),
+            last_seen: row.try_get("last_seen").unwrap_or_default(),
+            expire: row.try_get("expire").unwrap_or_default(),
+            user_agent: row.try_get("user_agent").unwrap_or_default(),
+            ip: row.try_get("ip").unwrap_or_default(),
+        }),
+        Err(_) => None,
+    }
+}
+
+/// The sessions used last first
+pub async fn get_sessions_of_user(database: &mut DatabasePool, user_id: i32) -> Vec<Session> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT id, user_id, token_hash, created, last_seen, expire, user_agent, ip FROM Session WHERE user_id=?1 ORDER BY last_seen DESC",
+            &[user_id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_sessions_of_user : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut sessions: Vec<Session> = Vec::new();
+    for row in query {
+        sessions.push(Session {
+            id: row.try_get("id").unwrap_or_default(),
+            user_id: row.try_get("user_id").unwrap_or_default(),
+            token_hash: row.try_get("token_hash").unwrap_or_default(),
+            created: row.try_get("created").unwrap_or_default(),
+            last_seen: row.try_get("last_seen").unwrap_or_default(),
+            expire: row.try_get("expire").unwrap_or_default(),
+            user_agent: row.try_get("user_agent").unwrap_or_default(),
+            ip: row.try_get("ip").unwrap_or_default(),
+        });
+    }
+    sessions
+}
+
+pub async fn get_all_sessions(database: &mut DatabasePool) -> Vec<Session> {
+    let query = match database
+        .execute_and_fetch_all(
+            "SELECT id, user_id, token_hash, created, last_seen, expire, user_agent, ip FROM Session",
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_all_sessions : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut sessions: Vec<Session> = Vec::new();
+    for row in query {
+        sessions.push(Session {
+            id: row.try_get("id").unwrap_or_default(),
+            user_id: row.try_get("user_id").unwrap_or_default(),
+            token_hash: row.try_get("token_hash").unwrap_or_default(),
+            created: row.try_get("created").unwrap_or_default(),
+            last_seen: row.try_get("last_seen").unwrap_or_default(),
+            expire: row.try_get("expire").unwrap_or_default(),
+            user_agent: row.try_get("user_agent").unwrap_or_default(),
+            ip: row.try_get("ip").unwrap_or_default(),
+        });
+    }
+    sessions
+}
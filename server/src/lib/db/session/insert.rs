@@ -0,0 +1,20 @@
+use crate::lib::db::session::model::Session;
+use datagn::DatabasePool;
+
+pub async fn insert(database: &mut DatabasePool, session: &Session) -> bool {
+    database
+        .execute_with_bind(
+            "INSERT INTO Session (user_id,token_hash,created,last_seen,expire,user_agent,ip) VALUES(?1, ?2, ?3, ?4, ?5, NULLIF(?6, ''), NULLIF(?7, ''))",
+            &[
+                session.user_id.to_string(),
+                session.token_hash.clone(),
+                session.created.clone(),
+                session.last_seen.clone(),
+                session.expire.clone(),
+                session.user_agent.clone().unwrap_or_default(),
+                session.ip.clone().unwrap_or_default(),
+            ],
+        )
+        .await
+        .is_ok()
+}
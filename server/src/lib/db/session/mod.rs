@@ -0,0 +1,7 @@
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod insert;
+pub mod model;
+pub mod purge;
+pub mod update;
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use whirlpool::{Digest, Whirlpool};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Session {
+    pub id: i32,
+    pub user_id: i32,
+    /// Only the hash of the token is stored
+    pub token_hash: String,
+    /// RFC 3339
+    pub created: String,
+    pub last_seen: String,
+    pub expire: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl Session {
+    pub fn is_expired(&self) -> bool {
+        DateTime::parse_from_rfc3339(&self.expire)
+            .map(|e| e < Utc::now())
+            .unwrap_or(true)
+    }
+
+    /// The last use is saved once a minute at most
+    pub fn needs_touch(&self) -> bool {
+        DateTime::parse_from_rfc3339(&self.last_seen)
+            .map(|e| Utc::now().signed_duration_since(e).num_seconds() >= 60)
+            .unwrap_or(true)
+    }
+}
+
+/// The tokens are long and random, a digest without salt is enough for them
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Whirlpool::new().chain(token.as_bytes()).finalize())
+}
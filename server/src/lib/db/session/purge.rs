@@ -0,0 +1,26 @@
+use crate::lib::db::session::delete::delete_session;
+use crate::lib::db::session::get::get_all_sessions;
+use datagn::DatabasePool;
+
+/// Interval between two purges of the sessions
+const PURGE_INTERVAL: u64 = 60 * 60;
+
+/// Removes the expired sessions of every user, a login only cleans the sessions of its own user
+pub async fn purge(database: &mut DatabasePool) -> usize {
+    let mut purged = 0;
+    for session in get_all_sessions(database).await {
+        if session.is_expired()
+            && delete_session(database, session.id, session.user_id).await == Some(true)
+        {
+            purged += 1;
+        }
+    }
+    purged
+}
+
+pub async fn purge_task(mut database: DatabasePool) {
+    loop {
+        purge(&mut database).await;
+        async_std::task::sleep(std::time::Duration::from_secs(PURGE_INTERVAL)).await;
+    }
+}
@@ -0,0 +1,11 @@
+use datagn::DatabasePool;
+
+pub async fn touch(database: &mut DatabasePool, id: i32, date: String) -> bool {
+    database
+        .execute_with_bind(
+            "UPDATE Session SET last_seen=?1 WHERE id=?2",
+            &[date, id.to_string()],
+        )
+        .await
+        .is_ok()
+}
@@ -0,0 +1,30 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS Share (
+        id              INTEGER PRIMARY KEY,
+        slug            TEXT NOT NULL,
+        user_id         INTEGER NOT NULL,
+        path            TEXT NOT NULL,
+        kind            TEXT NOT NULL,
+        password        TEXT,
+        expire          TEXT,
+        max_downloads   INTEGER,
+        downloads       INTEGER NOT NULL,
+        max_size        INTEGER,
+        date            TEXT NOT NULL
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+}
@@ -0,0 +1,11 @@
+use datagn::DatabasePool;
+
+pub async fn delete_share(database: &mut DatabasePool, slug: String, user_id: i32) -> bool {
+    database
+        .execute_with_bind(
+            "DELETE FROM Share WHERE slug=?1 AND user_id=?2",
+            &[slug, user_id.to_string()],
+        )
+        .await
+        .is_ok()
+}
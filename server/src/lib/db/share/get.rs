@@ -0,0 +1,62 @@
+use crate::lib::db::share::model::{Share, ShareKind};
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
+
+pub async fn get_share(database: &mut DatabasePool, slug: String) -> Option<Share> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT slug, user_id, path, kind, password, expire, max_downloads, downloads, max_size, date FROM Share WHERE slug=?1",
+            &[slug],
+        )
+        .await
+    {
+        Ok(row) => Some(Share {
+            slug: row.try_get("slug").ok()?,
+            user_id: row.try_get("user_id").ok()?,
+            path: row.try_get("path").ok()?,
+            kind: ShareKind::from_name(&row.try_get::<String, &str>("kind").unwrap_or_default()),
+            password: row.try_get("password").unwrap_or_default(),
+            expire: row.try_get("expire").unwrap_or_default(),
+            max_downloads: row.try_get("max_downloads").unwrap_or_default(),
+            downloads: row.try_get("downloads").unwrap_or_default(),
+            max_size: row.try_get("max_size").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        }),
+        Err(_) => None,
+    }
+}
+
+pub async fn get_shares_of_user(database: &mut DatabasePool, user_id: i32) -> Vec<Share> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT slug, user_id, path, kind, password, expire, max_downloads, downloads, max_size, date FROM Share WHERE user_id=?1 ORDER BY date DESC",
+            &[user_id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_shares_of_user : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut shares: Vec<Share> = Vec::new();
+    for row in query {
+        shares.push(Share {
+            slug: row.try_get("slug").unwrap_or_default(),
+            user_id: row.try_get("user_id").unwrap_or_default(),
+            path: row.try_get("path").unwrap_or_default(),
+            kind: ShareKind::from_name(&row.try_get::<String, &str>("kind").unwrap_or_default()),
+            password: row.try_get("password").unwrap_or_default(),
+            expire: row.try_get("expire").unwrap_or_default(),
+            max_downloads: row.try_get("max_downloads").unwrap_or_default(),
+            downloads: row.try_get("downloads").unwrap_or_default(),
+            max_size: row.try_get("max_size").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        });
+    }
+    shares
+}
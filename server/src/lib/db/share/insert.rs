@@ -0,0 +1,21 @@
+use crate::lib::db::share::model::Share;
+use datagn::DatabasePool;
+
+pub async fn insert(database: &mut DatabasePool, share: &Share) -> bool {
+    database
+        .execute_with_bind(
+            "INSERT INTO Share (slug,user_id,path,kind,password,expire,max_downloads,downloads,max_size,date) VALUES(?1, ?2, ?3, ?4, NULLIF(?5, ''), NULLIF(?6, ''), NULLIF(?7, ''), 0, NULLIF(?8, ''), ?9)",
+            &[
+                share.slug.clone(),
+                share.user_id.to_string(),
+                share.path.clone(),
+                share.kind.format(),
+                share.password.clone().unwrap_or_default(),
+                share.expire.clone().unwrap_or_default(),
+                share.max_downloads.map(|e| e.to_string()).unwrap_or_default(),
+                share.date.clone(),
+            ],
+        )
+        .await
+        .is_ok()
+}
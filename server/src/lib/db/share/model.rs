@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum ShareKind {
+    /// The item can be listed and downloaded
+    Download,
+    /// Files can only be sent into the folder (drop box)
+    Upload,
+}
+
+impl ShareKind {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "upload" => ShareKind::Upload,
+            _ => ShareKind::Download,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            ShareKind::Download => String::from("download"),
+            ShareKind::Upload => String::from("upload"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Share {
+    pub slug: String,
+    pub user_id: i32,
+    /// Shared file or folder, relative to the home of the owner
+    pub path: String,
+    pub kind: ShareKind,
+    /// Hash of the password, none if the link is open
+    pub password: Option<String>,
+    /// RFC 3339
+    pub expire: Option<String>,
+    pub max_downloads: Option<i64>,
+    pub downloads: i64,
+    /// Maximum size of a file sent to a drop box
+    pub max_size: Option<i64>,
+    pub date: String,
+}
+
+impl Share {
+    pub fn is_expired(&self) -> bool {
+        match &self.expire {
+            Some(e) => DateTime::parse_from_rfc3339(e)
+                .map(|e| e < Utc::now())
+                .unwrap_or(true),
+            None => false,
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        if self.kind == ShareKind::Upload {
+            return false;
+        }
+        match self.max_downloads {
+            Some(e) => self.downloads >= e,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use datagn::DatabasePool;
+
+/// Counts a download, only while the limit is not reached. False if it is, the download must
+/// not be served.
+pub async fn add_download(database: &mut DatabasePool, slug: String) -> bool {
+    match database
+        .execute_with_bind(
+            "UPDATE Share SET downloads = downloads + 1 WHERE slug=?1 AND (max_downloads IS NULL OR downloads < max_downloads)",
+            &[slug],
+        )
+        .await
+    {
+        Ok(e) => e.rows_affected() > 0,
+        Err(_) => false,
+    }
+}
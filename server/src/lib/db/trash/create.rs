@@ -0,0 +1,24 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS Trash (
+        id              INTEGER PRIMARY KEY,
+        uid             TEXT NOT NULL,
+        user_id         INTEGER NOT NULL,
+        path            TEXT NOT NULL,
+        date            TEXT NOT NULL
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+}
@@ -0,0 +1,8 @@
+use datagn::DatabasePool;
+
+pub async fn delete_trash(database: &mut DatabasePool, uid: String) -> bool {
+    database
+        .execute_with_bind("DELETE FROM Trash WHERE uid=?1", &[uid])
+        .await
+        .is_ok()
+}
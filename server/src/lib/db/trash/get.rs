@@ -0,0 +1,75 @@
+use crate::lib::db::trash::model::Trash;
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
+
+pub async fn get_trash(database: &mut DatabasePool, uid: String, user_id: i32) -> Option<Trash> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT uid, user_id, path, date FROM Trash WHERE uid=?1 AND user_id=?2",
+            &[uid, user_id.to_string()],
+        )
+        .await
+    {
+        Ok(row) => Some(Trash {
+            uid: row.try_get("uid").ok()?,
+            user_id: row.try_get("user_id").ok()?,
+            path: row.try_get("path").ok()?,
+            date: row.try_get("date").ok()?,
+        }),
+        Err(_) => None,
+    }
+}
+
+pub async fn get_trash_of_user(database: &mut DatabasePool, user_id: i32) -> Vec<Trash> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT uid, user_id, path, date FROM Trash WHERE user_id=?1 ORDER BY date DESC",
+            &[user_id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_trash_of_user : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut trash: Vec<Trash> = Vec::new();
+    for row in query {
+        trash.push(Trash {
+            uid: row.try_get("uid").unwrap_or_default(),
+            user_id: row.try_get("user_id").unwrap_or_default(),
+            path: row.try_get("path").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        });
+    }
+    trash
+}
+
+pub async fn get_all_trash(database: &mut DatabasePool) -> Vec<Trash> {
+    let query = match database
+        .execute_and_fetch_all("SELECT uid, user_id, path, date FROM Trash")
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_all_trash : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut trash: Vec<Trash> = Vec::new();
+    for row in query {
+        trash.push(Trash {
+            uid: row.try_get("uid").unwrap_or_default(),
+            user_id: row.try_get("user_id").unwrap_or_default(),
+            path: row.try_get("path").unwrap_or_default(),
+            date: row.try_get("date").unwrap_or_default(),
+        });
+    }
+    trash
+}
@@ -0,0 +1,17 @@
+use crate::lib::db::trash::model::Trash;
+use datagn::DatabasePool;
+
+pub async fn insert(database: &mut DatabasePool, trash: &Trash) -> bool {
+    database
+        .execute_with_bind(
+            "INSERT INTO Trash (uid,user_id,path,date) VALUES(?1, ?2, ?3, ?4)",
+            &[
+                trash.uid.clone(),
+                trash.user_id.to_string(),
+                trash.path.clone(),
+                trash.date.clone(),
+            ],
+        )
+        .await
+        .is_ok()
+}
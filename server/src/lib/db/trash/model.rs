@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Trash {
+    pub uid: String,
+    pub user_id: i32,
+    /// Original path, relative to the home of the user
+    pub path: String,
+    /// RFC 3339, so the dates can be compared
+    pub date: String,
+}
+
+impl Trash {
+    pub fn trash_path(&self) -> String {
+        format!("{}/{}", trash_folder(self.user_id), self.uid)
+    }
+}
+
+pub fn trash_folder(user_id: i32) -> String {
+    format!("./trash/{}", user_id)
+}
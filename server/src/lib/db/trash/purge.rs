@@ -0,0 +1,56 @@
+use crate::lib::config::MAX_DAYS;
+use crate::lib::db::trash::delete::delete_trash;
+use crate::lib::db::trash::get::get_all_trash;
+use chrono::{DateTime, Duration, Utc};
+use datagn::DatabasePool;
+use logger::error;
+
+/// Interval between two purges of the trash
+const PURGE_INTERVAL: u64 = 60 * 60;
+
+/// Removes for good every item deleted more than `retention_days` ago
+pub async fn purge(database: &mut DatabasePool, retention_days: i64) -> usize {
+    let limit = match Utc::now().checked_sub_signed(Duration::days(retention_days.min(MAX_DAYS))) {
+        Some(e) => e,
+        None => return 0,
+    };
+    let mut purged = 0;
+    for trash in get_all_trash(database).await {
+        let expired = DateTime::parse_from_rfc3339(&trash.date)
+            .map(|e| e < limit)
+            .unwrap_or(true);
+        if !expired {
+            continue;
+        }
+        let path = trash.trash_path();
+        let removed = match async_std::fs::metadata(&path).await {
+            Ok(e) if e.is_dir() => async_std::fs::remove_dir_all(&path).await,
+            Ok(_) => async_std::fs::remove_file(&path).await,
+            // Already gone, only the row is left
+            Err(_) => Ok(()),
+        };
+        match removed {
+            Ok(_) => {
+                delete_trash(database, trash.uid).await;
+                purged += 1;
+            }
+            Err(e) => {
+                if cfg!(feature = "log") {
+                    error(format!("Can't purge {} : {:?}", path, e));
+                }
+            }
+        }
+    }
+    purged
+}
+
+pub async fn purge_task(mut database: DatabasePool, retention_days: i64) {
+    // A retention of 0 keeps the trash forever
+    if retention_days <= 0 {
+        return;
+    }
+    loop {
+        purge(&mut database, retention_days).await;
+        async_std::task::sleep(std::time::Duration::from_secs(PURGE_INTERVAL)).await;
+    }
+}
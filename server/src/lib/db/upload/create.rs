@@ -0,0 +1,31 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS Upload (
+        id              INTEGER PRIMARY KEY,
+        uid             TEXT NOT NULL,
+        user_id         INTEGER NOT NULL,
+        path            TEXT NOT NULL,
+        name            TEXT NOT NULL,
+        length          INTEGER NOT NULL,
+        date            TEXT
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+
+    // Owner of the folder, the quota of the pending uploads is theirs
+    let _ = database
+        .execute("ALTER TABLE Upload ADD COLUMN owner_id INTEGER")
+        .await;
+}
@@ -0,0 +1,76 @@
+use crate::lib::db::upload::model::Upload;
+use crate::lib::file::quota::Usage;
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
+
+pub async fn get_upload(database: &mut DatabasePool, uid: String, user_id: i32) -> Option<Upload> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT uid, user_id, COALESCE(owner_id, user_id) AS owner_id, path, name, length FROM Upload WHERE uid=?1 AND user_id=?2",
+            &[uid, user_id.to_string()],
+        )
+        .await
+    {
+        Ok(row) => Some(Upload {
+            uid: row.try_get("uid").ok()?,
+            user_id: row.try_get("user_id").ok()?,
+            owner_id: row.try_get("owner_id").ok()?,
+            path: row.try_get("path").ok()?,
+            name: row.try_get("name").ok()?,
+            length: row.try_get("length").ok()?,
+        }),
+        Err(_) => None,
+    }
+}
+
+pub async fn get_all_uploads(database: &mut DatabasePool) -> Vec<Upload> {
+    let query = match database
+        .execute_and_fetch_all(
+            "SELECT uid, user_id, COALESCE(owner_id, user_id) AS owner_id, path, name, length FROM Upload",
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_all_uploads : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut uploads: Vec<Upload> = Vec::new();
+    for row in query {
+        uploads.push(Upload {
+            uid: row.try_get("uid").unwrap_or_default(),
+            user_id: row.try_get("user_id").unwrap_or_default(),
+            owner_id: row.try_get("owner_id").unwrap_or_default(),
+            path: row.try_get("path").unwrap_or_default(),
+            name: row.try_get("name").unwrap_or_default(),
+            length: row.try_get("length").unwrap_or_default(),
+        });
+    }
+    uploads
+}
+
+/// Declared size and number of the unfinished uploads into the folders of the owner, but `except`
+pub async fn pending_usage(database: &mut DatabasePool, owner_id: i32, except: &str) -> Usage {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT COALESCE(SUM(length), 0) AS bytes, COUNT(*) AS files FROM Upload WHERE COALESCE(owner_id, user_id)=?1 AND uid<>?2",
+            &[owner_id.to_string(), except.to_string()],
+        )
+        .await
+    {
+        Ok(row) => Usage {
+            bytes: row.try_get::<i64, _>("bytes").unwrap_or_default().max(0) as u64,
+            files: row.try_get::<i64, _>("files").unwrap_or_default().max(0) as u64,
+        },
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on pending_usage : {:?}", e));
+            }
+            Usage::default()
+        }
+    }
+}
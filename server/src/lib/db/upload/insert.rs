@@ -0,0 +1,22 @@
+use crate::lib::db::upload::model::Upload;
+use chrono::Utc;
+use datagn::DatabasePool;
+
+pub async fn insert(database: &mut DatabasePool, upload: &Upload) -> bool {
+    let date: String = Utc::now().to_rfc2822();
+    database
+        .execute_with_bind(
+            "INSERT INTO Upload (uid,user_id,path,name,length,date,owner_id) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            &[
+                upload.uid.clone(),
+                upload.user_id.to_string(),
+                upload.path.clone(),
+                upload.name.clone(),
+                upload.length.to_string(),
+                date,
+                upload.owner_id.to_string(),
+            ],
+        )
+        .await
+        .is_ok()
+}
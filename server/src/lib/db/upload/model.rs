@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Upload {
+    pub uid: String,
+    pub user_id: i32,
+    /// Owner of the folder, the one who uploads may only have a permission on it
+    pub owner_id: i32,
+    pub path: String,
+    pub name: String,
+    pub length: i64,
+}
+
+impl Upload {
+    pub fn temp_path(&self) -> String {
+        temp_path(&self.uid)
+    }
+}
+
+pub fn temp_path(uid: &str) -> String {
+    format!("./temp/upload/{}", uid)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewUpload {
+    pub name: String,
+    pub length: i64,
+}
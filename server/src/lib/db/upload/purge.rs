@@ -0,0 +1,63 @@
+use crate::lib::config::MAX_DAYS;
+use crate::lib::db::upload::delete::delete_upload;
+use crate::lib::db::upload::get::get_all_uploads;
+use crate::lib::file::upload::UploadLocks;
+use actix_web::web;
+use datagn::DatabasePool;
+use logger::error;
+use std::time::{Duration, SystemTime};
+
+/// Interval between two purges of the uploads
+const PURGE_INTERVAL: u64 = 60 * 60;
+
+/// Removes the uploads which got no chunk for `expire_days`, with their staged data
+pub async fn purge(database: &mut DatabasePool, locks: &UploadLocks, expire_days: i64) -> usize {
+    let age = Duration::from_secs(expire_days.clamp(0, MAX_DAYS) as u64 * 24 * 60 * 60);
+    let limit = match SystemTime::now().checked_sub(age) {
+        Some(e) => e,
+        None => return 0,
+    };
+    let mut purged = 0;
+    for upload in get_all_uploads(database).await {
+        // A chunk is being written
+        let _lock = match locks.lock(&upload.uid) {
+            Some(e) => e,
+            None => continue,
+        };
+        let path = upload.temp_path();
+        match async_std::fs::metadata(&path)
+            .await
+            .and_then(|e| e.modified())
+        {
+            Ok(e) if e >= limit => continue,
+            Ok(_) => {
+                if let Err(e) = async_std::fs::remove_file(&path).await {
+                    if cfg!(feature = "log") {
+                        error(format!("Can't purge {} : {:?}", path, e));
+                    }
+                    continue;
+                }
+            }
+            // Already gone, only the row is left
+            Err(_) => {}
+        }
+        delete_upload(database, upload.uid.clone()).await;
+        purged += 1;
+    }
+    purged
+}
+
+pub async fn purge_task(
+    mut database: DatabasePool,
+    locks: web::Data<UploadLocks>,
+    expire_days: i64,
+) {
+    // 0 keeps the unfinished uploads forever
+    if expire_days <= 0 {
+        return;
+    }
+    loop {
+        purge(&mut database, &locks, expire_days).await;
+        async_std::task::sleep(std::time::Duration::from_secs(PURGE_INTERVAL)).await;
+    }
+}
@@ -1,4 +1,5 @@
 use datagn::DatabasePool;
+use logger::error;
 
 pub async fn create(database: &mut DatabasePool) {
     database
@@ -13,4 +14,33 @@ pub async fn create(database: &mut DatabasePool) {
         )
         .await
         .expect("Error");
+
+    // Columns added after the creation of the table, the error is expected once they exist
+    for column in &[
+        "quota_bytes INTEGER",
+        "quota_files INTEGER",
+        "role TEXT",
+        "disabled INTEGER",
+        "must_reset INTEGER",
+        "last_login TEXT",
+        "totp_secret TEXT",
+        "totp_enabled INTEGER",
+        "totp_step INTEGER",
+    ] {
+        let _ = database
+            .execute(&format!("ALTER TABLE User ADD COLUMN {}", column))
+            .await;
+    }
+    // Two accounts with the same name would share a home
+    if let Err(e) = database
+        .execute("CREATE UNIQUE INDEX IF NOT EXISTS user_name ON User (name)")
+        .await
+    {
+        if cfg!(feature = "log") {
+            error(format!(
+                "Can't make the names of the users unique : {:?}",
+                e
+            ));
+        }
+    }
 }
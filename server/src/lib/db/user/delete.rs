@@ -0,0 +1,25 @@
+use datagn::DatabasePool;
+
+/// Removes the account and everything which belongs to it in the database. The logs are kept.
+pub async fn delete_user(database: &mut DatabasePool, id: i32) -> bool {
+    let id = [id.to_string()];
+    for query in &[
+        "DELETE FROM Share WHERE user_id=?1",
+        "DELETE FROM Permission WHERE owner_id=?1 OR (grantee_type='user' AND grantee_id=?1)",
+        "DELETE FROM GroupMember WHERE user_id=?1",
+        "DELETE FROM Trash WHERE user_id=?1",
+        "DELETE FROM Version WHERE user_id=?1",
+        "DELETE FROM Uploader WHERE user_id=?1",
+        "DELETE FROM Upload WHERE user_id=?1",
+        "DELETE FROM Session WHERE user_id=?1",
+        "DELETE FROM RecoveryCode WHERE user_id=?1",
+    ] {
+        if database.execute_with_bind(query, &id).await.is_err() {
+            return false;
+        }
+    }
+    database
+        .execute_with_bind("DELETE FROM User WHERE id=?1", &id)
+        .await
+        .is_ok()
+}
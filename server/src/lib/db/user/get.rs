@@ -1,74 +1,157 @@
-use crate::lib::db::user::hash_password;
-use crate::lib::db::user::model::User;
+use crate::lib::db::session::get::get_session_by_token;
+use crate::lib::db::session::update::touch;
+use crate::lib::db::user::model::{Role, User};
+use crate::lib::db::user::update::update_password_hash;
+use crate::lib::db::user::{verify_dummy, verify_password, HashCost, Verified};
+use chrono::Utc;
 use datagn::DatabasePool;
 use logger::error;
 use sqlx::Row;
 
-pub async fn _get_users(database: &mut DatabasePool) -> Vec<User> {
-    let response = database
-        .execute_and_fetch_all("SELECT * FROM User")
-        .await
-        .unwrap();
+pub async fn get_users(database: &mut DatabasePool) -> Vec<User> {
+    let response = match database.execute_and_fetch_all("SELECT * FROM User").await {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_users : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
     let mut person_vec: Vec<User> = Vec::new();
 
     for row in response {
-        let name: String = row.try_get("name").expect("Error");
+        let name: String = row.try_get("name").unwrap_or_default();
         person_vec.push(User {
-            id: row.try_get("id").expect("Error"),
+            id: row.try_get("id").unwrap_or_default(),
             name: name.clone(),
-            password: row.try_get("password").expect("Error"),
+            password: row.try_get("password").unwrap_or_default(),
             token: row.try_get("token").unwrap_or_default(),
             email: row.try_get("email").unwrap_or_default(),
             home: Some(format!("./home/{}", name)),
+            quota_bytes: row.try_get("quota_bytes").unwrap_or_default(),
+            quota_files: row.try_get("quota_files").unwrap_or_default(),
+            role: Role::from_name(&row.try_get::<String, &str>("role").unwrap_or_default())
+                .unwrap_or_default(),
+            disabled: row.try_get("disabled").unwrap_or_default(),
+            must_reset: row.try_get("must_reset").unwrap_or_default(),
+            last_login: row.try_get("last_login").unwrap_or_default(),
+            totp_secret: row.try_get("totp_secret").unwrap_or_default(),
+            totp_enabled: row.try_get("totp_enabled").unwrap_or_default(),
+            totp_step: row.try_get("totp_step").unwrap_or_default(),
         });
     }
 
     person_vec
 }
 
+/// User of a session which is still valid, the last use of the session is saved
 pub async fn get_user_by_token(database: &mut DatabasePool, token: String) -> Option<User> {
-    let query = database
-        .execute_and_fetch_all_with_bind("SELECT * FROM User WHERE token = ?1", &[token])
+    let session = get_session_by_token(database, &token).await?;
+    if session.is_expired() {
+        return None;
+    }
+    let user = get_user_by_id(database, session.user_id).await?;
+    if user.disabled {
+        return None;
+    }
+    if session.needs_touch() {
+        touch(database, session.id, Utc::now().to_rfc3339()).await;
+    }
+    Some(user)
+}
+
+pub async fn get_user_by_id(database: &mut DatabasePool, id: i32) -> Option<User> {
+    match database
+        .execute_and_fetch_one_with_bind("SELECT * FROM User WHERE id = ?1", &[id.to_string()])
         .await
-        .expect("Error");
-    let mut user_vec: Vec<User> = Vec::new();
-    for row in query {
-        let name: String = row.try_get(1).expect("Error");
-        user_vec.push(User {
-            id: row.try_get(0).expect("Error"),
-            name: name.clone(),
-            password: row.try_get(2).expect("Error"),
-            token: row.try_get(3).unwrap_or_default(),
-            email: row.try_get(4).unwrap_or_default(),
-            home: Some(format!("./home/{}", name)),
-        });
+    {
+        Ok(row) => {
+            let name: String = row.try_get(1).ok()?;
+            Some(User {
+                id: row.try_get(0).ok()?,
+                name: name.clone(),
+                password: row.try_get(2).ok()?,
+                token: row.try_get(3).unwrap_or_default(),
+                email: row.try_get(4).unwrap_or_default(),
+                home: Some(format!("./home/{}", name)),
+                quota_bytes: row.try_get("quota_bytes").unwrap_or_default(),
+                quota_files: row.try_get("quota_files").unwrap_or_default(),
+                role: Role::from_name(&row.try_get::<String, &str>("role").unwrap_or_default())
+                    .unwrap_or_default(),
+                disabled: row.try_get("disabled").unwrap_or_default(),
+                must_reset: row.try_get("must_reset").unwrap_or_default(),
+                last_login: row.try_get("last_login").unwrap_or_default(),
+                totp_secret: row.try_get("totp_secret").unwrap_or_default(),
+                totp_enabled: row.try_get("totp_enabled").unwrap_or_default(),
+                totp_step: row.try_get("totp_step").unwrap_or_default(),
+            })
+        }
+        Err(_) => None,
     }
+}
 
-    if let Some(e) = user_vec.get(0) {
-        Some(e.clone())
-    } else {
-        None
+pub async fn get_user_by_name(database: &mut DatabasePool, name: String) -> Option<User> {
+    match database
+        .execute_and_fetch_one_with_bind("SELECT * FROM User WHERE name = ?1", &[name])
+        .await
+    {
+        Ok(row) => {
+            let name: String = row.try_get(1).ok()?;
+            Some(User {
+                id: row.try_get(0).ok()?,
+                name: name.clone(),
+                password: row.try_get(2).ok()?,
+                token: row.try_get(3).unwrap_or_default(),
+                email: row.try_get(4).unwrap_or_default(),
+                home: Some(format!("./home/{}", name)),
+                quota_bytes: row.try_get("quota_bytes").unwrap_or_default(),
+                quota_files: row.try_get("quota_files").unwrap_or_default(),
+                role: Role::from_name(&row.try_get::<String, &str>("role").unwrap_or_default())
+                    .unwrap_or_default(),
+                disabled: row.try_get("disabled").unwrap_or_default(),
+                must_reset: row.try_get("must_reset").unwrap_or_default(),
+                last_login: row.try_get("last_login").unwrap_or_default(),
+                totp_secret: row.try_get("totp_secret").unwrap_or_default(),
+                totp_enabled: row.try_get("totp_enabled").unwrap_or_default(),
+                totp_step: row.try_get("totp_step").unwrap_or_default(),
+            })
+        }
+        Err(_) => None,
     }
 }
 
+/// Checks the password of the user. An old hash is replaced by one with the current costs.
 pub async fn get_id_of_user(
     database: &mut DatabasePool,
     name: String,
     password: String,
+    cost: HashCost,
 ) -> Option<i32> {
-    match database
-        .execute_and_fetch_one_with_bind(
-            "SELECT id FROM User WHERE name=?1 AND password=?2",
-            &[name, hash_password(password)],
-        )
+    let row = match database
+        .execute_and_fetch_one_with_bind("SELECT id, password FROM User WHERE name=?1", &[name])
         .await
     {
-        Ok(e) => Some(e.try_get::<i32, &str>("id").unwrap()),
+        Ok(e) => e,
         Err(e) => {
             if cfg!(feature = "log") {
                 error(format!("Error on get_id_of_user : {:?}", e));
             }
-            None
+            verify_dummy(password, cost).await;
+            return None;
+        }
+    };
+    let id: i32 = row.try_get("id").ok()?;
+    let hash: String = row.try_get("password").ok()?;
+
+    match verify_password(password.clone(), hash, cost).await {
+        Verified::Valid => Some(id),
+        Verified::Outdated => {
+            if !update_password_hash(database, id, password, cost).await && cfg!(feature = "log") {
+                error(format!("Error on upgrade of the password of {}", id));
+            }
+            Some(id)
         }
+        Verified::Invalid => None,
     }
 }
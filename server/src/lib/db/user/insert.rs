@@ -1,4 +1,4 @@
-use super::hash_password;
+use super::{hash_password, HashCost};
 use datagn::DatabasePool;
 
 pub async fn insert_user(
@@ -6,15 +6,29 @@ pub async fn insert_user(
     name: String,
     email: String,
     password: String,
+    cost: HashCost,
 ) -> std::io::Result<usize> {
+    let password = match hash_password(password, cost).await {
+        Some(e) => e,
+        None => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "Can't hash the password",
+            ))
+        }
+    };
     match database
         .execute_with_bind(
-            "INSERT INTO User (name,email, password) VALUES(?1, ?2, ?3)",
-            &[name, email, hash_password(password)],
+            // An id is never given again, the log of a deleted user keeps it
+            "INSERT INTO User (id, name, email, password) VALUES((SELECT COALESCE(MAX(e.id), 0) + 1 FROM (SELECT id FROM User UNION ALL SELECT user_id FROM Log UNION ALL SELECT owner_id FROM Log) AS e), ?1, ?2, ?3)",
+            &[name, email, password],
         )
         .await
     {
         Ok(_) => Ok(usize::from(true)),
-        Err(_) => Ok(usize::from(false)),
+        Err(e) => Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("Can't insert the user : {:?}", e),
+        )),
     }
 }
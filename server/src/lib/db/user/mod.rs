@@ -1,5 +1,6 @@
 pub mod create;
 pub mod create_home;
+pub mod delete;
 pub mod get;
 pub mod insert;
 pub mod model;
@@ -7,11 +8,113 @@ pub mod token;
 pub mod update;
 pub mod valid_session;
 
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use token::generate_token;
 use whirlpool::{Digest, Whirlpool};
 
-pub fn hash_password(password: String) -> String {
+/// Cost of Argon2id, set in the config
+#[derive(Debug, Clone, Copy)]
+pub struct HashCost {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl HashCost {
+    fn params(&self) -> Option<Params> {
+        Params::new(self.memory_kib, self.iterations, self.parallelism, None).ok()
+    }
+}
+
+pub enum Verified {
+    Invalid,
+    Valid,
+    /// The password is right, but its hash must be replaced by one with the current costs
+    Outdated,
+}
+
+/// Unsalted digest of the first versions, only read to upgrade the accounts created with it
+fn legacy_hash(password: &str) -> String {
     format!(
         "{:x}",
         Whirlpool::new().chain(password.as_bytes()).finalize()
     )
 }
+
+/// Hash checked when the user doesn't exist, made once with the costs of the config
+static DUMMY_HASH: Mutex<Option<String>> = Mutex::new(None);
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn hash(password: &str, cost: HashCost) -> Option<String> {
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, cost.params()?);
+    let salt = SaltString::generate(&mut OsRng);
+    argon2
+        .hash_password(password.as_bytes(), &salt)
+        .ok()
+        .map(|e| e.to_string())
+}
+
+fn verify(password: &str, hash: &str, cost: HashCost) -> Verified {
+    let parsed = match PasswordHash::new(hash) {
+        Ok(e) => e,
+        Err(_) if constant_time_eq(legacy_hash(password).as_bytes(), hash.as_bytes()) => {
+            return Verified::Outdated
+        }
+        Err(_) => return Verified::Invalid,
+    };
+    // The comparison of the hashes is done in constant time by argon2
+    if Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_err()
+    {
+        return Verified::Invalid;
+    }
+    let current = Params::try_from(&parsed)
+        .map(|e| (e.m_cost(), e.t_cost(), e.p_cost()))
+        .ok();
+    if parsed.algorithm == Algorithm::Argon2id.ident()
+        && current == Some((cost.memory_kib, cost.iterations, cost.parallelism))
+    {
+        Verified::Valid
+    } else {
+        Verified::Outdated
+    }
+}
+
+/// Argon2id hash with a new salt, in the PHC string format. It is computed out of the server
+/// threads since it is slow on purpose.
+pub async fn hash_password(password: String, cost: HashCost) -> Option<String> {
+    actix_web::web::block(move || hash(&password, cost).ok_or(()))
+        .await
+        .ok()
+}
+
+pub async fn verify_password(password: String, hash: String, cost: HashCost) -> Verified {
+    actix_web::web::block(move || Ok::<Verified, ()>(verify(&password, &hash, cost)))
+        .await
+        .unwrap_or(Verified::Invalid)
+}
+
+/// Takes as long as checking a real password, so an unknown name can't be told by the time of
+/// the answer. Always invalid.
+pub async fn verify_dummy(password: String, cost: HashCost) -> Verified {
+    actix_web::web::block(move || {
+        let mut dummy = DUMMY_HASH.lock().map_err(|_| ())?;
+        if dummy.is_none() {
+            *dummy = hash(&generate_token(), cost);
+        }
+        let hash = dummy.clone().ok_or(())?;
+        drop(dummy);
+        verify(&password, &hash, cost);
+        Ok::<Verified, ()>(Verified::Invalid)
+    })
+    .await
+    .unwrap_or(Verified::Invalid)
+}
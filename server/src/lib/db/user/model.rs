@@ -1,5 +1,35 @@
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub enum Role {
+    User,
+    /// Manages the users, the groups and the instance
+    Admin,
+}
+
+impl Default for Role {
+    fn default() -> Self {
+        Role::User
+    }
+}
+
+impl Role {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "user" => Some(Role::User),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            Role::User => String::from("user"),
+            Role::Admin => String::from("admin"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct User {
     pub id: Option<i32>,
@@ -8,4 +38,28 @@ pub struct User {
     pub email: Option<String>,
     pub password: String,
     pub home: Option<String>,
+    /// Overrides the quota of the config, a negative value means unlimited
+    pub quota_bytes: Option<i64>,
+    pub quota_files: Option<i64>,
+    /// Never read from a request, it is only changed by an admin
+    #[serde(skip_deserializing)]
+    pub role: Role,
+    /// A disabled account can't log in
+    #[serde(skip_deserializing)]
+    pub disabled: bool,
+    /// The password was reset by an admin, the user must choose a new one before logging in
+    #[serde(skip_deserializing)]
+    pub must_reset: bool,
+    /// RFC 3339
+    #[serde(skip_deserializing)]
+    pub last_login: Option<String>,
+    /// Base32 secret of the authenticator, it is never sent back
+    #[serde(skip)]
+    pub totp_secret: Option<String>,
+    /// The secret was confirmed with a code, it is asked at login
+    #[serde(skip_deserializing)]
+    pub totp_enabled: bool,
+    /// Last step used, a code can't be used twice
+    #[serde(skip)]
+    pub totp_step: Option<i64>,
 }
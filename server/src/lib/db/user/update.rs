@@ -1,10 +1,176 @@
+use crate::lib::db::recovery::delete::delete_codes;
+use crate::lib::db::session::delete::delete_sessions_of_user;
+use crate::lib::db::user::model::Role;
+use crate::lib::db::user::{hash_password, HashCost};
 use datagn::DatabasePool;
 
-pub async fn update_token(database: &mut DatabasePool, token: String, id: i32) -> bool {
+pub async fn set_last_login(database: &mut DatabasePool, id: i32, date: String) -> bool {
     database
         .execute_with_bind(
-            "UPDATE User SET token=?1 WHERE id=?2",
-            &[token, id.to_string()],
+            "UPDATE User SET last_login=?1 WHERE id=?2",
+            &[date, id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Disabling an account also logs it out
+pub async fn set_disabled(database: &mut DatabasePool, id: i32, disabled: bool) -> bool {
+    if disabled && !delete_sessions_of_user(database, id).await {
+        return false;
+    }
+    database
+        .execute_with_bind(
+            "UPDATE User SET disabled=?1 WHERE id=?2",
+            &[i32::from(disabled).to_string(), id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+pub async fn set_password(
+    database: &mut DatabasePool,
+    id: i32,
+    password: String,
+    cost: HashCost,
+) -> bool {
+    let password = match hash_password(password, cost).await {
+        Some(e) => e,
+        None => return false,
+    };
+    database
+        .execute_with_bind(
+            "UPDATE User SET password=?1, must_reset=0 WHERE id=?2",
+            &[password, id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Replaces the password by a temporary one, which must be changed at the next login
+pub async fn reset_password(
+    database: &mut DatabasePool,
+    id: i32,
+    password: String,
+    cost: HashCost,
+) -> bool {
+    let password = match hash_password(password, cost).await {
+        Some(e) => e,
+        None => return false,
+    };
+    if !delete_sessions_of_user(database, id).await {
+        return false;
+    }
+    database
+        .execute_with_bind(
+            "UPDATE User SET password=?1, must_reset=1 WHERE id=?2",
+            &[password, id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Hashes the same password again, without changing anything else
+pub async fn update_password_hash(
+    database: &mut DatabasePool,
+    id: i32,
+    password: String,
+    cost: HashCost,
+) -> bool {
+    let password = match hash_password(password, cost).await {
+        Some(e) => e,
+        None => return false,
+    };
+    database
+        .execute_with_bind(
+            "UPDATE User SET password=?1 WHERE id=?2",
+            &[password, id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+pub async fn set_role(database: &mut DatabasePool, id: i32, role: Role) -> bool {
+    database
+        .execute_with_bind(
+            "UPDATE User SET role=?1 WHERE id=?2",
+            &[role.format(), id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Makes the account named in the config an admin, or the first account created if the instance
+/// has no admin yet
+pub async fn ensure_admin(database: &mut DatabasePool, name: Option<String>) {
+    if let Some(name) = name {
+        let _ = database
+            .execute_with_bind("UPDATE User SET role='admin' WHERE name=?1", &[name])
+            .await;
+    }
+    let _ = database
+        .execute(
+            "UPDATE User SET role='admin' WHERE id = (SELECT MIN(id) FROM User) AND NOT EXISTS (SELECT 1 FROM User WHERE role='admin')",
+        )
+        .await;
+}
+
+/// Makes the account an admin if it is the only one of the instance
+pub async fn promote_first_user(database: &mut DatabasePool, id: i32) -> bool {
+    database
+        .execute_with_bind(
+            "UPDATE User SET role='admin' WHERE id=?1 AND (SELECT COUNT(*) FROM User)=1",
+            &[id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Saves a new secret, it is only used once confirmed with `enable_totp`
+pub async fn set_totp_secret(database: &mut DatabasePool, id: i32, secret: String) -> bool {
+    database
+        .execute_with_bind(
+            "UPDATE User SET totp_secret=?1, totp_enabled=0, totp_step=NULL WHERE id=?2",
+            &[secret, id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+pub async fn enable_totp(database: &mut DatabasePool, id: i32, step: i64) -> bool {
+    database
+        .execute_with_bind(
+            "UPDATE User SET totp_enabled=1, totp_step=?1 WHERE id=?2",
+            &[step.to_string(), id.to_string()],
+        )
+        .await
+        .is_ok()
+}
+
+/// Saves the step of a used code, false if it or a newer one was already used, even by a
+/// login at the same time
+pub async fn set_totp_step(database: &mut DatabasePool, id: i32, step: i64) -> bool {
+    match database
+        .execute_with_bind(
+            "UPDATE User SET totp_step=?1 WHERE id=?2 AND (totp_step IS NULL OR totp_step < ?1)",
+            &[step.to_string(), id.to_string()],
+        )
+        .await
+    {
+        Ok(e) => e.rows_affected() > 0,
+        Err(_) => false,
+    }
+}
+
+/// Removes the secret and the recovery codes
+pub async fn disable_totp(database: &mut DatabasePool, id: i32) -> bool {
+    if !delete_codes(database, id).await {
+        return false;
+    }
+    database
+        .execute_with_bind(
+            "UPDATE User SET totp_secret=NULL, totp_enabled=0, totp_step=NULL WHERE id=?1",
+            &[id.to_string()],
         )
         .await
         .is_ok()
@@ -0,0 +1,44 @@
+use datagn::DatabasePool;
+use logger::error;
+
+pub async fn create(database: &mut DatabasePool) {
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS Version (
+        id              INTEGER PRIMARY KEY,
+        uid             TEXT NOT NULL,
+        user_id         INTEGER NOT NULL,
+        path            TEXT NOT NULL,
+        size            INTEGER NOT NULL,
+        uploader        INTEGER NOT NULL,
+        date            TEXT NOT NULL
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+    // Who wrote the current content of a file, it becomes the uploader of the version
+    match database
+        .execute(
+            "CREATE TABLE IF NOT EXISTS Uploader (
+        user_id         INTEGER NOT NULL,
+        path            TEXT NOT NULL,
+        uploader        INTEGER NOT NULL
+        )",
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(e);
+            }
+        }
+    };
+}
@@ -0,0 +1,8 @@
+use datagn::DatabasePool;
+
+pub async fn delete_version(database: &mut DatabasePool, uid: String) -> bool {
+    database
+        .execute_with_bind("DELETE FROM Version WHERE uid=?1", &[uid])
+        .await
+        .is_ok()
+}
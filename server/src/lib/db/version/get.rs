@@ -0,0 +1,106 @@
+use crate::lib::db::version::model::Version;
+use datagn::DatabasePool;
+use logger::error;
+use sqlx::Row;
+
+pub async fn get_version(
+    database: &mut DatabasePool,
+    uid: String,
+    user_id: i32,
+) -> Option<Version> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT uid, user_id, path, size, uploader, date FROM Version WHERE uid=?1 AND user_id=?2",
+            &[uid, user_id.to_string()],
+        )
+        .await
+    {
+        Ok(row) => Some(Version {
+            uid: row.try_get("uid").ok()?,
+            user_id: row.try_get("user_id").ok()?,
+            path: row.try_get("path").ok()?,
+            size: row.try_get("size").ok()?,
+            uploader: row.try_get("uploader").ok()?,
+            date: row.try_get("date").ok()?,
+        }),
+        Err(_) => None,
+    }
+}
+
+/// Who wrote the current content of a file, if it was recorded
+pub async fn get_uploader(database: &mut DatabasePool, user_id: i32, path: String) -> Option<i32> {
+    match database
+        .execute_and_fetch_one_with_bind(
+            "SELECT uploader FROM Uploader WHERE user_id=?1 AND path=?2",
+            &[user_id.to_string(), path],
+        )
+        .await
+    {
+        Ok(row) => row.try_get("uploader").ok(),
+        Err(_) => None,
+    }
+}
+
+/// Versions of a file with the name of their uploader, the newest first
+pub async fn get_versions_of_file(
+    database: &mut DatabasePool,
+    user_id: i32,
+    path: String,
+) -> Vec<(Version, String)> {
+    let query = match database
+        .execute_and_fetch_all_with_bind(
+            "SELECT Version.uid, Version.user_id, Version.path, Version.size, Version.uploader, Version.date, User.name
+            FROM Version LEFT JOIN User ON User.id = Version.uploader
+            WHERE Version.user_id=?1 AND Version.path=?2 ORDER BY Version.date DESC",
+            &[user_id.to_string(), path],
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_versions_of_file : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut versions: Vec<(Version, String)> = Vec::new();
+    for row in query {
+        versions.push((
+            Version {
+                uid: row.try_get(0).unwrap_or_default(),
+                user_id: row.try_get(1).unwrap_or_default(),
+                path: row.try_get(2).unwrap_or_default(),
+                size: row.try_get(3).unwrap_or_default(),
+                uploader: row.try_get(4).unwrap_or_default(),
+                date: row.try_get(5).unwrap_or_default(),
+            },
+            row.try_get(6).unwrap_or_default(),
+        ));
+    }
+    versions
+}
+
+/// Owner and path of every file which has versions
+pub async fn get_versioned_files(database: &mut DatabasePool) -> Vec<(i32, String)> {
+    let query = match database
+        .execute_and_fetch_all("SELECT DISTINCT user_id, path FROM Version")
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            if cfg!(feature = "log") {
+                error(format!("Error on get_versioned_files : {:?}", e));
+            }
+            return Vec::new();
+        }
+    };
+    let mut files: Vec<(i32, String)> = Vec::new();
+    for row in query {
+        files.push((
+            row.try_get(0).unwrap_or_default(),
+            row.try_get(1).unwrap_or_default(),
+        ));
+    }
+    files
+}
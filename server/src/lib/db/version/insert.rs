@@ -0,0 +1,42 @@
+use crate::lib::db::version::model::Version;
+use datagn::DatabasePool;
+
+pub async fn insert(database: &mut DatabasePool, version: &Version) -> bool {
+    database
+        .execute_with_bind(
+            "INSERT INTO Version (uid,user_id,path,size,uploader,date) VALUES(?1, ?2, ?3, ?4, ?5, ?6)",
+            &[
+                version.uid.clone(),
+                version.user_id.to_string(),
+                version.path.clone(),
+                version.size.to_string(),
+                version.uploader.to_string(),
+                version.date.clone(),
+            ],
+        )
+        .await
+        .is_ok()
+}
+
+/// Remembers who wrote the current content of a file
+pub async fn set_uploader(
+    database: &mut DatabasePool,
+    user_id: i32,
+    path: String,
+    uploader: i32,
+) -> bool {
+    database
+        .execute_with_bind(
+            "DELETE FROM Uploader WHERE user_id=?1 AND path=?2",
+            &[user_id.to_string(), path.clone()],
+        )
+        .await
+        .is_ok()
+        && database
+            .execute_with_bind(
+                "INSERT INTO Uploader (user_id,path,uploader) VALUES(?1, ?2, ?3)",
+                &[user_id.to_string(), path, uploader.to_string()],
+            )
+            .await
+            .is_ok()
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Version {
+    pub uid: String,
+    /// Owner of the file
+    pub user_id: i32,
+    /// Path of the file, relative to the home of the owner
+    pub path: String,
+    pub size: i64,
+    /// User who uploaded this revision
+    pub uploader: i32,
+    /// RFC 3339, so the dates can be compared
+    pub date: String,
+}
+
+impl Version {
+    pub fn version_path(&self) -> String {
+        format!("{}/{}", version_folder(self.user_id), self.uid)
+    }
+}
+
+pub fn version_folder(user_id: i32) -> String {
+    format!("./versions/{}", user_id)
+}
@@ -0,0 +1,73 @@
+use crate::lib::config::MAX_DAYS;
+use crate::lib::db::version::delete::delete_version;
+use crate::lib::db::version::get::{get_versioned_files, get_versions_of_file};
+use chrono::{DateTime, Duration, Utc};
+use datagn::DatabasePool;
+use logger::error;
+
+/// Interval between two prunes of the old versions
+const PRUNE_INTERVAL: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Retention {
+    /// Number of versions kept for a file, 0 keeps all of them
+    pub keep: i64,
+    /// Number of days a version is kept, 0 keeps it forever
+    pub days: i64,
+}
+
+/// Removes the versions of a file which are out of the retention and returns how many were removed
+pub async fn prune(
+    database: &mut DatabasePool,
+    user_id: i32,
+    path: String,
+    retention: Retention,
+) -> usize {
+    let limit = Utc::now().checked_sub_signed(Duration::days(retention.days.clamp(0, MAX_DAYS)));
+    let mut pruned = 0;
+    for (n, (version, _)) in get_versions_of_file(database, user_id, path)
+        .await
+        .into_iter()
+        .enumerate()
+    {
+        let too_many = retention.keep > 0 && n as i64 >= retention.keep;
+        let too_old = retention.days > 0
+            && match limit {
+                Some(limit) => DateTime::parse_from_rfc3339(&version.date)
+                    .map(|e| e < limit)
+                    .unwrap_or(true),
+                None => false,
+            };
+        if !too_many && !too_old {
+            continue;
+        }
+        let path = version.version_path();
+        match async_std::fs::remove_file(&path).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                if cfg!(feature = "log") {
+                    error(format!("Can't remove the version {} : {:?}", path, e));
+                }
+                continue;
+            }
+        }
+        delete_version(database, version.uid).await;
+        pruned += 1;
+    }
+    pruned
+}
+
+/// The number of versions is checked when a file is written, but a version gets old without it
+pub async fn prune_task(mut database: DatabasePool, retention: Retention) {
+    // 0 keeps the versions forever
+    if retention.days <= 0 {
+        return;
+    }
+    loop {
+        for (user_id, path) in get_versioned_files(&mut database).await {
+            prune(&mut database, user_id, path, retention).await;
+        }
+        async_std::task::sleep(std::time::Duration::from_secs(PRUNE_INTERVAL)).await;
+    }
+}
@@ -0,0 +1,13 @@
+use datagn::DatabasePool;
+
+/// Follows a file or a folder moved by its owner, its versions and its uploader are kept
+pub async fn move_versions(database: &mut DatabasePool, user_id: i32, from: String, to: String) {
+    for table in &["Version", "Uploader"] {
+        let _ = database
+            .execute_with_bind(
+                &format!("UPDATE {} SET path = ?3 || substr(path, length(?2) + 1) WHERE user_id=?1 AND (path=?2 OR substr(path, 1, length(?2) + 1) = ?2 || '/')", table),
+                &[user_id.to_string(), from.clone(), to.clone()],
+            )
+            .await;
+    }
+}
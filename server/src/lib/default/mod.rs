@@ -3,13 +3,92 @@
 use datagn::database::DatabaseType;
 use logger::{error, info};
 use std::fs::{read_dir, File};
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::PathBuf;
 
-use crate::lib::config::Config;
+use crate::lib::config::args::Args;
+use crate::lib::config::load::load;
+use crate::lib::config::{
+    default_api_burst, default_api_rate, default_argon2_iterations, default_argon2_memory_kib,
+    default_argon2_parallelism, default_lockout_secs, default_log_max_rows,
+    default_log_retention_days, default_login_attempts, default_login_ip_attempts,
+    default_login_window_secs, default_max_extract_entries, default_max_extract_ratio,
+    default_max_extract_size, default_session_days, default_signup_attempts,
+    default_trash_retention_days, default_upload_expire_days, default_version_keep,
+    default_version_retention_days, Config,
+};
 use std::process::exit;
 
-pub fn default() -> Config {
+/// Config written when there is no config file
+pub fn default_config() -> Config {
+    let db_type: DatabaseType = match () {
+        #[cfg(feature = "mysql")]
+        () => DatabaseType::Mysql,
+        #[cfg(feature = "sqlite")]
+        () => DatabaseType::Sqlite,
+        #[cfg(feature = "postgres")]
+        () => DatabaseType::Postgresql,
+    };
+    Config {
+        server_ip: "0.0.0.0".to_string(),
+        server_port: 8081,
+        folder_root: "/".to_string(),
+        db_type,
+        db_port: None,
+        db_ip: String::new(),
+        db_user: None,
+        db_password: None,
+        db_database: None,
+        max_extract_size: default_max_extract_size(),
+        max_extract_ratio: default_max_extract_ratio(),
+        max_extract_entries: default_max_extract_entries(),
+        trash_retention_days: default_trash_retention_days(),
+        version_keep: default_version_keep(),
+        version_retention_days: default_version_retention_days(),
+        upload_expire_days: default_upload_expire_days(),
+        quota_bytes: None,
+        quota_files: None,
+        admin: None,
+        argon2_memory_kib: default_argon2_memory_kib(),
+        argon2_iterations: default_argon2_iterations(),
+        argon2_parallelism: default_argon2_parallelism(),
+        session_days: default_session_days(),
+        login_attempts: default_login_attempts(),
+        login_ip_attempts: default_login_ip_attempts(),
+        signup_attempts: default_signup_attempts(),
+        login_window_secs: default_login_window_secs(),
+        lockout_secs: default_lockout_secs(),
+        api_rate: default_api_rate(),
+        api_burst: default_api_burst(),
+        trusted_proxies: Vec::new(),
+        log_retention_days: default_log_retention_days(),
+        log_max_rows: default_log_max_rows(),
+    }
+}
+
+fn fail(message: String) -> ! {
+    if cfg!(feature = "log") {
+        error(message);
+    } else {
+        eprintln!("{}", message);
+    }
+    exit(1)
+}
+
+/// Creates the data folders and builds the config, the process exits if it is invalid
+pub fn default(args: &Args) -> Config {
+    if let Some(data_dir) = &args.data_dir {
+        if let Err(e) =
+            std::fs::create_dir_all(data_dir).and_then(|_| std::env::set_current_dir(data_dir))
+        {
+            fail(format!(
+                "Can't use the data folder {} : {}",
+                data_dir.display(),
+                e
+            ));
+        }
+    }
+
     let mut vec: Vec<String> = Vec::new();
     let rd = read_dir(PathBuf::from("./")).expect("Error: Can't read the folder");
     for rde in rd {
@@ -20,75 +99,53 @@ pub fn default() -> Config {
     if !vec.contains(&String::from("temp")) {
         std::fs::create_dir("./temp").expect("Failed to create the temp folder");
     }
+    std::fs::create_dir_all("./temp/upload").expect("Failed to create the upload folder");
     if !vec.contains(&String::from("db.sql")) {
         std::fs::File::create("./db.sql").expect("Failed to create the database");
     }
+    std::fs::create_dir_all("./trash").expect("Failed to create the trash folder");
+    std::fs::create_dir_all("./versions").expect("Failed to create the versions folder");
     if !vec.contains(&String::from("home")) {
         std::fs::create_dir("./home").expect("Failed to create the home folder");
     }
-    if vec.contains(&String::from("config.yaml")) {
-        let mut buf = String::new();
-        match File::open("./config.yaml") {
-            Ok(mut e) => match e.read_to_string(&mut buf) {
-                Ok(_) => {}
-                Err(_) => {
-                    if cfg!(feature = "log") {
-                        error("Can't read the config");
-                    }
-                    exit(1)
-                }
-            },
-            Err(_) => {
-                if cfg!(feature = "log") {
-                    error("Can't found the config");
-                }
-                exit(1)
-            }
-        };
-        match serde_yaml::from_str(&buf) {
-            Ok(o) => o,
-            Err(_) => {
+    let path = args.config_path();
+    if !path.exists() {
+        match File::create(&path).and_then(|mut ff| {
+            ff.write_all(
+                serde_yaml::to_string(&default_config())
+                    .unwrap_or_default()
+                    .as_bytes(),
+            )
+        }) {
+            Err(why) => fail(format!("couldn't write to config : {}", why)),
+            Ok(_) => {
                 if cfg!(feature = "log") {
-                    error("Config Error");
+                    info("successfully wrote to config")
                 }
-                exit(1);
             }
         }
-    } else {
-        let db_type: DatabaseType = match () {
-            #[cfg(feature = "mysql")]
-            () => DatabaseType::Mysql,
-            #[cfg(feature = "sqlite")]
-            () => DatabaseType::Sqlite,
-            #[cfg(feature = "postgres")]
-            () => DatabaseType::Postgresql,
-        };
-        let config = Config {
-            server_ip: "0.0.0.0".to_string(),
-            server_port: 8081,
-            folder_root: "/".to_string(),
-            db_type,
-            db_port: None,
-            db_ip: String::new(),
-            db_user: None,
-            db_password: None,
-            db_database: None,
-        };
-        if let Ok(mut ff) = File::create("./config.yaml") {
-            match ff.write(serde_yaml::to_string(&config).unwrap().as_bytes()) {
-                Err(why) => {
-                    if cfg!(feature = "log") {
-                        error(format!("couldn't write to config : {}", why.to_string()));
-                    }
-                    exit(1)
-                }
-                Ok(_) => {
-                    if cfg!(feature = "log") {
-                        info("successfully wrote to config")
-                    }
-                }
-            }
+    }
+    match load(args, &path) {
+        Ok(config) => config,
+        Err(e) => fail(format!("Config error, {}", e)),
+    }
+}
+
+/// `check-config`, returns the exit code
+pub fn check_config(args: &Args) -> i32 {
+    let path = args.config_path();
+    if !path.exists() {
+        eprintln!("{}: not found", path.display());
+        return 1;
+    }
+    match load(args, &path) {
+        Ok(_) => {
+            println!("{}: valid", path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
         }
-        config
     }
 }
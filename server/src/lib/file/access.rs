@@ -0,0 +1,285 @@
+//! Access to the files of other users, through the folders they share.
+//!
+//! The folders shared with a user are shown in a virtual `Shared with me` folder at the root of
+//! their home, as `Shared with me/<owner>/<folder>/...`. A real folder with this name is hidden.
+
+use crate::lib::db::permission::get::get_permissions_for_user;
+use crate::lib::db::permission::model::{Access, Permission};
+use crate::lib::db::user::get::get_user_by_id;
+use crate::lib::db::user::model::User;
+use crate::lib::file::file_trait::TraitFolder;
+use crate::lib::file::inhome;
+use actix_web::HttpResponse;
+use datagn::DatabasePool;
+use shared::Folder;
+
+pub const SHARED_ROOT: &str = "Shared with me";
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Read,
+    Write,
+    Owner,
+}
+
+impl From<Access> for Level {
+    fn from(access: Access) -> Self {
+        match access {
+            Access::Read => Level::Read,
+            Access::Write => Level::Write,
+        }
+    }
+}
+
+/// A path of a home, seen by a user who may not be its owner. It can always be read.
+pub struct Resolved {
+    pub owner_id: i32,
+    pub owner: String,
+    /// Path relative to the home of the owner
+    pub relative: String,
+    pub level: Level,
+    /// The path is a shared folder, the user who received it can't move or delete it
+    pub shared_root: bool,
+}
+
+impl Resolved {
+    pub fn full_path(&self) -> String {
+        format!("./home/{}/{}", self.owner, self.relative)
+    }
+
+    pub fn can_write(&self) -> bool {
+        self.level >= Level::Write
+    }
+
+    /// The home and the shared folders themselves can't be moved or deleted
+    pub fn can_remove(&self) -> bool {
+        self.can_write() && !self.relative.is_empty() && !self.shared_root
+    }
+}
+
+pub enum Resolution {
+    Path(Resolved),
+    /// `Shared with me` and the folders of its owners only have a listing
+    Virtual(Vec<Folder>),
+}
+
+struct SharedFolder {
+    /// Name shown to the user who received it
+    name: String,
+    owner_id: i32,
+    path: String,
+}
+
+fn stay_at_home() -> HttpResponse {
+    HttpResponse::BadRequest().body("Stay at home please")
+}
+
+fn not_shared() -> HttpResponse {
+    HttpResponse::NotFound().body("This folder is not shared with you")
+}
+
+fn base_name(path: &str, owner: &str) -> String {
+    path.rsplit('/')
+        .next()
+        .filter(|e| !e.is_empty())
+        .unwrap_or(owner)
+        .to_string()
+}
+
+/// Folders shared by `owner`, a folder granted twice is shown once and the id of the grant is
+/// added to the names used by several folders
+fn shared_folders(grants: &[(Permission, String)], owner: &str) -> Vec<SharedFolder> {
+    let mut folders: Vec<(i32, &Permission)> = Vec::new();
+    for (grant, name) in grants {
+        if name == owner && !folders.iter().any(|(_, e)| e.path == grant.path) {
+            folders.push((grant.id, grant));
+        }
+    }
+    folders
+        .iter()
+        .map(|(id, grant)| {
+            let name = base_name(&grant.path, owner);
+            let homonyms = folders
+                .iter()
+                .filter(|(_, e)| base_name(&e.path, owner) == name)
+                .count();
+            SharedFolder {
+                name: if homonyms > 1 {
+                    format!("{} ({})", name, id)
+                } else {
+                    name
+                },
+                owner_id: grant.owner_id,
+                path: grant.path.clone(),
+            }
+        })
+        .collect()
+}
+
+/// The highest access given by the grants on the path or on one of its parents
+fn level_of(grants: &[(Permission, String)], owner: &str, relative: &str) -> Option<Level> {
+    grants
+        .iter()
+        .filter(|(grant, name)| {
+            name == owner
+                && (grant.path.is_empty()
+                    || relative == grant.path
+                    || relative.starts_with(&format!("{}/", grant.path)))
+        })
+        .map(|(grant, _)| Level::from(grant.access))
+        .fold(None, |max, e| match max {
+            Some(max) if max >= e => Some(max),
+            _ => Some(e),
+        })
+}
+
+fn virtual_folder(name: String) -> Folder {
+    Folder::new(
+        true,
+        name,
+        0,
+        String::from("0-0-0000 00:00:00"),
+        String::from("0-0-0000 00:00:00"),
+        String::from("Folder"),
+    )
+}
+
+fn checked(resolved: Resolved) -> Result<Resolution, HttpResponse> {
+    if inhome(resolved.full_path()) {
+        Ok(Resolution::Path(resolved))
+    } else {
+        Err(stay_at_home())
+    }
+}
+
+/// Owner of the resolved path, which is `user` for a path of their home
+pub async fn owner_of(
+    database: &mut DatabasePool,
+    resolved: &Resolved,
+    user: User,
+) -> Option<User> {
+    if user.id == Some(resolved.owner_id) {
+        Some(user)
+    } else {
+        get_user_by_id(database, resolved.owner_id).await
+    }
+}
+
+/// Entry of `Shared with me` in the root of the home, if something is shared with the user
+pub async fn shared_root_entry(database: &mut DatabasePool, user: &User) -> Option<Folder> {
+    if get_permissions_for_user(database, user.id?)
+        .await
+        .is_empty()
+    {
+        None
+    } else {
+        Some(virtual_folder(String::from(SHARED_ROOT)))
+    }
+}
+
+/// Finds which file a path sent by `user` is, and what they can do with it
+pub async fn resolve(
+    database: &mut DatabasePool,
+    user: &User,
+    path: &str,
+) -> Result<Resolution, HttpResponse> {
+    let user_id = user.id.unwrap_or_default();
+    let parts: Vec<&str> = path
+        .split('/')
+        .filter(|e| !e.is_empty() && *e != ".")
+        .collect();
+    if parts.contains(&"..") {
+        return Err(stay_at_home());
+    }
+    if parts.first() != Some(&SHARED_ROOT) {
+        return checked(Resolved {
+            owner_id: user_id,
+            owner: user.name.clone(),
+            relative: parts.join("/"),
+            level: Level::Owner,
+            shared_root: false,
+        });
+    }
+
+    let grants = get_permissions_for_user(database, user_id).await;
+    match parts.len() {
+        1 => {
+            let mut owners: Vec<String> = grants.iter().map(|(_, name)| name.clone()).collect();
+            owners.sort();
+            owners.dedup();
+            Ok(Resolution::Virtual(
+                owners.into_iter().map(virtual_folder).collect(),
+            ))
+        }
+        2 => {
+            let folders = shared_folders(&grants, parts[1]);
+            if folders.is_empty() {
+                return Err(not_shared());
+            }
+            let mut content: Vec<Folder> = Vec::new();
+            for folder in folders {
+                let full_path = format!("./home/{}/{}", parts[1], folder.path);
+                // The owner may have removed the folder since it was shared
+                if let Ok(metadata) = std::fs::metadata(&full_path) {
+                    let mut entry = Folder::from_metadata(metadata, full_path);
+                    entry.name = folder.name;
+                    content.push(entry);
+                }
+            }
+            Ok(Resolution::Virtual(content))
+        }
+        _ => {
+            let folder = match shared_folders(&grants, parts[1])
+                .into_iter()
+                .find(|e| e.name == parts[2])
+            {
+                Some(e) => e,
+                None => return Err(not_shared()),
+            };
+            let relative = std::iter::once(folder.path.as_str())
+                .chain(parts[3..].iter().copied())
+                .filter(|e| !e.is_empty())
+                .collect::<Vec<&str>>()
+                .join("/");
+            let level = match level_of(&grants, parts[1], &relative) {
+                Some(e) => e,
+                None => return Err(not_shared()),
+            };
+            checked(Resolved {
+                owner_id: folder.owner_id,
+                owner: parts[1].to_string(),
+                relative,
+                level,
+                shared_root: parts.len() == 3,
+            })
+        }
+    }
+}
+
+/// Resolves a path which must be a real file or folder
+pub async fn resolve_path(
+    database: &mut DatabasePool,
+    user: &User,
+    path: &str,
+) -> Result<Resolved, HttpResponse> {
+    match resolve(database, user, path).await? {
+        Resolution::Path(e) => Ok(e),
+        Resolution::Virtual(_) => {
+            Err(HttpResponse::Forbidden().body("This folder only lists the shared folders"))
+        }
+    }
+}
+
+/// Same as `resolve_path`, for a path which will be written
+pub async fn resolve_writable(
+    database: &mut DatabasePool,
+    user: &User,
+    path: &str,
+) -> Result<Resolved, HttpResponse> {
+    let resolved = resolve_path(database, user, path).await?;
+    if resolved.can_write() {
+        Ok(resolved)
+    } else {
+        Err(HttpResponse::Forbidden().body("This folder is shared in read only"))
+    }
+}
@@ -1,20 +1,24 @@
+pub mod access;
 pub mod default;
 pub mod file_trait;
+pub mod operation;
+pub mod quota;
+pub mod receive;
+pub mod stream;
+pub mod trash;
+pub mod upload;
+pub mod version;
 
-use crate::lib::archive::random_archive;
 use crate::lib::file::file_trait::TraitFolder;
+use crate::lib::file::stream::{stream_file, Disposition};
 use actix_web::body::Body;
 use actix_web::dev::BodyEncoding;
 use actix_web::http::ContentEncoding;
-use actix_web::web::Bytes;
-use actix_web::HttpResponse;
-use async_std::io::ReadExt;
-use logger::{error, warn};
+use actix_web::{HttpRequest, HttpResponse};
+use logger::warn;
 use shared::{FType, Folder, JsonStruct};
 use std::fs;
-use std::fs::{metadata, read_dir};
-
-use super::archive::ArchiveType;
+use std::fs::read_dir;
 
 pub enum Sort {
     Name,
@@ -24,14 +28,21 @@ pub enum Sort {
 }
 
 pub fn dir_content(path: String, sort: Sort) -> String {
+    if !inhome(path.clone()) {
+        return String::from("Stay at home please");
+    }
+    match serde_json::to_string(&dir_listing(path, sort)) {
+        Ok(e) => e,
+        Err(_e) => String::from("Not Work"),
+    }
+}
+
+pub fn dir_listing(path: String, sort: Sort) -> JsonStruct {
     let mut content: Vec<Folder> = Vec::new();
     let mut result: bool = false;
     let mut ftype: FType = FType::Error;
 
     let root = if cfg!(windows) { "C:" } else { "" };
-    if !inhome(path.clone()) {
-        return String::from("Stay at home please");
-    }
     match fs::metadata(format!("{}{}", root, path)) {
         Ok(e) => {
             if e.is_file() {
@@ -78,6 +89,16 @@ pub fn dir_content(path: String, sort: Sort) -> String {
         }
     }
 
+    sort_content(&mut content, sort);
+    JsonStruct {
+        result,
+        lenght: content.len() as i64,
+        ftype,
+        content,
+    }
+}
+
+pub fn sort_content(content: &mut [Folder], sort: Sort) {
     match sort {
         Sort::Name => {
             content.sort_by(|a, b| a.name.cmp(&b.name));
@@ -92,62 +113,19 @@ pub fn dir_content(path: String, sort: Sort) -> String {
             content.sort_by(|a, b| b.created.cmp(&a.created));
         }
     }
-    let folder = JsonStruct {
-        result,
-        lenght: content.len() as i64,
-        ftype,
-        content,
-    };
-    match serde_json::to_string(&folder) {
-        Ok(e) => e,
-        Err(_e) => String::from("Not Work"),
-    }
 }
 
-pub async fn get_file_as_byte_vec(filename: String, compress: ArchiveType) -> Bytes {
-    let mut buf: Vec<u8> = Vec::new();
-    if let Ok(e) = metadata(filename.clone()) {
-        if e.is_file() {
-            if let Ok(mut file) = async_std::fs::File::open(filename.clone()).await {
-                if file.read(&mut buf).await.is_ok() {}
-            }
-        } else {
-            let mut file = match compress {
-                ArchiveType::Targz => random_archive("tar.gz".to_string(), filename),
-                ArchiveType::Zip => random_archive("zip".to_string(), filename),
-            }
-            .await;
-            if cfg!(debug_assertions) {
-                println!("{}", file.metadata().await.unwrap().len());
-            }
-            match file.read_to_end(&mut buf).await {
-                Ok(e) => {
-                    if cfg!(debug_assertions) {
-                        println!("{}", e);
-                    }
-                }
-                Err(e) => {
-                    if cfg!(feature = "log") {
-                        error(format!("{:?}", e))
-                    }
-                }
-            };
-        }
-    }
-    if buf.is_empty() {
-        let vec: Vec<u8> = String::from("Error").as_bytes().to_vec();
-        buf = vec;
-    }
-    actix_web::web::Bytes::from(buf)
+pub fn get_dir(path: String, sort: Sort) -> HttpResponse<Body> {
+    listing_response(crate::lib::file::dir_content(path, sort))
 }
 
-pub fn get_dir(path: String, sort: Sort) -> HttpResponse<Body> {
+pub fn listing_response(body: String) -> HttpResponse<Body> {
     HttpResponse::Ok()
         .header("Access-Control-Allow-Origin", "*")
         .header("charset", "utf-8")
         .content_type("application/json")
         .encoding(ContentEncoding::Gzip)
-        .body(crate::lib::file::dir_content(path, sort))
+        .body(body)
 }
 
 pub fn get_size_dir(path: String) -> u64 {
@@ -165,34 +143,8 @@ pub fn get_size_dir(path: String) -> u64 {
     size
 }
 
-pub async fn get_file_preview(path: String) -> HttpResponse<Body> {
-    match async_std::fs::File::open(path.clone()).await {
-        Ok(mut f) => {
-            let mut buf: Vec<u8> = Vec::new();
-            match f.read_to_end(&mut buf).await {
-                Ok(e) => {
-                    if cfg!(debug_assertions) {
-                        println!("{}", e);
-                    }
-                }
-                Err(e) => error(format!("{:?}", e)),
-            }
-
-            HttpResponse::Ok()
-                .header("Access-Control-Allow-Origin", "*")
-                .header("charset", "utf-8")
-                .content_type(
-                    mime_guess::from_ext(path.split('/').last().unwrap_or(""))
-                        .first_or_octet_stream()
-                        .to_string(),
-                )
-                .body(buf)
-        }
-        Err(_) => HttpResponse::Ok()
-            .header("Access-Control-Allow-Origin", "*")
-            .header("charset", "utf-8")
-            .body("Error"),
-    }
+pub async fn get_file_preview(req: &HttpRequest, path: String) -> HttpResponse<Body> {
+    stream_file(req, path, Disposition::Inline).await
 }
 
 pub fn inhome(path: String) -> bool {
@@ -0,0 +1,162 @@
+use crate::lib::db::user::token::generate_token;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub enum Conflict {
+    Fail,
+    Overwrite,
+    Rename,
+}
+
+impl Conflict {
+    pub fn from_name(name: Option<&str>) -> Self {
+        match name {
+            Some("overwrite") => Conflict::Overwrite,
+            Some("rename") => Conflict::Rename,
+            _ => Conflict::Fail,
+        }
+    }
+}
+
+/// Finds a free name like `name (1).ext` next to `path`
+pub fn free_name(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let mut n = 1;
+    loop {
+        let candidate = path.with_file_name(format!("{} ({}){}", stem, n, extension));
+        if !candidate.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+pub fn remove(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Applies the conflict policy and returns the path which must be written. An overwritten item
+/// is still there, see `Placement`.
+pub fn destination(to: &Path, conflict: &Conflict) -> io::Result<PathBuf> {
+    if !to.exists() {
+        return Ok(to.to_path_buf());
+    }
+    match conflict {
+        Conflict::Fail => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "The destination already exists",
+        )),
+        Conflict::Overwrite => Ok(to.to_path_buf()),
+        Conflict::Rename => Ok(free_name(to)),
+    }
+}
+
+/// Where a move or a copy is written. An overwritten item is only replaced once the new content
+/// is complete: the content is written next to it, then renamed over it by `finish`.
+#[derive(Clone)]
+pub struct Placement {
+    pub path: PathBuf,
+    /// The item which is overwritten
+    pub replaced: Option<PathBuf>,
+}
+
+impl Placement {
+    fn new(to: &Path, conflict: &Conflict) -> io::Result<Self> {
+        let path = destination(to, conflict)?;
+        if !path.exists() {
+            return Ok(Self {
+                path,
+                replaced: None,
+            });
+        }
+        let name = path
+            .file_name()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Ok(Self {
+            path: path.with_file_name(format!(".{}.{}.part", name, generate_token())),
+            replaced: Some(path),
+        })
+    }
+
+    /// Puts the content in the place of the replaced item, which must have been moved away
+    pub fn finish(&self) -> io::Result<PathBuf> {
+        match &self.replaced {
+            Some(e) => {
+                fs::rename(&self.path, e)?;
+                Ok(e.clone())
+            }
+            None => Ok(self.path.clone()),
+        }
+    }
+}
+
+/// Copies a file or a folder with its content and returns the number of copied bytes
+pub fn copy_recursive(from: &Path, to: &Path) -> io::Result<u64> {
+    if from.is_dir() {
+        fs::create_dir(to)?;
+        let mut size = 0;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            size += copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(size)
+    } else {
+        fs::copy(from, to)
+    }
+}
+
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // rename doesn't work between two devices
+    copy_recursive(from, to)?;
+    remove(from)
+}
+
+fn check(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.exists() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "No file"));
+    }
+    if to.starts_with(from) || from.starts_with(to) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "A folder can't be put inside itself",
+        ));
+    }
+    Ok(())
+}
+
+pub fn move_with_conflict(from: &Path, to: &Path, conflict: Conflict) -> io::Result<Placement> {
+    check(from, to)?;
+    let target = Placement::new(to, &conflict)?;
+    move_path(from, &target.path)?;
+    Ok(target)
+}
+
+pub fn copy_with_conflict(from: &Path, to: &Path, conflict: Conflict) -> io::Result<Placement> {
+    check(from, to)?;
+    let target = Placement::new(to, &conflict)?;
+    if let Err(e) = copy_recursive(from, &target.path) {
+        // A partial copy is not left behind
+        let _ = remove(&target.path);
+        return Err(e);
+    }
+    Ok(target)
+}
@@ -0,0 +1,160 @@
+use crate::lib::config::Config;
+use crate::lib::db::group::get::get_groups_of_user;
+use crate::lib::db::group::model::Group;
+use crate::lib::db::trash::model::trash_folder;
+use crate::lib::db::user::model::User;
+use crate::lib::db::version::model::version_folder;
+use actix_web::HttpResponse;
+use datagn::DatabasePool;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Usage {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// `None` means unlimited
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    pub bytes: Option<u64>,
+    pub files: Option<u64>,
+}
+
+pub enum Exceeded {
+    /// The file alone is bigger than the whole quota
+    TooLarge,
+    Bytes,
+    Files,
+}
+
+impl Exceeded {
+    pub fn response(&self) -> HttpResponse {
+        match self {
+            Exceeded::TooLarge => {
+                HttpResponse::PayloadTooLarge().body("The file is bigger than your quota")
+            }
+            Exceeded::Bytes => HttpResponse::InsufficientStorage().body("Your storage is full"),
+            Exceeded::Files => HttpResponse::InsufficientStorage().body("You have too many files"),
+        }
+    }
+}
+
+/// A negative value is unlimited, so it is larger than every other one
+fn largest(values: impl Iterator<Item = i64>) -> Option<i64> {
+    values.fold(None, |max, e| match max {
+        Some(max) if max < 0 || (e >= 0 && max >= e) => Some(max),
+        _ => Some(e),
+    })
+}
+
+impl Quota {
+    /// The quota of the user, else the largest one of their groups, else the default one of the
+    /// config
+    pub fn of(user: &User, groups: &[Group], config: &Config) -> Self {
+        Self {
+            bytes: user
+                .quota_bytes
+                .or_else(|| largest(groups.iter().filter_map(|e| e.quota_bytes)))
+                .or(config.quota_bytes)
+                .filter(|e| *e >= 0)
+                .map(|e| e as u64),
+            files: user
+                .quota_files
+                .or_else(|| largest(groups.iter().filter_map(|e| e.quota_files)))
+                .or(config.quota_files)
+                .filter(|e| *e >= 0)
+                .map(|e| e as u64),
+        }
+    }
+
+    /// Bytes and files which still fit, `u64::MAX` when unlimited
+    pub fn remaining(&self, usage: Usage) -> Usage {
+        Usage {
+            bytes: self
+                .bytes
+                .map_or(u64::MAX, |e| e.saturating_sub(usage.bytes)),
+            files: self
+                .files
+                .map_or(u64::MAX, |e| e.saturating_sub(usage.files)),
+        }
+    }
+
+    /// Checks if `bytes` more bytes and `files` more files still fit
+    pub fn check(&self, usage: Usage, bytes: u64, files: u64) -> Result<(), Exceeded> {
+        if let Some(limit) = self.bytes {
+            if bytes > limit {
+                return Err(Exceeded::TooLarge);
+            }
+            if usage.bytes + bytes > limit {
+                return Err(Exceeded::Bytes);
+            }
+        }
+        if let Some(limit) = self.files {
+            if usage.files + files > limit {
+                return Err(Exceeded::Files);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Quota of a user, with the ones of their groups
+pub async fn quota_of(database: &mut DatabasePool, user: &User, config: &Config) -> Quota {
+    let groups = get_groups_of_user(database, user.id.unwrap_or_default()).await;
+    Quota::of(user, &groups, config)
+}
+
+/// Size and number of files in a folder, sub folders included
+pub fn usage(path: &Path) -> Usage {
+    let mut usage = Usage::default();
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            match entry.metadata() {
+                Ok(e) if e.is_dir() => {
+                    let sub = self::usage(&entry.path());
+                    usage.bytes += sub.bytes;
+                    usage.files += sub.files;
+                }
+                Ok(e) => {
+                    usage.bytes += e.len();
+                    usage.files += 1;
+                }
+                Err(_) => {}
+            }
+        }
+    }
+    usage
+}
+
+/// Size and number of files of a file or a folder
+pub fn path_usage(path: &Path) -> Usage {
+    match fs::symlink_metadata(path) {
+        Ok(e) if e.is_dir() => usage(path),
+        Ok(e) => Usage {
+            bytes: e.len(),
+            files: 1,
+        },
+        Err(_) => Usage::default(),
+    }
+}
+
+/// Usage of a user: their home, their trash and the previous versions of their files
+pub async fn home_usage(user: &User) -> Usage {
+    let user_id = user.id.unwrap_or_default();
+    let mut usage = folder_usage(format!("./home/{}", user.name)).await;
+    for folder in [trash_folder(user_id), version_folder(user_id)].iter() {
+        let other = folder_usage(folder.clone()).await;
+        usage.bytes += other.bytes;
+        usage.files += other.files;
+    }
+    usage
+}
+
+/// Same as `usage`, without blocking the server
+pub async fn folder_usage(path: String) -> Usage {
+    actix_web::web::block(move || Ok::<Usage, ()>(usage(Path::new(&path))))
+        .await
+        .unwrap_or_default()
+}
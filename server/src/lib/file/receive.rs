@@ -0,0 +1,169 @@
+//! Multipart uploads, shared by the files API and the drop box links.
+
+use crate::lib::db::log::insert::insert;
+use crate::lib::db::log::model::NewLog;
+use crate::lib::db::upload::model::temp_path;
+use crate::lib::db::user::token::generate_token;
+use crate::lib::db::version::prune::Retention;
+use crate::lib::file::operation::free_name;
+use crate::lib::file::quota::{Quota, Usage};
+use crate::lib::file::version::{keep_previous, record_uploader};
+use actix_multipart::{Field, Multipart};
+use actix_web::HttpResponse;
+use async_std::io::prelude::WriteExt;
+use datagn::DatabasePool;
+use logger::error;
+use std::path::Path;
+use tokio_stream::StreamExt;
+
+pub enum Collision {
+    /// The existing file is replaced and kept in the versions
+    Version(Retention),
+    /// The received file is renamed `name (1).ext`
+    Rename,
+}
+
+pub struct Target {
+    /// Owner of the folder
+    pub user_id: i32,
+    /// User who sends the files
+    pub actor: i32,
+    pub home: String,
+    /// Folder which receives the files, relative to the home
+    pub folder: String,
+    pub quota: Quota,
+    pub usage: Usage,
+    /// Maximum size of one file
+    pub max_size: Option<u64>,
+    pub collision: Collision,
+    /// Entry logged for each file, with the client of the request
+    pub log: NewLog,
+}
+
+/// Only the name of the file is kept, a client can't choose where it is written
+fn file_name(field: &Field) -> Option<String> {
+    let name = field
+        .content_disposition()?
+        .get_filename()?
+        .rsplit(|c| c == '/' || c == '\\')
+        .next()?
+        .to_string();
+    if name.is_empty() || name == "." || name == ".." {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Writes every file of the form into the target folder and returns their paths
+pub async fn receive(
+    database: &mut DatabasePool,
+    payload: &mut Multipart,
+    mut target: Target,
+) -> Result<Vec<String>, HttpResponse> {
+    let folder = target.folder.trim_matches('/').to_string();
+    let mut received: Vec<String> = Vec::new();
+    while let Ok(Some(mut field)) = payload.try_next().await {
+        let name = match file_name(&field) {
+            Some(e) => e,
+            None => return Err(HttpResponse::BadRequest().body("Bad file name")),
+        };
+        let mut filepath = Path::new(&target.home).join(&folder).join(&name);
+        if let Collision::Rename = target.collision {
+            filepath = free_name(&filepath);
+        }
+
+        // The file is only put in place once it is complete, a refused or broken upload leaves
+        // the existing one untouched
+        let temp = temp_path(&generate_token());
+        let mut f = match async_std::fs::File::create(&temp).await {
+            Ok(e) => e,
+            Err(_) => {
+                return Err(HttpResponse::InternalServerError().body("Error on creation of file"))
+            }
+        };
+        let mut written: u64 = 0;
+        while let Some(chunk) = field.next().await {
+            let chunk = match chunk {
+                Ok(e) => e,
+                Err(e) => {
+                    if cfg!(feature = "log") {
+                        error(format!("The upload was interrupted : {:?}", e));
+                    }
+                    drop(f);
+                    let _ = async_std::fs::remove_file(&temp).await;
+                    return Err(HttpResponse::BadRequest().body("The upload was interrupted"));
+                }
+            };
+            written += chunk.len() as u64;
+            let refused = if target.max_size.map_or(false, |max| written > max) {
+                Some(HttpResponse::PayloadTooLarge().body("The file is too big"))
+            } else {
+                target
+                    .quota
+                    .check(target.usage, written, 1)
+                    .err()
+                    .map(|e| e.response())
+            };
+            if let Some(response) = refused {
+                drop(f);
+                let _ = async_std::fs::remove_file(&temp).await;
+                return Err(response);
+            }
+            if f.write_all(&chunk).await.is_err() {
+                drop(f);
+                let _ = async_std::fs::remove_file(&temp).await;
+                return Err(HttpResponse::InternalServerError().body("Error"));
+            }
+        }
+        if f.flush().await.is_err() {
+            drop(f);
+            let _ = async_std::fs::remove_file(&temp).await;
+            return Err(HttpResponse::InternalServerError().body("Error"));
+        }
+        drop(f);
+
+        if let Collision::Version(retention) = &target.collision {
+            if let Err(e) = keep_previous(
+                database,
+                target.user_id,
+                format!("{}/{}", folder, name),
+                &filepath.to_string_lossy(),
+                *retention,
+            )
+            .await
+            {
+                if cfg!(feature = "log") {
+                    error(format!("Can't keep the previous version : {:?}", e));
+                }
+                let _ = async_std::fs::remove_file(&temp).await;
+                return Err(HttpResponse::InternalServerError().body("Error on creation of file"));
+            }
+        }
+        if async_std::fs::rename(&temp, &filepath).await.is_err() {
+            // ./temp and ./home may be on different devices
+            let copied = async_std::fs::copy(&temp, &filepath).await;
+            let _ = async_std::fs::remove_file(&temp).await;
+            if copied.is_err() {
+                return Err(HttpResponse::InternalServerError().body("Error on creation of file"));
+            }
+        }
+
+        target.usage.bytes += written;
+        target.usage.files += 1;
+        let relative = filepath
+            .strip_prefix(&target.home)
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or(name);
+        record_uploader(database, target.user_id, &relative, target.actor).await;
+        let log = target
+            .log
+            .clone()
+            .owner(target.user_id)
+            .path(&relative)
+            .bytes(written);
+        insert(database, log).await;
+        received.push(relative);
+    }
+    Ok(received)
+}
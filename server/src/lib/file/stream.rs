@@ -0,0 +1,373 @@
+use actix_web::body::{Body, SizedStream};
+use actix_web::http::header::HttpDate;
+use actix_web::web::Bytes;
+use actix_web::{Error, HttpRequest, HttpResponse};
+use async_std::fs as afs;
+use async_std::io::prelude::{ReadExt, SeekExt};
+use async_std::io::SeekFrom;
+use futures::stream::Stream;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+pub enum Disposition {
+    Inline,
+    Attachment,
+}
+
+enum Range {
+    Full,
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+pub fn file_stream(
+    file: afs::File,
+    length: u64,
+) -> impl Stream<Item = Result<Bytes, Error>> + Unpin + 'static {
+    Box::pin(futures::stream::unfold(
+        (file, length),
+        |(mut file, remaining)| async move {
+            if remaining == 0 {
+                return None;
+            }
+            let mut buf = vec![0u8; remaining.min(CHUNK_SIZE) as usize];
+            match file.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buf.truncate(n);
+                    Some((Ok(Bytes::from(buf)), (file, remaining - n as u64)))
+                }
+                Err(e) => Some((Err(Error::from(e)), (file, 0))),
+            }
+        },
+    ))
+}
+
+fn header(req: &HttpRequest, name: &str) -> Option<String> {
+    req.headers()
+        .get(name)
+        .and_then(|e| e.to_str().ok())
+        .map(|e| e.trim().to_string())
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|e| e.as_secs())
+        .unwrap_or_default()
+}
+
+fn parse_date(value: &str) -> Option<u64> {
+    value
+        .parse::<HttpDate>()
+        .ok()
+        .map(|e| seconds(SystemTime::from(e)))
+}
+
+fn etag_matches(list: &str, etag: &str) -> bool {
+    list.split(',')
+        .map(|e| e.trim().trim_start_matches("W/"))
+        .any(|e| e == "*" || e == etag)
+}
+
+fn not_modified(req: &HttpRequest, etag: &str, modified: u64) -> bool {
+    if let Some(e) = header(req, "If-None-Match") {
+        etag_matches(&e, etag)
+    } else if let Some(e) = header(req, "If-Modified-Since") {
+        parse_date(&e).map_or(false, |since| modified <= since)
+    } else {
+        false
+    }
+}
+
+fn parse_range(req: &HttpRequest, etag: &str, modified: u64, length: u64) -> Range {
+    let range = match header(req, "Range") {
+        Some(e) => e,
+        None => return Range::Full,
+    };
+    if let Some(e) = header(req, "If-Range") {
+        let fresh = if e.starts_with('"') {
+            e == etag
+        } else {
+            parse_date(&e) == Some(modified)
+        };
+        if !fresh {
+            return Range::Full;
+        }
+    }
+
+    let spec = match range.strip_prefix("bytes=") {
+        // Multiple ranges are allowed to be ignored, the whole file is sent instead
+        Some(e) if !e.contains(',') => e.trim(),
+        _ => return Range::Full,
+    };
+    let (start, end) = match spec.find('-') {
+        Some(i) => (spec[..i].trim(), spec[i + 1..].trim()),
+        None => return Range::Full,
+    };
+
+    if start.is_empty() {
+        match end.parse::<u64>() {
+            Ok(0) => Range::Unsatisfiable,
+            Ok(_) if length == 0 => Range::Unsatisfiable,
+            Ok(suffix) => Range::Partial(length.saturating_sub(suffix), length - 1),
+            Err(_) => Range::Full,
+        }
+    } else {
+        let start = match start.parse::<u64>() {
+            Ok(e) => e,
+            Err(_) => return Range::Full,
+        };
+        if start >= length {
+            return Range::Unsatisfiable;
+        }
+        if end.is_empty() {
+            return Range::Partial(start, length - 1);
+        }
+        match end.parse::<u64>() {
+            Ok(end) if end >= start => Range::Partial(start, end.min(length - 1)),
+            _ => Range::Full,
+        }
+    }
+}
+
+/// Last modification in seconds and ETag of a file
+fn validators(metadata: &std::fs::Metadata) -> (SystemTime, u64, String) {
+    let modified_time = metadata.modified().unwrap_or_else(|_| SystemTime::now());
+    let modified = seconds(modified_time);
+    let etag = format!("\"{:x}-{:x}\"", modified, metadata.len());
+    (modified_time, modified, etag)
+}
+
+/// Offset of the first byte `stream_file` sends for the request, `None` if it sends no content
+pub fn first_byte(req: &HttpRequest, metadata: &std::fs::Metadata) -> Option<u64> {
+    let (_, modified, etag) = validators(metadata);
+    if not_modified(req, &etag, modified) {
+        return None;
+    }
+    match parse_range(req, &etag, modified, metadata.len()) {
+        Range::Full => Some(0),
+        Range::Partial(start, _) => Some(start),
+        Range::Unsatisfiable => None,
+    }
+}
+
+pub async fn stream_file(
+    req: &HttpRequest,
+    path: String,
+    disposition: Disposition,
+) -> HttpResponse {
+    let name = path.rsplit('/').next().unwrap_or("file").to_string();
+    stream_named_file(req, path, name, disposition).await
+}
+
+/// Same as `stream_file`, for files stored under a name which isn't the one shown to the user
+pub async fn stream_named_file(
+    req: &HttpRequest,
+    path: String,
+    name: String,
+    disposition: Disposition,
+) -> HttpResponse {
+    let mut file = match afs::File::open(path.clone()).await {
+        Ok(e) => e,
+        Err(_) => return HttpResponse::BadRequest().body("Bad File"),
+    };
+    let metadata = match file.metadata().await {
+        Ok(e) if e.is_file() => e,
+        _ => return HttpResponse::BadRequest().body("Bad File"),
+    };
+
+    let length = metadata.len();
+    let (modified_time, modified, etag) = validators(&metadata);
+    let last_modified = HttpDate::from(modified_time).to_string();
+
+    if not_modified(req, &etag, modified) {
+        return HttpResponse::NotModified()
+            .header("ETag", etag)
+            .header("Last-Modified", last_modified)
+            .finish();
+    }
+
+    let (mut response, start, size) = match parse_range(req, &etag, modified, length) {
+        Range::Full => (HttpResponse::Ok(), 0, length),
+        Range::Partial(start, end) => {
+            let mut response = HttpResponse::PartialContent();
+            response.header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, length),
+            );
+            (response, start, end - start + 1)
+        }
+        Range::Unsatisfiable => {
+            return HttpResponse::RangeNotSatisfiable()
+                .header("Content-Range", format!("bytes */{}", length))
+                .finish()
+        }
+    };
+    if start > 0 && file.seek(SeekFrom::Start(start)).await.is_err() {
+        return HttpResponse::InternalServerError().body("Error");
+    }
+
+    if let Disposition::Attachment = disposition {
+        response.header(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", name),
+        );
+    }
+    response
+        .header("Access-Control-Allow-Origin", "*")
+        .header("charset", "utf-8")
+        .header("Accept-Ranges", "bytes")
+        .header("ETag", etag)
+        .header("Last-Modified", last_modified)
+        .content_type(
+            mime_guess::from_path(&name)
+                .first_or_octet_stream()
+                .to_string(),
+        )
+        .body(Body::from_message(SizedStream::new(
+            size,
+            file_stream(file, size),
+        )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    const ETAG: &str = "\"5f5e100-64\"";
+    const MODIFIED: u64 = 100_000_000;
+
+    fn request(headers: &[(&str, &str)]) -> HttpRequest {
+        let mut req = TestRequest::default();
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        req.to_http_request()
+    }
+
+    fn range(headers: &[(&str, &str)]) -> Range {
+        parse_range(&request(headers), ETAG, MODIFIED, 100)
+    }
+
+    #[test]
+    fn suffix_range() {
+        assert!(matches!(
+            range(&[("Range", "bytes=-10")]),
+            Range::Partial(90, 99)
+        ));
+        // A suffix longer than the file is the whole file
+        assert!(matches!(
+            range(&[("Range", "bytes=-500")]),
+            Range::Partial(0, 99)
+        ));
+        assert!(matches!(
+            range(&[("Range", "bytes=-0")]),
+            Range::Unsatisfiable
+        ));
+    }
+
+    #[test]
+    fn open_range() {
+        assert!(matches!(
+            range(&[("Range", "bytes=40-")]),
+            Range::Partial(40, 99)
+        ));
+        assert!(matches!(
+            range(&[("Range", "bytes=10-19")]),
+            Range::Partial(10, 19)
+        ));
+        assert!(matches!(
+            range(&[("Range", "bytes=10-500")]),
+            Range::Partial(10, 99)
+        ));
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert!(matches!(
+            range(&[("Range", "bytes=100-")]),
+            Range::Unsatisfiable
+        ));
+        assert!(matches!(
+            range(&[("Range", "bytes=150-160")]),
+            Range::Unsatisfiable
+        ));
+        // Ranges which can't be read are ignored
+        assert!(matches!(range(&[("Range", "bytes=20-10")]), Range::Full));
+        assert!(matches!(range(&[("Range", "bytes=0-1,5-6")]), Range::Full));
+        assert!(matches!(range(&[("Range", "lines=1-2")]), Range::Full));
+    }
+
+    #[test]
+    fn if_range() {
+        assert!(matches!(
+            range(&[("Range", "bytes=40-"), ("If-Range", ETAG)]),
+            Range::Partial(40, 99)
+        ));
+        // The file changed, the whole new content is sent
+        assert!(matches!(
+            range(&[("Range", "bytes=40-"), ("If-Range", "\"other\"")]),
+            Range::Full
+        ));
+        let date =
+            HttpDate::from(UNIX_EPOCH + std::time::Duration::from_secs(MODIFIED)).to_string();
+        assert!(matches!(
+            range(&[("Range", "bytes=40-"), ("If-Range", &date)]),
+            Range::Partial(40, 99)
+        ));
+        let old =
+            HttpDate::from(UNIX_EPOCH + std::time::Duration::from_secs(MODIFIED - 1)).to_string();
+        assert!(matches!(
+            range(&[("Range", "bytes=40-"), ("If-Range", &old)]),
+            Range::Full
+        ));
+    }
+
+    #[test]
+    fn etags() {
+        assert!(etag_matches(ETAG, ETAG));
+        assert!(etag_matches(&format!("\"a\", W/{}", ETAG), ETAG));
+        assert!(etag_matches("*", ETAG));
+        assert!(!etag_matches("\"a\", \"b\"", ETAG));
+    }
+
+    #[test]
+    fn conditional_get() {
+        assert!(not_modified(
+            &request(&[("If-None-Match", "*")]),
+            ETAG,
+            MODIFIED
+        ));
+        assert!(not_modified(
+            &request(&[("If-None-Match", ETAG)]),
+            ETAG,
+            MODIFIED
+        ));
+        assert!(!not_modified(
+            &request(&[("If-None-Match", "\"a\"")]),
+            ETAG,
+            MODIFIED
+        ));
+        let date =
+            HttpDate::from(UNIX_EPOCH + std::time::Duration::from_secs(MODIFIED)).to_string();
+        assert!(not_modified(
+            &request(&[("If-Modified-Since", &date)]),
+            ETAG,
+            MODIFIED
+        ));
+        assert!(!not_modified(
+            &request(&[("If-Modified-Since", &date)]),
+            ETAG,
+            MODIFIED + 1
+        ));
+        // If-None-Match wins over the date
+        assert!(!not_modified(
+            &request(&[("If-None-Match", "\"a\""), ("If-Modified-Since", &date)]),
+            ETAG,
+            MODIFIED
+        ));
+        assert!(!not_modified(&request(&[]), ETAG, MODIFIED));
+    }
+}
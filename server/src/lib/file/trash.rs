@@ -0,0 +1,55 @@
+use crate::lib::db::trash::insert::insert;
+use crate::lib::db::trash::model::{trash_folder, Trash};
+use crate::lib::db::user::token::generate_token;
+use crate::lib::file::operation::{destination, move_path, Conflict};
+use actix_web::web;
+use chrono::Utc;
+use datagn::DatabasePool;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub fn move_to_trash(path: &Path, trash: &Trash) -> io::Result<()> {
+    fs::create_dir_all(trash_folder(trash.user_id))?;
+    move_path(path, Path::new(&trash.trash_path()))
+}
+
+/// Puts the item back at its original path, or next to it if the name is taken
+pub fn restore(trash: &Trash, home: &str) -> io::Result<PathBuf> {
+    let target = Path::new(home).join(&trash.path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let target = destination(&target, &Conflict::Rename)?;
+    move_path(Path::new(&trash.trash_path()), &target)?;
+    Ok(target)
+}
+
+/// Moves the item at `full_path` to the trash of its owner and saves it. The item is put back if
+/// it can't be saved, else it couldn't be restored.
+pub async fn send_to_trash(
+    database: &mut DatabasePool,
+    owner_id: i32,
+    relative: &str,
+    full_path: &str,
+) -> io::Result<Trash> {
+    let trash = Trash {
+        uid: generate_token(),
+        user_id: owner_id,
+        path: relative.to_string(),
+        date: Utc::now().to_rfc3339(),
+    };
+    let (moved, path) = (trash.clone(), full_path.to_string());
+    web::block(move || move_to_trash(Path::new(&path), &moved))
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    if insert(database, &trash).await {
+        return Ok(trash);
+    }
+    let (from, path) = (trash.trash_path(), full_path.to_string());
+    let _ = web::block(move || move_path(Path::new(&from), Path::new(&path))).await;
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "Can't save the item in the trash",
+    ))
+}
@@ -0,0 +1,42 @@
+//! Uploads being written. A request holds its upload until it is done, so two chunks at the same
+//! offset can't both be appended.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct UploadLocks {
+    busy: Mutex<HashSet<String>>,
+}
+
+/// Given back when dropped
+pub struct UploadLock<'a> {
+    locks: &'a UploadLocks,
+    uid: String,
+}
+
+impl UploadLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// None if another request holds the upload
+    pub fn lock(&self, uid: &str) -> Option<UploadLock<'_>> {
+        let mut busy = self.busy.lock().ok()?;
+        if !busy.insert(uid.to_string()) {
+            return None;
+        }
+        Some(UploadLock {
+            locks: self,
+            uid: uid.to_string(),
+        })
+    }
+}
+
+impl Drop for UploadLock<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut busy) = self.locks.busy.lock() {
+            busy.remove(&self.uid);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::lib::db::user::token::generate_token;
+use crate::lib::db::version::get::get_uploader;
+use crate::lib::db::version::insert::{insert, set_uploader};
+use crate::lib::db::version::model::{version_folder, Version};
+use crate::lib::db::version::prune::{prune, Retention};
+use chrono::Utc;
+use datagn::DatabasePool;
+use std::io;
+
+/// `a//b/` and `/a/b` are the same file, its versions are stored under `a/b`
+pub fn relative_path(path: &str) -> String {
+    path.split('/')
+        .filter(|e| !e.is_empty() && *e != ".")
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// Records `uploader` as the author of the content just written at `relative`
+pub async fn record_uploader(
+    database: &mut DatabasePool,
+    user_id: i32,
+    relative: &str,
+    uploader: i32,
+) -> bool {
+    set_uploader(database, user_id, relative_path(relative), uploader).await
+}
+
+/// Moves the current content of `full_path` to the versions before it is overwritten.
+/// Nothing is done if there is no file yet.
+pub async fn keep_previous(
+    database: &mut DatabasePool,
+    user_id: i32,
+    relative: String,
+    full_path: &str,
+    retention: Retention,
+) -> io::Result<()> {
+    let metadata = match async_std::fs::metadata(full_path).await {
+        Ok(e) if e.is_file() => e,
+        _ => return Ok(()),
+    };
+    let path = relative_path(&relative);
+    // A file written before the uploaders were recorded is credited to its owner
+    let uploader = get_uploader(database, user_id, path.clone())
+        .await
+        .unwrap_or(user_id);
+    let version = Version {
+        uid: generate_token(),
+        user_id,
+        path,
+        size: metadata.len() as i64,
+        uploader,
+        date: Utc::now().to_rfc3339(),
+    };
+
+    async_std::fs::create_dir_all(version_folder(user_id)).await?;
+    if async_std::fs::rename(full_path, version.version_path())
+        .await
+        .is_err()
+    {
+        async_std::fs::copy(full_path, version.version_path()).await?;
+    }
+    if !insert(database, &version).await {
+        let _ = async_std::fs::remove_file(version.version_path()).await;
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Can't save the version",
+        ));
+    }
+    prune(database, user_id, version.path, retention).await;
+    Ok(())
+}
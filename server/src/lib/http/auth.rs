@@ -0,0 +1,207 @@
+//! Authentication of a request with the token of a session.
+//!
+//! A handler which takes `Auth` is only called with a valid session, else the request is
+//! answered with a 401 and the reason.
+
+use crate::lib::db::user::get::get_user_by_token;
+use crate::lib::db::user::model::User;
+use crate::lib::db::user::valid_session::token_of;
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse, ResponseError};
+use datagn::DatabasePool;
+use futures::future::LocalBoxFuture;
+use std::fmt;
+
+/// The user of the session
+pub struct Auth(pub User);
+
+#[derive(Debug)]
+pub enum AuthError {
+    /// No `token` header nor `?token=`
+    MissingToken,
+    /// Unknown or expired session, or disabled account
+    InvalidToken,
+    /// The database is not given to the app
+    NoDatabase,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingToken => write!(f, "A token is required"),
+            AuthError::InvalidToken => write!(f, "Error on token"),
+            AuthError::NoDatabase => write!(f, "Can't get user"),
+        }
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::MissingToken | AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthError::NoDatabase => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).body(self.to_string())
+    }
+}
+
+impl FromRequest for Auth {
+    type Error = AuthError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let token = token_of(req);
+        let database = req
+            .app_data::<web::Data<DatabasePool>>()
+            .map(|e| e.get_ref().clone());
+        Box::pin(async move {
+            let mut database = database.ok_or(AuthError::NoDatabase)?;
+            if token.is_empty() {
+                return Err(AuthError::MissingToken);
+            }
+            get_user_by_token(&mut database, token)
+                .await
+                .map(Auth)
+                .ok_or(AuthError::InvalidToken)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::config::Config;
+    use crate::lib::db::create_db;
+    use crate::lib::db::session::insert::insert;
+    use crate::lib::db::session::model::{hash_token, Session};
+    use crate::lib::db::user::get::get_user_by_name;
+    use crate::lib::db::user::insert::insert_user;
+    use crate::lib::db::user::token::generate_token;
+    use crate::lib::db::user::HashCost;
+    use crate::lib::default::default_config;
+    use actix_web::test::TestRequest;
+    use chrono::{Duration, Utc};
+
+    /// A database of its own in the temporary folder, the working directory is left alone
+    async fn database() -> DatabasePool {
+        let path = std::env::temp_dir().join(format!("opencloud-auth-{}.sql", generate_token()));
+        std::fs::File::create(&path).expect("Can't create the database");
+        let config = Config {
+            db_ip: path.to_string_lossy().to_string(),
+            ..default_config()
+        };
+        let mut database = config.get_db_config().to_datapool().await;
+        create_db(&mut database).await;
+        database
+    }
+
+    /// A new user with a session which expires in `days`, and the token of the session
+    async fn user_with_session(database: &mut DatabasePool, days: i64) -> (User, String) {
+        let name = generate_token();
+        let cost = HashCost {
+            memory_kib: 8,
+            iterations: 1,
+            parallelism: 1,
+        };
+        insert_user(
+            database,
+            name.clone(),
+            String::new(),
+            generate_token(),
+            cost,
+        )
+        .await
+        .expect("Can't insert the user");
+        let user = get_user_by_name(database, name)
+            .await
+            .expect("Can't get the user");
+        let token = generate_token();
+        let now = Utc::now();
+        let session = Session {
+            id: 0,
+            user_id: user.id.expect("The user has no id"),
+            token_hash: hash_token(&token),
+            created: now.to_rfc3339(),
+            last_seen: now.to_rfc3339(),
+            expire: (now + Duration::days(days)).to_rfc3339(),
+            user_agent: None,
+            ip: None,
+        };
+        assert!(insert(database, &session).await);
+        (user, token)
+    }
+
+    async fn auth(database: DatabasePool, token: Option<&str>) -> Result<Auth, AuthError> {
+        let mut req = TestRequest::default().data(database);
+        if let Some(token) = token {
+            req = req.header("token", token);
+        }
+        Auth::from_request(&req.to_http_request(), &mut Payload::None).await
+    }
+
+    fn status(result: Result<Auth, AuthError>) -> StatusCode {
+        match result {
+            Ok(_) => StatusCode::OK,
+            Err(e) => e.error_response().status(),
+        }
+    }
+
+    #[test]
+    fn missing_token() {
+        actix_web::rt::System::new("test").block_on(async {
+            let database = database().await;
+            assert_eq!(
+                status(auth(database.clone(), None).await),
+                StatusCode::UNAUTHORIZED
+            );
+            assert_eq!(
+                status(auth(database, Some("")).await),
+                StatusCode::UNAUTHORIZED
+            );
+        });
+    }
+
+    #[test]
+    fn unknown_token() {
+        actix_web::rt::System::new("test").block_on(async {
+            let database = database().await;
+            let token = generate_token();
+            assert_eq!(
+                status(auth(database, Some(&token)).await),
+                StatusCode::UNAUTHORIZED
+            );
+        });
+    }
+
+    #[test]
+    fn expired_session() {
+        actix_web::rt::System::new("test").block_on(async {
+            let mut database = database().await;
+            let (_, token) = user_with_session(&mut database, -1).await;
+            assert_eq!(
+                status(auth(database, Some(&token)).await),
+                StatusCode::UNAUTHORIZED
+            );
+        });
+    }
+
+    #[test]
+    fn valid_session() {
+        actix_web::rt::System::new("test").block_on(async {
+            let mut database = database().await;
+            let (user, token) = user_with_session(&mut database, 1).await;
+            match auth(database, Some(&token)).await {
+                Ok(Auth(e)) => {
+                    assert_eq!(e.id, user.id);
+                    assert_eq!(e.name, user.name);
+                }
+                Err(e) => panic!("The session is refused: {}", e),
+            }
+        });
+    }
+}
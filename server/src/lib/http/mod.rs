@@ -1,3 +1,7 @@
+pub mod auth;
+pub mod role;
+pub mod throttle;
+
 use actix_web::HttpRequest;
 use std::collections::BTreeMap;
 
@@ -0,0 +1,102 @@
+//! Middleware which only lets the users with a role through.
+//!
+//! `web::scope("/admin").wrap(RequireRole::new(Role::Admin))` protects every route of the scope.
+//! The user is stored in the extensions of the request, so the handlers don't have to get it again.
+
+use crate::lib::db::user::model::Role;
+use crate::lib::db::user::valid_session::from_headers_get_user;
+use crate::lib::http::throttle::any_body;
+use actix_web::body::{Body, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::ErrorInternalServerError;
+use actix_web::{web, Error, HttpResponse};
+use datagn::DatabasePool;
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+pub struct RequireRole {
+    role: Role,
+}
+
+impl RequireRole {
+    pub fn new(role: Role) -> Self {
+        Self { role }
+    }
+}
+
+impl<S, B> Transform<S> for RequireRole
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + Unpin + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequireRoleMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequireRoleMiddleware {
+            service: Rc::new(RefCell::new(service)),
+            role: self.role,
+        })
+    }
+}
+
+pub struct RequireRoleMiddleware<S> {
+    service: Rc<RefCell<S>>,
+    role: Role,
+}
+
+impl<S, B> Service for RequireRoleMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + Unpin + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let role = self.role;
+        Box::pin(async move {
+            // An error would be answered as a 500 by the middlewares of the app
+            let mut database = match req.app_data::<web::Data<DatabasePool>>() {
+                Some(e) => e.get_ref().clone(),
+                None => {
+                    let res = HttpResponse::InternalServerError().body("Can't get user");
+                    return Ok(req.into_response(res));
+                }
+            };
+            // The request is taken apart to be read, it can only be rebuilt once the copy is dropped
+            let (request, payload) = req.into_parts();
+            let res = match from_headers_get_user(&mut database, request.clone()).await {
+                None => HttpResponse::Unauthorized().body("Error on token"),
+                Some(e) if e.role < role => {
+                    HttpResponse::Forbidden().body("You don't have the rights to do this")
+                }
+                Some(e) => {
+                    request.extensions_mut().insert(e);
+                    let req = match ServiceRequest::from_parts(request, payload) {
+                        Ok(e) => e,
+                        Err(_) => return Err(ErrorInternalServerError("Error on request")),
+                    };
+                    let future = service.borrow_mut().call(req);
+                    return future.await.map(any_body);
+                }
+            };
+            Ok(ServiceResponse::new(request, res))
+        })
+    }
+}
@@ -0,0 +1,275 @@
+//! Limits on the number of requests, against the guessing of passwords and the flooding of the API.
+//!
+//! `LoginThrottle` counts the attempts of a key (an IP or an account) and locks it for a while
+//! once it has too many of them. `RateLimit` is a token bucket per IP wrapped on the `/api`
+//! scope: a request takes a token, the tokens come back at a fixed rate. Both answer a 429 with
+//! the number of seconds to wait in `Retry-After`.
+
+use crate::lib::config::Config;
+use actix_web::body::{Body, MessageBody, ResponseBody};
+use actix_web::dev::{ConnectionInfo, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::StatusCode;
+use actix_web::{web, Error, HttpRequest, HttpResponse, ResponseError};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Answer of a limited request, with the number of seconds to wait
+#[derive(Debug)]
+pub struct TooManyRequests(pub u64);
+
+impl fmt::Display for TooManyRequests {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Too many requests, retry in {} seconds", self.0)
+    }
+}
+
+impl ResponseError for TooManyRequests {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::TooManyRequests()
+            .header("Retry-After", self.0.to_string())
+            .body(self.to_string())
+    }
+}
+
+/// The answer of a middleware has one type of body, whether it is its own or the one of the
+/// service
+pub fn any_body<B: MessageBody + Unpin + 'static>(
+    res: ServiceResponse<B>,
+) -> ServiceResponse<Body> {
+    res.map_body(|_, body| ResponseBody::Other(Body::from_message(body)))
+}
+
+/// The address of the peer has a port, which changes with each connection
+fn without_port(address: Option<&str>) -> String {
+    match address {
+        Some(e) => e
+            .parse::<SocketAddr>()
+            .map(|e| e.ip().to_string())
+            .unwrap_or_else(|_| e.to_string()),
+        None => String::new(),
+    }
+}
+
+/// IP of the client. The one of `X-Forwarded-For` is only taken when the peer is a trusted
+/// proxy, else it is the IP of the peer.
+fn client_ip(peer: Option<SocketAddr>, info: &ConnectionInfo, trusted: &[String]) -> String {
+    let peer = match peer {
+        Some(e) => e.ip().to_string(),
+        None => return String::new(),
+    };
+    if trusted.contains(&peer) {
+        without_port(info.realip_remote_addr())
+    } else {
+        peer
+    }
+}
+
+pub fn ip_of(req: &HttpRequest) -> String {
+    let trusted = req
+        .app_data::<web::Data<Config>>()
+        .map(|e| e.trusted_proxies.as_slice())
+        .unwrap_or_default();
+    client_ip(req.peer_addr(), &req.connection_info(), trusted)
+}
+
+struct Attempts {
+    count: u32,
+    first: Instant,
+    locked_until: Option<Instant>,
+}
+
+pub struct LoginThrottle {
+    /// Attempts are forgotten after this time
+    window: Duration,
+    /// Time a key stays locked
+    lockout: Duration,
+    attempts: Mutex<HashMap<String, Attempts>>,
+}
+
+impl LoginThrottle {
+    pub fn new(window_secs: u64, lockout_secs: u64) -> Self {
+        Self {
+            window: Duration::from_secs(window_secs),
+            lockout: Duration::from_secs(lockout_secs),
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seconds before the key can try again, if it is locked
+    pub fn retry_after(&self, key: &str) -> Option<u64> {
+        let attempts = self.attempts.lock().ok()?;
+        let until = attempts.get(key)?.locked_until?;
+        let now = Instant::now();
+        if until > now {
+            Some((until - now).as_secs().max(1))
+        } else {
+            None
+        }
+    }
+
+    /// Counts an attempt, the key is locked when it reaches `limit` in the window. Returns true
+    /// if this attempt locked it. A limit of 0 never locks.
+    pub fn hit(&self, key: &str, limit: u32) -> bool {
+        if limit == 0 {
+            return false;
+        }
+        let mut attempts = match self.attempts.lock() {
+            Ok(e) => e,
+            Err(_) => return false,
+        };
+        let now = Instant::now();
+        let (window, lockout) = (self.window, self.lockout);
+        attempts.retain(|_, e| {
+            now.duration_since(e.first) < window || matches!(e.locked_until, Some(u) if u > now)
+        });
+
+        let entry = attempts.entry(key.to_string()).or_insert(Attempts {
+            count: 0,
+            first: now,
+            locked_until: None,
+        });
+        if matches!(entry.locked_until, Some(u) if u <= now) {
+            // The lockout is over, the counting starts again
+            entry.count = 0;
+            entry.first = now;
+            entry.locked_until = None;
+        }
+        entry.count += 1;
+        if entry.count >= limit && entry.locked_until.is_none() {
+            // A lockout too long for the clock locks until the restart
+            entry.locked_until = Some(
+                now.checked_add(lockout)
+                    .unwrap_or_else(|| now + Duration::from_secs(u32::MAX.into())),
+            );
+            return true;
+        }
+        false
+    }
+
+    /// Forgets the attempts of the key, after a successful login
+    pub fn clear(&self, key: &str) {
+        if let Ok(mut attempts) = self.attempts.lock() {
+            attempts.remove(key);
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per IP: `burst` requests at once, then `rate` requests per second. A rate of 0
+/// disables it.
+#[derive(Clone)]
+pub struct RateLimit {
+    rate: f64,
+    burst: f64,
+    trusted: Arc<Vec<String>>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimit {
+    pub fn new(rate: u32, burst: u32, trusted: Vec<String>) -> Self {
+        Self {
+            rate: f64::from(rate),
+            burst: f64::from(burst.max(1)),
+            trusted: Arc::new(trusted),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Takes a token of the IP, or gives the seconds before the next one
+    fn take(&self, ip: &str) -> Result<(), u64> {
+        if self.rate <= 0.0 {
+            return Ok(());
+        }
+        let mut buckets = match self.buckets.lock() {
+            Ok(e) => e,
+            Err(_) => return Ok(()),
+        };
+        let now = Instant::now();
+        let (rate, burst) = (self.rate, self.burst);
+        // A full bucket is the same as no bucket
+        buckets
+            .retain(|_, e| e.tokens + now.duration_since(e.updated).as_secs_f64() * rate < burst);
+
+        let bucket = buckets.entry(ip.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        bucket.tokens =
+            (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rate).ceil() as u64)
+        }
+    }
+}
+
+impl<S, B> Transform<S> for RateLimit
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + Unpin + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitMiddleware {
+            service: Rc::new(RefCell::new(service)),
+            limit: self.clone(),
+        })
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<RefCell<S>>,
+    limit: RateLimit,
+}
+
+impl<S, B> Service for RateLimitMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + Unpin + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let ip = client_ip(req.peer_addr(), &req.connection_info(), &self.limit.trusted);
+        if let Err(seconds) = self.limit.take(&ip) {
+            // An error would be answered as a 500 by the middlewares of the app
+            let res = req.into_response(TooManyRequests(seconds).error_response());
+            return Box::pin(async move { Ok(res) });
+        }
+        let future = self.service.borrow_mut().call(req);
+        Box::pin(async move { future.await.map(any_body) })
+    }
+}
@@ -4,3 +4,4 @@ pub mod db;
 pub mod default;
 pub mod file;
 pub mod http;
+pub mod totp;
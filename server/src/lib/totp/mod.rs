@@ -0,0 +1,193 @@
+//! Time-based one-time passwords (RFC 6238) used as second factor at login.
+//!
+//! The codes are the ones of the authenticator apps: HMAC-SHA1, 6 digits and a new code every
+//! 30 seconds. A code of the previous or of the next step is accepted for the clock drift, and a
+//! step can only be used once.
+
+use crate::lib::db::recovery::delete::use_code;
+use crate::lib::db::user::model::User;
+use crate::lib::db::user::update::set_totp_step;
+use base32::Alphabet;
+use datagn::DatabasePool;
+use hmac::{Hmac, Mac, NewMac};
+use qrcode::render::svg;
+use qrcode::QrCode;
+use rand::Rng;
+use sha1::Sha1;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const ISSUER: &str = "OpenCloud";
+const STEP: u64 = 30;
+const DIGITS: u32 = 6;
+const ALPHABET: Alphabet = Alphabet::RFC4648 { padding: false };
+
+/// Secret shared with the authenticator, in base32
+pub fn generate_secret() -> String {
+    let bytes: [u8; 20] = rand::thread_rng().gen();
+    base32::encode(ALPHABET, &bytes)
+}
+
+/// Codes given once to log in without the authenticator
+pub fn generate_recovery_codes() -> Vec<String> {
+    let charset: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..10)
+        .map(|_| {
+            let code: String = (0..10)
+                .map(|_| charset[rng.gen_range(0..charset.len())] as char)
+                .collect();
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect()
+}
+
+fn code_at(key: &[u8], step: u64) -> Option<u32> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).ok()?;
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    Some(value % 10u32.pow(DIGITS))
+}
+
+fn current_step() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|e| e.as_secs() / STEP)
+        .unwrap_or_default()
+}
+
+/// Step of the code if it is valid and newer than `last_step`
+pub fn verify(secret: &str, code: &str, last_step: Option<i64>) -> Option<i64> {
+    verify_at(secret, code, last_step, current_step())
+}
+
+fn verify_at(secret: &str, code: &str, last_step: Option<i64>, now: u64) -> Option<i64> {
+    let key = base32::decode(ALPHABET, secret)?;
+    let code: String = code.chars().filter(|e| !e.is_whitespace()).collect();
+    if code.len() != DIGITS as usize {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    (now.saturating_sub(1)..=now + 1)
+        .filter(|step| Some(*step as i64) > last_step)
+        .find(|step| code_at(&key, *step) == Some(code))
+        .map(|step| step as i64)
+}
+
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|e| {
+            if e.is_ascii_alphanumeric() || b"-._~".contains(&e) {
+                (e as char).to_string()
+            } else {
+                format!("%{:02X}", e)
+            }
+        })
+        .collect()
+}
+
+/// URI read by the authenticator apps
+pub fn provisioning_uri(name: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{name}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = encode(ISSUER),
+        name = encode(name),
+        secret = secret,
+        digits = DIGITS,
+        period = STEP,
+    )
+}
+
+/// QR code of the URI, as an SVG image
+pub fn qr_svg(uri: &str) -> Option<String> {
+    let code = QrCode::new(uri.as_bytes()).ok()?;
+    Some(code.render::<svg::Color>().min_dimensions(200, 200).build())
+}
+
+/// Checks a code of the authenticator or a recovery code, which can't be used again
+pub async fn check_second_factor(database: &mut DatabasePool, user: &User, code: &str) -> bool {
+    let id = user.id.unwrap_or_default();
+    if let Some(secret) = &user.totp_secret {
+        if let Some(step) = verify(secret, code, user.totp_step) {
+            return set_totp_step(database, id, step).await;
+        }
+    }
+    use_code(database, id, code.trim()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Key of the test vectors of RFC 6238
+    const KEY: &[u8] = b"12345678901234567890";
+
+    fn code(step: u64) -> String {
+        format!("{:06}", code_at(KEY, step).expect("Bad key"))
+    }
+
+    #[test]
+    fn rfc_6238() {
+        // The last 6 digits of the SHA-1 vectors
+        let vectors = [
+            (59, 287_082),
+            (1_111_111_109, 81_804),
+            (1_111_111_111, 50_471),
+            (1_234_567_890, 5_924),
+            (2_000_000_000, 279_037),
+            (20_000_000_000, 353_130),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(code_at(KEY, time / STEP), Some(expected), "at {}", time);
+        }
+    }
+
+    #[test]
+    fn drift() {
+        let secret = base32::encode(ALPHABET, KEY);
+        let now = 1_000;
+        assert_eq!(verify_at(&secret, &code(now), None, now), Some(now as i64));
+        assert_eq!(
+            verify_at(&secret, &code(now - 1), None, now),
+            Some(now as i64 - 1)
+        );
+        assert_eq!(
+            verify_at(&secret, &code(now + 1), None, now),
+            Some(now as i64 + 1)
+        );
+        assert_eq!(verify_at(&secret, &code(now - 2), None, now), None);
+        assert_eq!(verify_at(&secret, &code(now + 2), None, now), None);
+        assert_eq!(verify_at(&secret, "12345", None, now), None);
+    }
+
+    #[test]
+    fn replay() {
+        let secret = base32::encode(ALPHABET, KEY);
+        let now = 1_000;
+        // A used step and the older ones are refused
+        assert_eq!(verify_at(&secret, &code(now), Some(now as i64), now), None);
+        assert_eq!(
+            verify_at(&secret, &code(now - 1), Some(now as i64 - 1), now),
+            None
+        );
+        assert_eq!(
+            verify_at(&secret, &code(now - 1), Some(now as i64), now),
+            None
+        );
+        assert_eq!(
+            verify_at(&secret, &code(now), Some(now as i64 - 1), now),
+            Some(now as i64)
+        );
+        assert_eq!(
+            verify_at(&secret, &code(now + 1), Some(now as i64), now),
+            Some(now as i64 + 1)
+        );
+    }
+}
@@ -1,11 +1,41 @@
 use crate::http_handler::{
+    admin::{
+        add_group_member, create_group, create_user as admin_create_user, delete_group,
+        delete_user, disable_user, download_log_archive, enable_user, get_stats, list_groups,
+        list_log_archives, list_logs, list_users, remove_group_member, reset_user_password,
+        reset_user_totp, set_user_role, update_group,
+    },
     default::{default_404, default_api_handler, p500},
-    files::{delete_file, get_files, save_file},
-    users::{create_user, login_user},
+    files::{
+        copy_file, create_folder, delete_file, extract_archive, get_archive, get_files, move_file,
+        save_file,
+    },
+    logs::my_logs,
+    permission::{grant_permission, list_permissions, revoke_permission},
+    sessions::{list_sessions, logout, logout_everywhere, revoke_session},
+    share::{create_share, list_shares, public_file, public_upload, revoke_share},
+    totp::{confirm_totp, remove_totp, setup_totp},
+    trash::{delete_from_trash, empty_trash, list_trash, restore_trash},
+    upload::{abort_upload, append_upload, create_upload, finish_upload, upload_status},
+    users::{change_password, create_user, get_profile, get_usage, login_user},
+    version::{
+        delete_one_version, download_version, list_versions, prune_versions, restore_version,
+    },
 };
+use crate::lib::config::args::{Args, Command, USAGE};
 use crate::lib::config::Config;
 use crate::lib::db::create_db;
-use crate::lib::default::default;
+use crate::lib::db::log::retention::retention_task;
+use crate::lib::db::session::purge::purge_task as purge_sessions;
+use crate::lib::db::trash::purge::purge_task;
+use crate::lib::db::upload::purge::purge_task as purge_uploads;
+use crate::lib::db::user::model::Role;
+use crate::lib::db::user::update::ensure_admin;
+use crate::lib::db::version::prune::prune_task;
+use crate::lib::default::{check_config, default};
+use crate::lib::file::upload::UploadLocks;
+use crate::lib::http::role::RequireRole;
+use crate::lib::http::throttle::{LoginThrottle, RateLimit};
 use actix_web::{dev::Service, middleware::errhandlers::ErrorHandlers};
 use actix_web::{http, web, App, HttpServer};
 use lib::file::default::{bulma, file_svg, folder_svg, indexhtml, wasm, wasmloader};
@@ -16,9 +46,25 @@ mod lib;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let config: Config = default();
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2)
+        }
+    };
+    match args.command {
+        Command::Help => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Command::CheckConfig => std::process::exit(check_config(&args)),
+        Command::Serve => {}
+    }
+    let config: Config = default(&args);
     let mut database = config.get_db_config().to_datapool().await;
     create_db(&mut database).await;
+    ensure_admin(&mut database, config.admin.clone()).await;
     let server_ip: &str = &config.get_server();
 
     if cfg!(feature = "log") {
@@ -30,6 +76,28 @@ async fn main() -> std::io::Result<()> {
         println!("Server running");
     }
 
+    actix_web::rt::spawn(purge_task(database.clone(), config.trash_retention_days));
+    actix_web::rt::spawn(retention_task(database.clone(), config.log_retention()));
+    let uploads = web::Data::new(UploadLocks::new());
+    actix_web::rt::spawn(purge_uploads(
+        database.clone(),
+        uploads.clone(),
+        config.upload_expire_days,
+    ));
+    actix_web::rt::spawn(purge_sessions(database.clone()));
+    actix_web::rt::spawn(prune_task(database.clone(), config.version_retention()));
+
+    let throttle = web::Data::new(LoginThrottle::new(
+        config.login_window_secs,
+        config.lockout_secs,
+    ));
+    let rate_limit = RateLimit::new(
+        config.api_rate,
+        config.api_burst,
+        config.trusted_proxies.clone(),
+    );
+    let config = web::Data::new(config);
+
     HttpServer::new(move || {
         App::new()
             .default_service(web::to(indexhtml))
@@ -44,25 +112,88 @@ async fn main() -> std::io::Result<()> {
             )
             .service(
                 web::scope("/api")
+                    .wrap(rate_limit.clone())
                     .default_service(web::to(default_api_handler))
                     .service(get_files)
                     .service(save_file)
                     .service(delete_file)
+                    .service(get_archive)
+                    .service(extract_archive)
+                    .service(move_file)
+                    .service(copy_file)
+                    .service(create_folder)
+                    .service(list_trash)
+                    .service(restore_trash)
+                    .service(delete_from_trash)
+                    .service(empty_trash)
+                    .service(list_versions)
+                    .service(download_version)
+                    .service(restore_version)
+                    .service(delete_one_version)
+                    .service(prune_versions)
+                    .service(create_share)
+                    .service(list_shares)
+                    .service(revoke_share)
+                    .service(public_file)
+                    .service(public_upload)
+                    .service(grant_permission)
+                    .service(list_permissions)
+                    .service(revoke_permission)
+                    .service(create_upload)
+                    .service(upload_status)
+                    .service(append_upload)
+                    .service(finish_upload)
+                    .service(abort_upload)
                     .service(create_user)
-                    .service(login_user),
+                    .service(login_user)
+                    .service(change_password)
+                    .service(get_usage)
+                    .service(get_profile)
+                    .service(my_logs)
+                    .service(list_sessions)
+                    .service(revoke_session)
+                    .service(logout)
+                    .service(logout_everywhere)
+                    .service(setup_totp)
+                    .service(confirm_totp)
+                    .service(remove_totp)
+                    .service(
+                        web::scope("/admin")
+                            .wrap(RequireRole::new(Role::Admin))
+                            .service(list_users)
+                            .service(list_logs)
+                            .service(list_log_archives)
+                            .service(download_log_archive)
+                            .service(get_stats)
+                            .service(admin_create_user)
+                            .service(disable_user)
+                            .service(enable_user)
+                            .service(reset_user_password)
+                            .service(reset_user_totp)
+                            .service(delete_user)
+                            .service(list_groups)
+                            .service(create_group)
+                            .service(update_group)
+                            .service(delete_group)
+                            .service(add_group_member)
+                            .service(remove_group_member)
+                            .service(set_user_role),
+                    ),
             )
             .data(database.clone())
+            .app_data(config.clone())
+            .app_data(throttle.clone())
+            .app_data(uploads.clone())
             .wrap_fn(|req, srv| {
                 let fut = srv.call(req);
                 async move {
-                    let res = if let Ok(e) = fut.await {
-                        e
-                    } else {
-                        error("error");
-                        return Err(actix_web::Error::from(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "Error",
-                        )));
+                    let res = match fut.await {
+                        Ok(e) => e,
+                        Err(e) => {
+                            // The error keeps its status, a 401 or a 429 isn't a 500
+                            error(format!("error: {}", e));
+                            return Err(e);
+                        }
                     };
                     let e = res.request();
                     if cfg!(feature = "log") {
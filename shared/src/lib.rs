@@ -47,3 +47,245 @@ pub enum FType {
     Error,
     Unset,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchiveRequest {
+    pub paths: Vec<String>,
+    pub format: String,
+    pub compression: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExtractRequest {
+    pub target: String,
+    pub entries: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MoveRequest {
+    pub to: String,
+    pub conflict: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashItem {
+    pub id: String,
+    pub path: String,
+    pub date: String,
+    pub folder: bool,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VersionItem {
+    pub id: String,
+    pub date: String,
+    pub size: u64,
+    pub uploader: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StorageUsage {
+    pub bytes: u64,
+    pub files: u64,
+    pub quota_bytes: Option<u64>,
+    pub quota_files: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct NewShare {
+    /// `download` (default) or `upload` for a drop box
+    pub kind: Option<String>,
+    pub password: Option<String>,
+    /// RFC 3339
+    pub expire: Option<String>,
+    pub max_downloads: Option<i64>,
+    /// Maximum size of a file sent to a drop box
+    pub max_size: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShareItem {
+    pub slug: String,
+    pub path: String,
+    pub kind: String,
+    pub password: bool,
+    pub expire: Option<String>,
+    pub max_downloads: Option<i64>,
+    pub downloads: i64,
+    pub max_size: Option<i64>,
+    pub date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewPermission {
+    /// Name of the user who receives the folder
+    pub user: Option<String>,
+    /// Name of the group which receives the folder, instead of a user
+    pub group: Option<String>,
+    /// `read` or `write`
+    pub access: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PermissionItem {
+    pub id: i32,
+    pub path: String,
+    /// `user` or `group`
+    pub grantee_type: String,
+    /// Name of the user or of the group which received the folder
+    pub grantee: String,
+    pub access: String,
+    pub date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewGroup {
+    pub name: String,
+    /// Quota of the members who don't have their own, a negative value means unlimited
+    pub quota_bytes: Option<i64>,
+    pub quota_files: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GroupItem {
+    pub id: i32,
+    pub name: String,
+    pub members: Vec<String>,
+    pub quota_bytes: Option<i64>,
+    pub quota_files: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoleRequest {
+    /// `user` or `admin`
+    pub role: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserItem {
+    pub id: i32,
+    pub name: String,
+    pub email: Option<String>,
+    /// `user` or `admin`
+    pub role: String,
+    pub disabled: bool,
+    /// The user must change their password at the next login
+    pub must_reset: bool,
+    /// RFC 3339
+    pub last_login: Option<String>,
+    /// Two-factor authentication is enabled
+    pub totp: bool,
+    pub bytes: u64,
+    pub files: u64,
+    pub quota_bytes: Option<u64>,
+    pub quota_files: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewUser {
+    pub name: String,
+    pub email: Option<String>,
+    pub password: String,
+    /// `user` (default) or `admin`
+    pub role: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Login {
+    pub name: String,
+    pub password: String,
+    /// Code of the authenticator or recovery code, when two-factor authentication is enabled
+    pub code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PasswordChange {
+    pub name: String,
+    pub password: String,
+    pub new_password: String,
+    /// Code of the authenticator or recovery code, when two-factor authentication is enabled
+    pub code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    /// `user` or `admin`
+    pub role: String,
+    /// Two-factor authentication is enabled
+    pub totp: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogItem {
+    pub id: i32,
+    pub action: String,
+    /// Name of the user who acted
+    pub user: String,
+    /// Name of the owner of the file
+    pub owner: String,
+    /// RFC 3339 in UTC
+    pub date: String,
+    /// File of the action, relative to the home of the owner
+    pub path: Option<String>,
+    /// Target of a move, a copy or an extraction
+    pub destination: Option<String>,
+    pub bytes: Option<i64>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogArchive {
+    /// `log-<last id>-<date>.jsonl.gz`, one entry of the log per line
+    pub name: String,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogPage {
+    pub items: Vec<LogItem>,
+    /// Cursor of the next page, to send as `?before=`
+    pub next: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstanceStats {
+    pub users: u64,
+    pub disabled_users: u64,
+    pub groups: u64,
+    /// Homes of every user
+    pub bytes: u64,
+    pub files: u64,
+    pub trash_bytes: u64,
+    pub versions_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionItem {
+    pub id: i32,
+    /// The session of the request
+    pub current: bool,
+    pub created: String,
+    pub last_seen: String,
+    pub expire: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TotpSetup {
+    /// Base32 secret, to type it when the QR code can't be scanned
+    pub secret: String,
+    /// `otpauth://` URI
+    pub uri: String,
+    /// QR code of the URI, as an SVG image
+    pub qr: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TotpCode {
+    pub code: String,
+}